@@ -0,0 +1,273 @@
+//! Measures the most performance critical systems on generated universes with an increasing amount of ships,
+//! so we can track progress towards the performance goal in `docs/todo.md`.
+//!
+//! Simulation systems are timed right where they run within their schedule, so measuring them doesn't change
+//! the universe. Systems which don't modify anything (like pathfinding) are run on their own in between ticks.
+//!
+//! Usage: `cargo bench -- [--max-ships <amount>] [--save-baseline <file>] [--baseline <file>]`
+//! Comparing against a baseline reports every system which got noticeably slower.
+
+use bevy::ecs::schedule::IntoSystemSet;
+use bevy::prelude::{App, Entity, FixedUpdate, IntoSystemConfigs, Query, ResMut, Resource, State};
+use bevy_space::components::Sector;
+use bevy_space::game_data::GameData;
+use bevy_space::headless::HeadlessPlugin;
+use bevy_space::pathfinding;
+use bevy_space::persistence::universe_generator::UniverseGenerator;
+use bevy_space::persistence::UniverseSaveDataLoadingPlugin;
+use bevy_space::session_data::SessionData;
+use bevy_space::simulation::determinism::DeterministicSimulationPlugin;
+use bevy_space::simulation::plugin::SimulationPlugin;
+use bevy_space::simulation::prelude::{
+    check_if_production_is_finished_and_start_new_one,
+    make_asteroids_disappear_when_they_leave_sector, respawn_asteroids, MoveToEntity,
+};
+use bevy_space::simulation::ship_ai::behaviors::auto_trade;
+use bevy_space::simulation::transform::simulation_transform::SimulationTransform;
+use bevy_space::states::{ApplicationState, StatePlugin};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const SHIP_COUNTS: [u32; 4] = [1_000, 10_000, 100_000, 1_000_000];
+/// Universes grow alongside the ship count, so ships don't just end up piling up in a couple of sectors.
+const SHIPS_PER_SECTOR: u32 = 1000;
+const SEED: u64 = 42;
+
+/// Loading happens in batches, which takes quite a few frames for the larger universes.
+const MAX_LOADING_UPDATES: usize = 10_000;
+/// Gives ships some time to spread out and production to get going before anything is measured.
+const WARM_UP_TICKS: usize = 300;
+const MEASURED_TICKS: usize = 100;
+
+/// Median timings above the baseline multiplied by this factor are reported as regressions.
+const REGRESSION_THRESHOLD: f64 = 1.15;
+
+type Baseline = BTreeMap<String, f64>;
+
+fn main() {
+    let max_ships = get_argument("--max-ships").map_or(u32::MAX, |x| {
+        x.parse()
+            .unwrap_or_else(|e| panic!("Invalid ship count {x}: {e}"))
+    });
+
+    let mut results = Baseline::new();
+    for ship_count in SHIP_COUNTS.into_iter().filter(|x| x <= &max_ships) {
+        println!("\n{ship_count} ships");
+        for (name, timings) in measure(ship_count) {
+            let median = median_micros(&timings);
+            println!(
+                "  {name:<60} median {median:>10.1}µs   max {:>10.1}µs",
+                timings.iter().max().unwrap().as_secs_f64() * 1_000_000.0
+            );
+            results.insert(format!("{name}/{ship_count}"), median);
+        }
+    }
+
+    if let Some(path) = get_argument("--baseline") {
+        compare_with_baseline(&results, &read_baseline(Path::new(&path)));
+    }
+
+    if let Some(path) = get_argument("--save-baseline") {
+        let content = serde_json::to_string_pretty(&results).unwrap();
+        std::fs::write(&path, content)
+            .unwrap_or_else(|e| panic!("Unable to write baseline {path}: {e}"));
+    }
+}
+
+/// Durations of the simulation systems in [add_timed_systems], collected while [SystemTimings::measuring].
+#[derive(Resource, Default)]
+struct SystemTimings {
+    measuring: bool,
+    names: Vec<&'static str>,
+    started: Vec<Option<Instant>>,
+    timings: Vec<Vec<Duration>>,
+}
+
+impl SystemTimings {
+    fn start(&mut self, index: usize) {
+        if self.measuring {
+            self.started[index] = Some(Instant::now());
+        }
+    }
+
+    fn stop(&mut self, index: usize) {
+        if let Some(started) = self.started[index].take() {
+            self.timings[index].push(started.elapsed());
+        }
+    }
+}
+
+/// Returns the timings for every benchmarked system, one entry per measured tick.
+fn measure(ship_count: u32) -> Vec<(&'static str, Vec<Duration>)> {
+    let mut app = build_app(ship_count);
+    for _ in 0..WARM_UP_TICKS {
+        app.update();
+    }
+
+    app.world_mut().resource_mut::<SystemTimings>().measuring = true;
+    let a_star = app.world_mut().register_system(find_path_across_universe);
+    let mut a_star_timings = Vec::with_capacity(MEASURED_TICKS);
+    for _ in 0..MEASURED_TICKS {
+        app.update();
+
+        let start = Instant::now();
+        app.world_mut().run_system(a_star).unwrap();
+        a_star_timings.push(start.elapsed());
+    }
+
+    let timings = app.world_mut().remove_resource::<SystemTimings>().unwrap();
+    timings
+        .names
+        .into_iter()
+        .zip(timings.timings)
+        .chain([("a_star", a_star_timings)])
+        .collect()
+}
+
+/// Surrounds every benchmarked system with two systems which start and stop a timer.
+fn add_timed_systems(app: &mut App) {
+    let mut timings = SystemTimings::default();
+    add_timed_system(
+        app,
+        &mut timings,
+        "auto_trade::handle_idle_ships",
+        auto_trade::handle_idle_ships,
+    );
+    add_timed_system(
+        app,
+        &mut timings,
+        "MoveToEntity::run_tasks",
+        MoveToEntity::run_tasks,
+    );
+    add_timed_system(
+        app,
+        &mut timings,
+        "check_if_production_is_finished_and_start_new_one",
+        check_if_production_is_finished_and_start_new_one,
+    );
+    add_timed_system(app, &mut timings, "respawn_asteroids", respawn_asteroids);
+    add_timed_system(
+        app,
+        &mut timings,
+        "make_asteroids_disappear_when_they_leave_sector",
+        make_asteroids_disappear_when_they_leave_sector,
+    );
+    app.insert_resource(timings);
+}
+
+/// Unrelated systems may still be scheduled in between, so this measures slightly more than just `system`.
+fn add_timed_system<M>(
+    app: &mut App,
+    timings: &mut SystemTimings,
+    name: &'static str,
+    system: impl IntoSystemSet<M> + Copy,
+) {
+    let index = timings.names.len();
+    timings.names.push(name);
+    timings.started.push(None);
+    timings.timings.push(Vec::with_capacity(MEASURED_TICKS));
+
+    app.add_systems(
+        FixedUpdate,
+        (
+            (move |mut timings: ResMut<SystemTimings>| timings.start(index)).before(system),
+            (move |mut timings: ResMut<SystemTimings>| timings.stop(index)).after(system),
+        ),
+    );
+}
+
+fn build_app(ship_count: u32) -> App {
+    let sector_count = (ship_count / SHIPS_PER_SECTOR).max(1);
+    // A hexagonal map with n rings contains 3n² + 3n + 1 sectors
+    let rings = ((sector_count as f64 / 3.0).sqrt().ceil() as u32).max(2);
+    let data = UniverseGenerator::new(SEED, rings)
+        .with_ships(ship_count)
+        .generate();
+
+    let mut app = App::new();
+    app.add_plugins((
+        HeadlessPlugin,
+        StatePlugin,
+        UniverseSaveDataLoadingPlugin,
+        SimulationPlugin,
+        DeterministicSimulationPlugin { seed: SEED },
+    ))
+    .insert_resource(GameData::mock_data())
+    .insert_resource(SessionData::mock_data());
+    add_timed_systems(&mut app);
+    data.insert_as_resources(app.world_mut());
+    app.finish();
+    app.cleanup();
+
+    for _ in 0..MAX_LOADING_UPDATES {
+        app.update();
+        if app.world().resource::<State<ApplicationState>>().get() == &ApplicationState::InGame {
+            return app;
+        }
+    }
+
+    panic!("Universe with {ship_count} ships did not finish loading within {MAX_LOADING_UPDATES} updates!");
+}
+
+/// Searches for a path between the two sectors which are furthest apart.
+fn find_path_across_universe(
+    all_sectors: Query<&Sector>,
+    sector_entities: Query<(Entity, &Sector)>,
+    all_transforms: Query<&SimulationTransform>,
+) {
+    let (from, from_sector) = sector_entities
+        .iter()
+        .min_by_key(|(_, sector)| (sector.coordinate.x, sector.coordinate.y))
+        .unwrap();
+    let (to, _) = sector_entities
+        .iter()
+        .max_by_key(|(_, sector)| (sector.coordinate.x, sector.coordinate.y))
+        .unwrap();
+
+    pathfinding::find_path(
+        &all_sectors,
+        &all_transforms,
+        from.into(),
+        from_sector.world_pos,
+        to.into(),
+        None,
+    )
+    .expect("Generated universes should always be fully connected!");
+}
+
+fn median_micros(timings: &[Duration]) -> f64 {
+    let mut sorted = timings.to_vec();
+    sorted.sort();
+    sorted[sorted.len() / 2].as_secs_f64() * 1_000_000.0
+}
+
+fn read_baseline(path: &Path) -> Baseline {
+    let content = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Unable to read baseline {}: {e}", path.display()));
+    serde_json::from_str(&content)
+        .unwrap_or_else(|e| panic!("Baseline {} is malformed: {e}", path.display()))
+}
+
+fn compare_with_baseline(results: &Baseline, baseline: &Baseline) {
+    println!();
+    let mut regressions = 0;
+    for (name, median) in results {
+        let Some(previous) = baseline.get(name) else {
+            continue;
+        };
+
+        if *median > previous * REGRESSION_THRESHOLD {
+            regressions += 1;
+            println!("REGRESSION {name}: {previous:.1}µs -> {median:.1}µs");
+        }
+    }
+
+    if regressions == 0 {
+        println!("No regressions compared to baseline.");
+    }
+}
+
+fn get_argument(name: &str) -> Option<String> {
+    std::env::args().skip_while(|x| x != name).nth(1)
+}
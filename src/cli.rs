@@ -0,0 +1,379 @@
+//! Parses the command line arguments of the `bevy_space` binary.
+
+use bevy_space::constants;
+use bevy_space::persistence::autosave::{DEFAULT_AUTOSAVE_INTERVAL, DEFAULT_AUTOSAVE_SLOT_COUNT};
+use bevy_space::persistence::universe_generator::ShipCounts;
+use bevy_space::simulation::determinism::DEFAULT_SIMULATION_SEED;
+use bevy_space::simulation::state_checksum::DEFAULT_CHECKSUM_INTERVAL;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+pub const USAGE: &str = "\
+Usage: bevy_space [OPTIONS]
+
+Universe (defaults to the default scenario):
+  --load <slot>                 Load a save slot
+  --scenario <path>             Load a scenario file
+  --replay <path>               Play back a replay in deterministic mode, without game data hot reloading
+  --generate                    Generate a universe from --seed, implied by passing a seed on its own
+  --rings <amount>              Rings around the center sector of generated universes [default: 3]
+  --ships <amount>              Ships in generated universes, split across all behaviors
+  --traders <amount>            Trading ships in generated universes
+  --miners <amount>             Mining ships in generated universes
+  --harvesters <amount>         Harvesting ships in generated universes
+
+Simulation:
+  --seed <seed>                 Seed for generated universes and deterministic runs [default: 0]
+  --deterministic               Run the simulation single-threaded with a seeded RNG
+  --ticks-per-second <amount>   Fixed simulation tick rate [default: 10]
+  --run-for <seconds>           Exit once this much time has been simulated
+  --autosave-interval <seconds> Simulated time between two autosaves, 0 disables them [default: 600]
+  --autosave-slots <amount>     Autosave slots which are cycled through [default: 3]
+  --headless                    Run without window, rendering or GUI
+  --record-replay <path>        Record a replay of this run, implies --deterministic and disables game data hot reloading
+  --stop-at-tick <tick>         Pause (or exit when headless) once a replay reaches this tick
+
+Debugging:
+  --checksum-log <path>         Write state checksums into a file
+  --checksum-interval <ticks>   Ticks between two checksums [default: 100]
+  --compare-checksums <a> <b>   Find the first divergence between two checksum logs and exit
+  --help                        Print this message and exit";
+
+const DEFAULT_GENERATED_RINGS: u32 = 3;
+
+/// Where the universe which should be simulated comes from.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum UniverseSource {
+    SaveSlot(String),
+    Scenario(PathBuf),
+    Generated {
+        rings: u32,
+        ships: ShipCounts,
+    },
+    /// Also determines seed and tick rate.
+    Replay(PathBuf),
+}
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum Command {
+    Run(Box<CommandLineArguments>),
+    CompareChecksums(PathBuf, PathBuf),
+    PrintHelp,
+}
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct CommandLineArguments {
+    pub universe: UniverseSource,
+    pub seed: u64,
+    pub deterministic: bool,
+    pub ticks_per_second: f64,
+    pub run_for: Option<Duration>,
+    /// None if autosaves are disabled.
+    pub autosave_interval: Option<Duration>,
+    pub autosave_slots: usize,
+    pub headless: bool,
+    pub checksum_log: Option<PathBuf>,
+    pub checksum_interval: u32,
+    pub record_replay: Option<PathBuf>,
+    /// Only used while playing back a replay.
+    pub stop_at_tick: Option<u32>,
+}
+
+#[derive(Debug)]
+pub enum ArgumentError {
+    Unknown(String),
+    MissingValue(&'static str),
+    InvalidValue {
+        argument: &'static str,
+        value: String,
+        error: String,
+    },
+    Conflicting(&'static str, &'static str),
+    Requires(&'static str, &'static str),
+}
+
+impl Display for ArgumentError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgumentError::Unknown(argument) => write!(f, "Unknown argument {argument}"),
+            ArgumentError::MissingValue(argument) => write!(f, "{argument} requires a value"),
+            ArgumentError::InvalidValue {
+                argument,
+                value,
+                error,
+            } => write!(f, "Invalid value {value} for {argument}: {error}"),
+            ArgumentError::Conflicting(a, b) => write!(f, "{a} can't be combined with {b}"),
+            ArgumentError::Requires(a, b) => write!(f, "{a} requires {b}"),
+        }
+    }
+}
+
+impl std::error::Error for ArgumentError {}
+
+impl Command {
+    pub fn from_env() -> Result<Self, ArgumentError> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, ArgumentError> {
+        let mut args = args.into_iter();
+
+        let mut load = None;
+        let mut scenario = None;
+        let mut generate = false;
+        let mut rings = None;
+        let mut ships = None;
+        let mut traders = None;
+        let mut miners = None;
+        let mut harvesters = None;
+        let mut seed = None;
+        let mut replay = None;
+        let mut custom_tick_rate = false;
+        let mut arguments = CommandLineArguments {
+            universe: UniverseSource::Scenario(PathBuf::from(
+                bevy_space::persistence::scenario::DEFAULT_SCENARIO_PATH,
+            )),
+            seed: DEFAULT_SIMULATION_SEED,
+            deterministic: false,
+            ticks_per_second: constants::TICKS_PER_SECOND,
+            run_for: None,
+            autosave_interval: Some(DEFAULT_AUTOSAVE_INTERVAL),
+            autosave_slots: DEFAULT_AUTOSAVE_SLOT_COUNT,
+            headless: false,
+            checksum_log: None,
+            checksum_interval: DEFAULT_CHECKSUM_INTERVAL,
+            record_replay: None,
+            stop_at_tick: None,
+        };
+
+        while let Some(argument) = args.next() {
+            match argument.as_str() {
+                "--help" | "-h" => return Ok(Command::PrintHelp),
+                "--compare-checksums" => {
+                    let a = next_value(&mut args, "--compare-checksums")?;
+                    let b = next_value(&mut args, "--compare-checksums")?;
+                    return Ok(Command::CompareChecksums(a, b));
+                }
+                "--load" => load = Some(next_value::<String>(&mut args, "--load")?),
+                "--scenario" => scenario = Some(next_value(&mut args, "--scenario")?),
+                "--replay" => replay = Some(next_value(&mut args, "--replay")?),
+                "--generate" => generate = true,
+                "--rings" => rings = Some(next_value(&mut args, "--rings")?),
+                "--ships" => ships = Some(next_value(&mut args, "--ships")?),
+                "--traders" => traders = Some(next_value(&mut args, "--traders")?),
+                "--miners" => miners = Some(next_value(&mut args, "--miners")?),
+                "--harvesters" => harvesters = Some(next_value(&mut args, "--harvesters")?),
+                "--seed" => seed = Some(next_value(&mut args, "--seed")?),
+                "--deterministic" => arguments.deterministic = true,
+                "--ticks-per-second" => {
+                    custom_tick_rate = true;
+                    arguments.ticks_per_second = next_value(&mut args, "--ticks-per-second")?;
+                    if arguments.ticks_per_second <= 0.0 {
+                        return Err(ArgumentError::InvalidValue {
+                            argument: "--ticks-per-second",
+                            value: arguments.ticks_per_second.to_string(),
+                            error: "needs to be positive".into(),
+                        });
+                    }
+                }
+                "--run-for" => {
+                    arguments.run_for = Some(next_seconds(&mut args, "--run-for")?);
+                }
+                "--autosave-interval" => {
+                    let interval = next_seconds(&mut args, "--autosave-interval")?;
+                    arguments.autosave_interval = (!interval.is_zero()).then_some(interval);
+                }
+                "--autosave-slots" => {
+                    arguments.autosave_slots = next_value(&mut args, "--autosave-slots")?;
+                    if arguments.autosave_slots == 0 {
+                        return Err(ArgumentError::InvalidValue {
+                            argument: "--autosave-slots",
+                            value: "0".into(),
+                            error: "at least one slot is required".into(),
+                        });
+                    }
+                }
+                "--headless" => arguments.headless = true,
+                "--checksum-log" => {
+                    arguments.checksum_log = Some(next_value(&mut args, "--checksum-log")?);
+                }
+                "--checksum-interval" => {
+                    arguments.checksum_interval = next_value(&mut args, "--checksum-interval")?;
+                }
+                "--record-replay" => {
+                    arguments.record_replay = Some(next_value(&mut args, "--record-replay")?);
+                    arguments.deterministic = true;
+                }
+                "--stop-at-tick" => {
+                    arguments.stop_at_tick = Some(next_value(&mut args, "--stop-at-tick")?);
+                }
+                _ => return Err(ArgumentError::Unknown(argument)),
+            }
+        }
+
+        let wants_generated = generate
+            || rings.is_some()
+            || ships.is_some()
+            || traders.is_some()
+            || miners.is_some()
+            || harvesters.is_some();
+
+        if load.is_some() && scenario.is_some() {
+            return Err(ArgumentError::Conflicting("--load", "--scenario"));
+        }
+        if wants_generated && load.is_some() {
+            return Err(ArgumentError::Conflicting("--load", "--generate"));
+        }
+        if wants_generated && scenario.is_some() {
+            return Err(ArgumentError::Conflicting("--scenario", "--generate"));
+        }
+        if replay.is_some() {
+            let conflict = if load.is_some() {
+                Some("--load")
+            } else if scenario.is_some() {
+                Some("--scenario")
+            } else if wants_generated {
+                Some("--generate")
+            } else if seed.is_some() {
+                Some("--seed")
+            } else if custom_tick_rate {
+                Some("--ticks-per-second")
+            } else if arguments.record_replay.is_some() {
+                Some("--record-replay")
+            } else {
+                None
+            };
+            if let Some(conflict) = conflict {
+                return Err(ArgumentError::Conflicting("--replay", conflict));
+            }
+        } else if arguments.stop_at_tick.is_some() {
+            return Err(ArgumentError::Requires("--stop-at-tick", "--replay"));
+        }
+        if ships.is_some() && (traders.is_some() || miners.is_some() || harvesters.is_some()) {
+            return Err(ArgumentError::Conflicting(
+                "--ships",
+                "--traders, --miners or --harvesters",
+            ));
+        }
+
+        if let Some(path) = replay {
+            arguments.universe = UniverseSource::Replay(path);
+        } else if let Some(slot_name) = load {
+            arguments.universe = UniverseSource::SaveSlot(slot_name);
+        } else if let Some(path) = scenario {
+            arguments.universe = UniverseSource::Scenario(path);
+        } else if wants_generated || seed.is_some() {
+            arguments.universe = UniverseSource::Generated {
+                rings: rings.unwrap_or(DEFAULT_GENERATED_RINGS),
+                ships: ships.map(ShipCounts::split).unwrap_or(ShipCounts {
+                    trading: traders.unwrap_or_default(),
+                    mining: miners.unwrap_or_default(),
+                    harvesting: harvesters.unwrap_or_default(),
+                }),
+            };
+        }
+
+        if let Some(seed) = seed {
+            arguments.seed = seed;
+        }
+
+        Ok(Command::Run(Box::new(arguments)))
+    }
+}
+
+fn next_value<T>(
+    args: &mut impl Iterator<Item = String>,
+    argument: &'static str,
+) -> Result<T, ArgumentError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let value = args.next().ok_or(ArgumentError::MissingValue(argument))?;
+    value
+        .parse()
+        .map_err(|e: T::Err| ArgumentError::InvalidValue {
+            argument,
+            error: e.to_string(),
+            value,
+        })
+}
+
+fn next_seconds(
+    args: &mut impl Iterator<Item = String>,
+    argument: &'static str,
+) -> Result<Duration, ArgumentError> {
+    let seconds: f64 = next_value(args, argument)?;
+    Duration::try_from_secs_f64(seconds).map_err(|e| ArgumentError::InvalidValue {
+        argument,
+        value: seconds.to_string(),
+        error: e.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Command, ArgumentError> {
+        Command::parse(args.split_whitespace().map(String::from))
+    }
+
+    fn parse_run(args: &str) -> CommandLineArguments {
+        match parse(args).unwrap() {
+            Command::Run(arguments) => *arguments,
+            _ => panic!("Expected {args} to start a run!"),
+        }
+    }
+
+    #[test]
+    fn generated_universe_with_ship_counts_per_behavior() {
+        let arguments = parse_run(
+            "--seed 7 --rings 5 --traders 100 --miners 20 --headless --run-for 60 --ticks-per-second 20 --autosave-interval 0",
+        );
+
+        assert_eq!(
+            arguments.universe,
+            UniverseSource::Generated {
+                rings: 5,
+                ships: ShipCounts {
+                    trading: 100,
+                    mining: 20,
+                    harvesting: 0,
+                },
+            }
+        );
+        assert_eq!(arguments.seed, 7);
+        assert!(arguments.headless);
+        assert_eq!(arguments.run_for, Some(Duration::from_secs(60)));
+        assert_eq!(arguments.ticks_per_second, 20.0);
+        assert_eq!(arguments.autosave_interval, None);
+    }
+
+    #[test]
+    fn invalid_arguments_are_rejected() {
+        assert!(matches!(
+            parse("--ships many"),
+            Err(ArgumentError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            parse("--run-for"),
+            Err(ArgumentError::MissingValue(_))
+        ));
+        assert!(matches!(
+            parse("--load a --ships 5"),
+            Err(ArgumentError::Conflicting(..))
+        ));
+        assert!(matches!(parse("--fast"), Err(ArgumentError::Unknown(_))));
+        assert!(matches!(
+            parse("--replay a --seed 5"),
+            Err(ArgumentError::Conflicting(..))
+        ));
+        assert!(matches!(
+            parse("--stop-at-tick 5"),
+            Err(ArgumentError::Requires(..))
+        ));
+    }
+}
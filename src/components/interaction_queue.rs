@@ -19,6 +19,19 @@ impl InteractionQueue {
         }
     }
 
+    /// Recreates a previously persisted queue.
+    pub fn restore(
+        maximum_simultaneous_interactions: u32,
+        currently_interacting: u32,
+        waiting_queue: VecDeque<ShipEntity>,
+    ) -> Self {
+        Self {
+            maximum_simultaneous_interactions,
+            currently_interacting,
+            waiting_queue,
+        }
+    }
+
     pub fn maximum_interactions(&self) -> u32 {
         self.maximum_simultaneous_interactions
     }
@@ -27,6 +40,11 @@ impl InteractionQueue {
         self.currently_interacting
     }
 
+    /// The entities waiting for their turn to interact, in order.
+    pub fn waiting_queue(&self) -> &VecDeque<ShipEntity> {
+        &self.waiting_queue
+    }
+
     /// Attempts to start an interaction.
     ///
     /// # Returns
@@ -134,6 +134,25 @@ impl Inventory {
         }
     }
 
+    /// Reverts [Self::create_order] for an order which won't be completed anymore.
+    pub fn cancel_order(&mut self, item_id: ItemId, intent: TradeIntent, amount: u32) {
+        let Some(inventory) = self.inventory.get_mut(&item_id) else {
+            error!("Inventory Entry did not exist on order cancellation!");
+            return;
+        };
+
+        match intent {
+            TradeIntent::Buy => {
+                inventory.planned_buying -= amount;
+                inventory.total -= amount;
+            }
+            TradeIntent::Sell => {
+                inventory.planned_selling -= amount;
+                inventory.total += amount;
+            }
+        }
+    }
+
     /// Tests if there are enough items in stock to start a production run
     pub fn has_enough_items_in_inventory(
         &self,
@@ -225,8 +244,8 @@ impl Inventory {
         }
     }
 
-    pub fn finish_production(&mut self, item_recipe: &ItemRecipe, multiplier: u32) {
-        for output in &item_recipe.output {
+    pub fn finish_production(&mut self, production_yield: &[ItemRecipeElement], multiplier: u32) {
+        for output in production_yield {
             if let Some(inventory) = self.inventory.get_mut(&output.item_id) {
                 inventory.currently_available += output.amount * multiplier;
                 inventory.planned_producing -= output.amount * multiplier;
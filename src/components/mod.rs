@@ -6,6 +6,7 @@ mod gate_connection;
 mod interaction_queue;
 mod inventory;
 mod is_docked;
+mod owner;
 mod planet;
 mod sector;
 mod selectable_entity;
@@ -13,9 +14,10 @@ mod ship;
 mod star;
 mod station;
 mod trade;
+mod wallet;
 
 pub use {
     asteroid::*, constant_orbit::*, engine::Engine, gate::*, gate_connection::*,
-    interaction_queue::*, inventory::Inventory, is_docked::*, planet::*, sector::*,
-    selectable_entity::*, ship::*, star::*, station::*, trade::*,
+    interaction_queue::*, inventory::Inventory, is_docked::*, owner::*, planet::*, sector::*,
+    selectable_entity::*, ship::*, star::*, station::*, trade::*, wallet::*,
 };
@@ -0,0 +1,34 @@
+use crate::components::Wallet;
+use crate::session_data::{FactionId, SessionData};
+use bevy::prelude::{Component, Entity, Mut, Query};
+
+/// The [crate::session_data::Faction] an entity belongs to. Entities without one are independent.
+#[derive(Component, Copy, Clone, PartialEq, Eq)]
+pub struct Owner {
+    pub faction: FactionId,
+}
+
+/// Where the credits of a ship are kept.
+pub enum ShipAccount {
+    /// Independent ships carry a [Wallet] around.
+    Wallet(Wallet),
+    /// Owned ships trade on their faction's account.
+    Owner(Owner),
+}
+
+/// Returns the [Wallet] which pays for the trades of `ship`, as described by [ShipAccount].
+pub fn ship_wallet<'a>(
+    ship: Entity,
+    all_wallets: &'a mut Query<&mut Wallet>,
+    all_owners: &Query<&Owner>,
+    session_data: &'a mut SessionData,
+) -> Option<&'a mut Wallet> {
+    if let Ok(owner) = all_owners.get(ship) {
+        session_data
+            .factions
+            .get_mut(&owner.faction)
+            .map(|faction| &mut faction.wallet)
+    } else {
+        all_wallets.get_mut(ship).ok().map(Mut::into_inner)
+    }
+}
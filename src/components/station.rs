@@ -7,6 +7,10 @@ pub struct Station {
     pub id: PersistentStationId,
 }
 
+/// Marker Component for the icon which is drawn on top of a [Station] to show what it produces.
+#[derive(Component)]
+pub struct StationIcon;
+
 impl ComponentWithPersistentId<Station> for Station {
     #[inline]
     fn id(&self) -> TypedPersistentEntityId<Station> {
@@ -1,5 +1,5 @@
 use crate::components::inventory::InventoryElement;
-use crate::components::{OrderData, TradeOrder};
+use crate::components::{affordable_amount, OrderData, TradeOrder, Wallet};
 use crate::game_data::ItemId;
 use crate::utils::PriceSetting;
 use bevy::prelude::Component;
@@ -18,6 +18,22 @@ pub struct BuyOrderData {
     pub price_setting: PriceSetting,
 }
 
+impl BuyOrders {
+    /// Reduces the order amounts to what `wallet` can still pay for, once all purchases which have already been
+    /// planned are paid at their agreed prices. The budget is shared by all orders, so those with lower item IDs are
+    /// served first. Needs to be called after [TradeOrder::update], which resets the amounts.
+    pub fn limit_to_budget(&mut self, wallet: &Wallet) {
+        let mut budget = wallet.available_credits();
+
+        let mut orders: Vec<_> = self.orders.iter_mut().collect();
+        orders.sort_by_key(|(item_id, _)| **item_id);
+        for (_, order) in orders {
+            order.amount = order.amount.min(affordable_amount(budget, order.price));
+            budget -= order.amount as i64 * order.price as i64;
+        }
+    }
+}
+
 impl TradeOrder<BuyOrderData> for BuyOrders {
     fn orders(&self) -> &HashMap<ItemId, BuyOrderData> {
         &self.orders
@@ -44,4 +60,8 @@ impl OrderData for BuyOrderData {
             self.price = self.price_setting.calculate_price(stored_amount, capacity);
         }
     }
+
+    fn price_setting_mut(&mut self) -> &mut PriceSetting {
+        &mut self.price_setting
+    }
 }
@@ -44,4 +44,8 @@ impl OrderData for SellOrderData {
             self.price = self.price_setting.calculate_price(stored_amount, capacity);
         }
     }
+
+    fn price_setting_mut(&mut self) -> &mut PriceSetting {
+        &mut self.price_setting
+    }
 }
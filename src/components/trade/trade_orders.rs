@@ -1,6 +1,8 @@
 use crate::components::inventory::InventoryElement;
 use crate::components::Inventory;
-use crate::game_data::ItemId;
+use crate::game_data::{GameData, ItemId};
+use crate::utils::PriceSetting;
+use bevy::log::warn;
 use bevy::prelude::Component;
 use bevy::utils::HashMap;
 
@@ -15,6 +17,19 @@ pub trait TradeOrder<TOrderData: OrderData>: Default + Component {
         }
     }
 
+    /// Replaces the price settings of all orders with the price ranges defined inside [GameData].
+    /// Call [Self::update] afterward to recalculate the cached prices.
+    fn apply_price_ranges(&mut self, game_data: &GameData) {
+        for (item_id, order) in self.orders_mut() {
+            let Some(item) = game_data.items.get(item_id) else {
+                warn!("Unable to find item {item_id} to update its order prices, keeping the old ones.");
+                continue;
+            };
+
+            *order.price_setting_mut() = PriceSetting::Dynamic(item.price);
+        }
+    }
+
     fn from_vec(vec: Vec<(ItemId, TOrderData)>) -> Self {
         let mut result = Self::default();
         let orders = result.orders_mut();
@@ -30,4 +45,57 @@ pub trait TradeOrder<TOrderData: OrderData>: Default + Component {
 pub trait OrderData {
     /// Updates the order amount and cached price
     fn update(&mut self, capacity: u32, inventory_element: Option<&InventoryElement>);
+
+    fn price_setting_mut(&mut self) -> &mut PriceSetting;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::components::{BuyOrders, Wallet};
+    use crate::game_data::{DEBUG_ITEM_ID_A, DEBUG_ITEM_ID_B};
+    use crate::utils::PriceRange;
+
+    #[test]
+    fn apply_price_ranges_uses_changed_item_prices() {
+        let mut game_data = GameData::mock_data();
+        let mut orders = BuyOrders::mock(vec![&game_data.items[&DEBUG_ITEM_ID_A]]);
+
+        game_data.items.get_mut(&DEBUG_ITEM_ID_A).unwrap().price = PriceRange::new(10, 20);
+        orders.apply_price_ranges(&game_data);
+        orders.update(&Inventory::new(100));
+
+        assert_eq!(orders.orders()[&DEBUG_ITEM_ID_A].price, 20);
+    }
+
+    #[test]
+    fn buy_orders_are_limited_to_what_is_left_after_planned_purchases() {
+        let game_data = GameData::mock_data();
+        let mut orders = BuyOrders::mock(vec![&game_data.items[&DEBUG_ITEM_ID_A]]);
+        let price = orders.orders()[&DEBUG_ITEM_ID_A].price as i64;
+        let mut wallet = Wallet::new(price * 10 + 1);
+        // Agreed upon when the prices were higher
+        wallet.reserve(4 * price * 2);
+
+        orders.limit_to_budget(&wallet);
+
+        assert_eq!(orders.orders()[&DEBUG_ITEM_ID_A].amount, 2);
+    }
+
+    #[test]
+    fn buy_orders_share_the_budget() {
+        let game_data = GameData::mock_data();
+        let mut orders = BuyOrders::mock(vec![
+            &game_data.items[&DEBUG_ITEM_ID_A],
+            &game_data.items[&DEBUG_ITEM_ID_B],
+        ]);
+        let price_a = orders.orders()[&DEBUG_ITEM_ID_A].price as i64;
+        let price_b = orders.orders()[&DEBUG_ITEM_ID_B].price as i64;
+        let budget = price_a * 10 + price_b * 5;
+
+        orders.limit_to_budget(&Wallet::new(budget));
+
+        assert_eq!(orders.orders()[&DEBUG_ITEM_ID_A].amount, 10);
+        assert_eq!(orders.orders()[&DEBUG_ITEM_ID_B].amount, 5);
+    }
 }
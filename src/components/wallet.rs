@@ -0,0 +1,54 @@
+use bevy::prelude::Component;
+
+/// The credits owned by a station, an independent ship or a [crate::session_data::Faction]. Wares are paid for whenever
+/// they are exchanged.
+///
+/// Purchases are limited to the credits which haven't been reserved yet while planning them. The price which was agreed
+/// upon back then stays reserved until the purchase has been paid for or aborted, so the balance never becomes negative.
+#[derive(Component)]
+pub struct Wallet {
+    credits: i64,
+    /// Not persisted, since every planned purchase reserves its credits again while loading its tasks.
+    reserved: i64,
+}
+
+impl Wallet {
+    pub fn new(credits: i64) -> Self {
+        Self {
+            credits,
+            reserved: 0,
+        }
+    }
+
+    pub fn credits(&self) -> i64 {
+        self.credits
+    }
+
+    /// What's left to plan further purchases with.
+    pub fn available_credits(&self) -> i64 {
+        self.credits - self.reserved
+    }
+
+    pub fn reserve(&mut self, credits: i64) {
+        self.reserved += credits;
+    }
+
+    pub fn release(&mut self, credits: i64) {
+        self.reserved -= credits;
+    }
+
+    /// Moves `credits` from `payer` to `payee`.
+    pub fn transfer(payer: &mut Wallet, payee: &mut Wallet, credits: i64) {
+        payer.credits -= credits;
+        payee.credits += credits;
+    }
+}
+
+/// How many items at `price` can be paid for with `credits`.
+pub fn affordable_amount(credits: i64, price: u32) -> u32 {
+    if price == 0 {
+        return u32::MAX;
+    }
+
+    (credits.max(0) / price as i64).min(u32::MAX as i64) as u32
+}
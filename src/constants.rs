@@ -1,15 +1,6 @@
 use crate::simulation::prelude::Milliseconds;
 use std::ops::Range;
 
-#[cfg(debug_assertions)]
-pub const TRADE_SHIP_COUNT: u32 = 20;
-#[cfg(not(debug_assertions))]
-pub const TRADE_SHIP_COUNT: u32 = 200000;
-
-pub const MINING_SHIP_COUNT: u32 = 20;
-pub const HARVESTING_SHIP_COUNT: u32 = 20;
-pub const ASTEROID_COUNT: usize = 400;
-
 /// Lower values here mean ships will spread out more when buying / selling things, but also come to a halt much sooner,
 /// as prices reach an equilibrium and resource consumption just isn't high enough yet.
 #[cfg(debug_assertions)]
@@ -32,10 +23,40 @@ pub const SECTOR_SIZE: f32 = 500.0;
 pub const SECTOR_AREA_PERCENTAGE: f32 = 0.99;
 
 pub const ASTEROID_RESPAWN_TIME: Milliseconds = 5000;
+/// How long asteroids take to fade out before they are despawned.
+pub const ASTEROID_FADE_OUT_TIME: Milliseconds = 1000;
 
 /// Basically a multiplier for orbit speeds
 pub const GRAVITATIONAL_CONSTANT: f32 = 0.000000067;
 
+/// How many sectors are spawned per frame while loading. Sectors may contain hundreds of asteroids each.
+pub const SECTORS_LOADED_PER_FRAME: usize = 2;
+/// How many gates, stations or ships are spawned per frame while loading.
+pub const ENTITIES_LOADED_PER_FRAME: usize = 10000;
+
+pub const SHIP_INVENTORY_SIZE: u32 = 100;
+
+/// Enough to buy a few thousand items at the highest price.
+pub const STATION_STARTING_CREDITS: i64 = 250_000;
+/// Enough for a single full ship load at the highest price.
+pub const SHIP_STARTING_CREDITS: i64 = 10_000;
+
+/// How many gates a trading ship may jump through to reach the seller, and from there to its final buyer.
+pub const DEFAULT_MAX_TRADE_JUMP_RANGE: u8 = 4;
+/// Docking, exchanging wares and undocking, used to estimate how long a trade run takes.
+pub const ESTIMATED_SECONDS_PER_TRADE_STOP: f32 = 5.0;
+/// How many buyers a ship may deliver to within a single trade run.
+pub const MAX_TRADE_ROUTE_STOPS: usize = 4;
+/// How many additional gate jumps a ship may take compared to the direct route to its final buyer, in order to sell to others on the way.
+pub const MAX_TRADE_ROUTE_DETOUR_IN_JUMPS: u8 = 1;
+/// How many times as long as estimated a ship may take to exchange the goods it reserved for a trade run.
+/// Delays cost favor with the trade partner.
+pub const DELIVERY_DEADLINE_TRAVEL_TIME_FACTOR: f32 = 2.0;
+/// Added on top of every delivery deadline, since ships may have to wait until they can dock.
+pub const DELIVERY_DEADLINE_GRACE_SECONDS: f32 = 60.0;
+/// How far other factions may run into debt with a faction's favor before it stops trading with them.
+pub const DEFAULT_REQUIRED_FAVOR: i64 = -50_000;
+
 pub const SIMULTANEOUS_STATION_INTERACTIONS: u32 = 4;
 pub const SIMULTANEOUS_PLANET_INTERACTIONS: u32 = 8;
 pub const DOCKING_DISTANCE_TO_STATION: f32 = 24.0;
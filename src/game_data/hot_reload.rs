@@ -0,0 +1,236 @@
+use crate::components::{BuyOrders, Inventory, SellOrders, TradeOrder, Wallet};
+use crate::game_data::loading::{
+    ITEMS_FILE_NAME, PRODUCTION_MODULES_FILE_NAME, RECIPES_FILE_NAME, SHIPYARD_MODULES_FILE_NAME,
+};
+use crate::game_data::{
+    GameData, GameDataError, ItemDefinition, ItemRecipe, ProductionModuleDefinition,
+    ShipyardModuleDefinition,
+};
+use crate::utils;
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::asset::io::Reader;
+use bevy::asset::{
+    Asset, AssetApp, AssetEvent, AssetLoader, AssetServer, Assets, AsyncReadExt, Handle,
+    LoadContext,
+};
+use bevy::ecs::system::SystemParam;
+use bevy::log::{error, info, warn};
+use bevy::prelude::{Commands, EventReader, Or, Query, Res, ResMut, Resource, TypePath, With};
+use bevy::utils::HashMap;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::marker::PhantomData;
+
+/// The directory containing the game data files, relative to the asset folder.
+const GAME_DATA_ASSET_DIRECTORY: &str = "game_data";
+
+/// Watches the game data files for changes and applies them to the running simulation.
+///
+/// [GameData] is still loaded synchronously on startup, this plugin only takes care of edits made afterward.
+/// Changes to recipes take effect with the next production run, changes to prices are applied to all existing orders.
+pub struct GameDataHotReloadPlugin;
+
+impl Plugin for GameDataHotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<ItemDefinitions>()
+            .init_asset::<RecipeDefinitions>()
+            .init_asset::<ProductionModuleDefinitions>()
+            .init_asset::<ShipyardModuleDefinitions>()
+            .register_asset_loader(RonAssetLoader::<ItemDefinitions>::default())
+            .register_asset_loader(RonAssetLoader::<RecipeDefinitions>::default())
+            .register_asset_loader(RonAssetLoader::<ProductionModuleDefinitions>::default())
+            .register_asset_loader(RonAssetLoader::<ShipyardModuleDefinitions>::default())
+            .add_systems(Startup, load_game_data_assets)
+            .add_systems(Update, reload_modified_game_data);
+    }
+}
+
+#[derive(Asset, TypePath, Deserialize)]
+#[serde(transparent)]
+pub struct ItemDefinitions(pub Vec<ItemDefinition>);
+
+#[derive(Asset, TypePath, Deserialize)]
+#[serde(transparent)]
+pub struct RecipeDefinitions(pub Vec<ItemRecipe>);
+
+#[derive(Asset, TypePath, Deserialize)]
+#[serde(transparent)]
+pub struct ProductionModuleDefinitions(pub Vec<ProductionModuleDefinition>);
+
+#[derive(Asset, TypePath, Deserialize)]
+#[serde(transparent)]
+pub struct ShipyardModuleDefinitions(pub Vec<ShipyardModuleDefinition>);
+
+/// Parses a single game data file into the respective asset type.
+struct RonAssetLoader<A> {
+    phantom: PhantomData<fn() -> A>,
+}
+
+impl<A> Default for RonAssetLoader<A> {
+    fn default() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<A: Asset + DeserializeOwned> AssetLoader for RonAssetLoader<A> {
+    type Asset = A;
+    type Settings = ();
+    type Error = GameDataError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<A, GameDataError> {
+        let path = load_context.path().to_path_buf();
+        let mut content = String::new();
+        reader
+            .read_to_string(&mut content)
+            .await
+            .map_err(|e| GameDataError::Io(path.clone(), e))?;
+
+        ron::from_str(&content).map_err(|e| GameDataError::Parse(path, e))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+/// Keeps the game data assets alive, so their files are being watched.
+#[derive(Resource)]
+struct GameDataHandles {
+    items: Handle<ItemDefinitions>,
+    recipes: Handle<RecipeDefinitions>,
+    production_modules: Handle<ProductionModuleDefinitions>,
+    shipyard_modules: Handle<ShipyardModuleDefinitions>,
+}
+
+fn load_game_data_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let path = |file_name: &str| format!("{GAME_DATA_ASSET_DIRECTORY}/{file_name}");
+    commands.insert_resource(GameDataHandles {
+        items: asset_server.load(path(ITEMS_FILE_NAME)),
+        recipes: asset_server.load(path(RECIPES_FILE_NAME)),
+        production_modules: asset_server.load(path(PRODUCTION_MODULES_FILE_NAME)),
+        shipyard_modules: asset_server.load(path(SHIPYARD_MODULES_FILE_NAME)),
+    });
+}
+
+#[derive(SystemParam)]
+struct GameDataAssets<'w, 's> {
+    handles: Res<'w, GameDataHandles>,
+    items: Res<'w, Assets<ItemDefinitions>>,
+    recipes: Res<'w, Assets<RecipeDefinitions>>,
+    production_modules: Res<'w, Assets<ProductionModuleDefinitions>>,
+    shipyard_modules: Res<'w, Assets<ShipyardModuleDefinitions>>,
+    item_events: EventReader<'w, 's, AssetEvent<ItemDefinitions>>,
+    recipe_events: EventReader<'w, 's, AssetEvent<RecipeDefinitions>>,
+    production_module_events: EventReader<'w, 's, AssetEvent<ProductionModuleDefinitions>>,
+    shipyard_module_events: EventReader<'w, 's, AssetEvent<ShipyardModuleDefinitions>>,
+}
+
+impl GameDataAssets<'_, '_> {
+    /// Consumes all pending asset events and returns whether any of the game data files was modified.
+    fn any_modified(&mut self) -> bool {
+        // Non-short-circuiting so every reader is drained.
+        was_modified(&mut self.item_events)
+            | was_modified(&mut self.recipe_events)
+            | was_modified(&mut self.production_module_events)
+            | was_modified(&mut self.shipyard_module_events)
+    }
+
+    /// Assembles [GameData] from the current asset contents, or None if some of them aren't loaded.
+    fn build(&self) -> Option<Result<GameData, GameDataError>> {
+        let items = self.items.get(&self.handles.items)?;
+        let recipes = self.recipes.get(&self.handles.recipes)?;
+        let production_modules = self
+            .production_modules
+            .get(&self.handles.production_modules)?;
+        let shipyard_modules = self.shipyard_modules.get(&self.handles.shipyard_modules)?;
+
+        Some(GameData::from_definitions(
+            items.0.clone(),
+            recipes.0.clone(),
+            production_modules.0.clone(),
+            shipyard_modules.0.clone(),
+        ))
+    }
+}
+
+fn was_modified<A: Asset>(events: &mut EventReader<AssetEvent<A>>) -> bool {
+    events.read().fold(false, |result, event| {
+        result || matches!(event, AssetEvent::Modified { .. })
+    })
+}
+
+#[allow(clippy::type_complexity)]
+fn reload_modified_game_data(
+    mut assets: GameDataAssets,
+    mut game_data: ResMut<GameData>,
+    mut traders: Query<
+        (
+            &Inventory,
+            Option<&Wallet>,
+            Option<&mut BuyOrders>,
+            Option<&mut SellOrders>,
+        ),
+        Or<(With<BuyOrders>, With<SellOrders>)>,
+    >,
+) {
+    if !assets.any_modified() {
+        return;
+    }
+
+    let new_game_data = match assets.build() {
+        None => return,
+        Some(Err(e)) => {
+            error!("Unable to reload game data, keeping the previous definitions: {e}");
+            return;
+        }
+        Some(Ok(game_data)) => game_data,
+    };
+
+    warn_about_removed_definitions("Item", &game_data.items, &new_game_data.items);
+    warn_about_removed_definitions(
+        "Recipe",
+        &game_data.item_recipes,
+        &new_game_data.item_recipes,
+    );
+    warn_about_removed_definitions(
+        "Production module",
+        &game_data.production_modules,
+        &new_game_data.production_modules,
+    );
+    warn_about_removed_definitions(
+        "Shipyard module",
+        &game_data.shipyard_modules,
+        &new_game_data.shipyard_modules,
+    );
+
+    for (inventory, wallet, mut buy_orders, mut sell_orders) in traders.iter_mut() {
+        if let Some(buy_orders) = &mut buy_orders {
+            buy_orders.apply_price_ranges(&new_game_data);
+        }
+        if let Some(sell_orders) = &mut sell_orders {
+            sell_orders.apply_price_ranges(&new_game_data);
+        }
+
+        utils::update_orders(inventory, wallet, buy_orders, sell_orders);
+    }
+
+    *game_data = new_game_data;
+    info!("Game data has been reloaded.");
+}
+
+fn warn_about_removed_definitions<T>(
+    kind: &str,
+    previous: &HashMap<u32, T>,
+    current: &HashMap<u32, T>,
+) {
+    for id in previous.keys().filter(|id| !current.contains_key(id)) {
+        warn!("{kind} {id} has been removed from the game data. Anything still referring to it will stop working!");
+    }
+}
@@ -1,4 +1,5 @@
 use crate::utils::PriceRange;
+use serde::{Deserialize, Serialize};
 
 pub type ItemId = u32;
 
@@ -8,6 +9,7 @@ pub const DEBUG_ITEM_ID_C: ItemId = 3;
 pub const DEBUG_ITEM_ID_ORE: ItemId = DEBUG_ITEM_ID_A;
 pub const DEBUG_ITEM_ID_GAS: ItemId = DEBUG_ITEM_ID_B;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ItemDefinition {
     pub id: ItemId,
     pub icon: String, // TODO: Should be converted into asset handle during parsing
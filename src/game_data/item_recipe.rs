@@ -1,5 +1,6 @@
 use crate::game_data::ItemId;
 use crate::simulation::prelude::Milliseconds;
+use serde::{Deserialize, Serialize};
 
 pub type RecipeId = u32;
 
@@ -7,6 +8,7 @@ pub const RECIPE_A_ID: RecipeId = 1;
 pub const RECIPE_B_ID: RecipeId = 2;
 pub const RECIPE_C_ID: RecipeId = 3;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ItemRecipe {
     /// Unique ID to differentiate between recipes
     pub id: RecipeId,
@@ -20,6 +22,7 @@ pub struct ItemRecipe {
     pub output: Vec<ItemRecipeElement>,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ItemRecipeElement {
     pub item_id: ItemId,
     pub amount: u32,
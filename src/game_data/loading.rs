@@ -0,0 +1,274 @@
+use crate::game_data::{
+    GameData, ItemDefinition, ItemId, ItemRecipe, ProductionModuleDefinition, ProductionModuleId,
+    RecipeId, ShipyardModuleDefinition,
+};
+use bevy::utils::HashMap;
+use serde::de::DeserializeOwned;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// The directory containing the game data files, relative to the working directory.
+pub const DEFAULT_GAME_DATA_DIRECTORY: &str = "assets/game_data";
+
+pub(super) const ITEMS_FILE_NAME: &str = "items.ron";
+pub(super) const RECIPES_FILE_NAME: &str = "recipes.ron";
+pub(super) const PRODUCTION_MODULES_FILE_NAME: &str = "production_modules.ron";
+pub(super) const SHIPYARD_MODULES_FILE_NAME: &str = "shipyard_modules.ron";
+
+#[derive(Debug)]
+pub enum GameDataError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, ron::error::SpannedError),
+    Invalid(Vec<GameDataValidationError>),
+}
+
+impl Display for GameDataError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameDataError::Io(path, e) => write!(f, "Unable to read {}: {e}", path.display()),
+            GameDataError::Parse(path, e) => write!(f, "{} is malformed: {e}", path.display()),
+            GameDataError::Invalid(errors) => {
+                write!(f, "Game data contains {} error(s):", errors.len())?;
+                for error in errors {
+                    write!(f, "\n - {error}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for GameDataError {}
+
+/// A single problem with otherwise well-formed game data, usually a reference to something which doesn't exist.
+#[derive(Debug, PartialEq)]
+pub enum GameDataValidationError {
+    DuplicateId {
+        kind: &'static str,
+        id: u32,
+    },
+    InvalidPriceRange {
+        item: ItemId,
+    },
+    UnknownItem {
+        recipe: RecipeId,
+        item: ItemId,
+    },
+    UnknownRecipe {
+        module: ProductionModuleId,
+        recipe: RecipeId,
+    },
+}
+
+impl Display for GameDataValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameDataValidationError::DuplicateId { kind, id } => {
+                write!(f, "{kind} id {id} is used more than once")
+            }
+            GameDataValidationError::InvalidPriceRange { item } => {
+                write!(f, "Item {item} has a minimum price above its maximum price")
+            }
+            GameDataValidationError::UnknownItem { recipe, item } => {
+                write!(f, "Recipe {recipe} refers to unknown item {item}")
+            }
+            GameDataValidationError::UnknownRecipe { module, recipe } => {
+                write!(
+                    f,
+                    "Production module {module} refers to unknown recipe {recipe}"
+                )
+            }
+        }
+    }
+}
+
+impl GameData {
+    /// Reads all game data files inside `directory` and validates them.
+    pub fn load_from_directory(directory: &Path) -> Result<Self, GameDataError> {
+        Self::from_definitions(
+            read_file(&directory.join(ITEMS_FILE_NAME))?,
+            read_file(&directory.join(RECIPES_FILE_NAME))?,
+            read_file(&directory.join(PRODUCTION_MODULES_FILE_NAME))?,
+            read_file(&directory.join(SHIPYARD_MODULES_FILE_NAME))?,
+        )
+    }
+
+    /// Validates the given definitions and assembles them into [GameData].
+    /// All problems are collected, so they can be fixed in one go.
+    pub fn from_definitions(
+        items: Vec<ItemDefinition>,
+        recipes: Vec<ItemRecipe>,
+        production_modules: Vec<ProductionModuleDefinition>,
+        shipyard_modules: Vec<ShipyardModuleDefinition>,
+    ) -> Result<Self, GameDataError> {
+        let mut errors = Vec::new();
+
+        let items = collect_unique("Item", items, |x| x.id, &mut errors);
+        let item_recipes = collect_unique("Recipe", recipes, |x| x.id, &mut errors);
+        let production_modules = collect_unique(
+            "Production module",
+            production_modules,
+            |x| x.id,
+            &mut errors,
+        );
+        let shipyard_modules =
+            collect_unique("Shipyard module", shipyard_modules, |x| x.id, &mut errors);
+
+        let mut item_ids: Vec<&ItemId> = items.keys().collect();
+        item_ids.sort();
+        for id in item_ids {
+            let price = items[id].price;
+            if price.min > price.max {
+                errors.push(GameDataValidationError::InvalidPriceRange { item: *id });
+            }
+        }
+
+        let mut recipe_ids: Vec<&RecipeId> = item_recipes.keys().collect();
+        recipe_ids.sort();
+        for id in recipe_ids {
+            let recipe = &item_recipes[id];
+            for element in recipe.input.iter().chain(recipe.output.iter()) {
+                if !items.contains_key(&element.item_id) {
+                    errors.push(GameDataValidationError::UnknownItem {
+                        recipe: recipe.id,
+                        item: element.item_id,
+                    });
+                }
+            }
+        }
+
+        let mut module_ids: Vec<&ProductionModuleId> = production_modules.keys().collect();
+        module_ids.sort();
+        for id in module_ids {
+            let module = &production_modules[id];
+            for recipe in &module.available_recipes {
+                if !item_recipes.contains_key(recipe) {
+                    errors.push(GameDataValidationError::UnknownRecipe {
+                        module: module.id,
+                        recipe: *recipe,
+                    });
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(GameDataError::Invalid(errors));
+        }
+
+        Ok(Self {
+            items,
+            item_recipes,
+            production_modules,
+            shipyard_modules,
+        })
+    }
+}
+
+fn read_file<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, GameDataError> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| GameDataError::Io(path.to_path_buf(), e))?;
+    ron::from_str(&content).map_err(|e| GameDataError::Parse(path.to_path_buf(), e))
+}
+
+fn collect_unique<T>(
+    kind: &'static str,
+    definitions: Vec<T>,
+    get_id: impl Fn(&T) -> u32,
+    errors: &mut Vec<GameDataValidationError>,
+) -> HashMap<u32, T> {
+    let mut result = HashMap::new();
+    for definition in definitions {
+        let id = get_id(&definition);
+        if result.insert(id, definition).is_some() {
+            errors.push(GameDataValidationError::DuplicateId { kind, id });
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_data::{ItemRecipeElement, DEBUG_ITEM_ID_A, DEBUG_ITEM_ID_B, RECIPE_A_ID};
+    use crate::utils::PriceRange;
+
+    fn item(id: ItemId) -> ItemDefinition {
+        ItemDefinition {
+            id,
+            icon: "ui_icons/items/unknown.png".into(),
+            name: format!("Item {id}"),
+            price: PriceRange::new(5, 1000),
+        }
+    }
+
+    fn recipe(id: RecipeId, input: ItemId, output: ItemId) -> ItemRecipe {
+        ItemRecipe {
+            id,
+            name: format!("Recipe {id}"),
+            duration: 1000,
+            input: vec![ItemRecipeElement {
+                item_id: input,
+                amount: 1,
+            }],
+            output: vec![ItemRecipeElement {
+                item_id: output,
+                amount: 1,
+            }],
+        }
+    }
+
+    #[test]
+    fn bundled_game_data_is_valid() {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_GAME_DATA_DIRECTORY);
+        let game_data = GameData::load_from_directory(&directory).unwrap();
+
+        let mock_data = GameData::mock_data();
+        assert_eq!(mock_data.items.len(), game_data.items.len());
+        assert_eq!(mock_data.item_recipes.len(), game_data.item_recipes.len());
+        assert_eq!(
+            mock_data.production_modules.len(),
+            game_data.production_modules.len()
+        );
+        assert_eq!(
+            mock_data.shipyard_modules.len(),
+            game_data.shipyard_modules.len()
+        );
+    }
+
+    #[test]
+    fn unknown_references_and_duplicates_are_reported() {
+        let result = GameData::from_definitions(
+            vec![item(DEBUG_ITEM_ID_A), item(DEBUG_ITEM_ID_A)],
+            vec![recipe(RECIPE_A_ID, DEBUG_ITEM_ID_A, DEBUG_ITEM_ID_B)],
+            vec![ProductionModuleDefinition {
+                id: 1,
+                name: "Module".into(),
+                available_recipes: vec![RECIPE_A_ID, 42],
+            }],
+            Vec::new(),
+        );
+
+        let Err(GameDataError::Invalid(errors)) = result else {
+            panic!("Invalid game data should not be accepted!");
+        };
+
+        assert_eq!(
+            errors,
+            vec![
+                GameDataValidationError::DuplicateId {
+                    kind: "Item",
+                    id: DEBUG_ITEM_ID_A
+                },
+                GameDataValidationError::UnknownItem {
+                    recipe: RECIPE_A_ID,
+                    item: DEBUG_ITEM_ID_B
+                },
+                GameDataValidationError::UnknownRecipe {
+                    module: 1,
+                    recipe: 42
+                },
+            ]
+        );
+    }
+}
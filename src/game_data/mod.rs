@@ -1,5 +1,7 @@
+mod hot_reload;
 mod item;
 mod item_recipe;
+mod loading;
 mod production_module;
 mod shipyard_module;
 
@@ -7,9 +9,12 @@ use crate::utils::PriceRange;
 use bevy::prelude::Resource;
 use bevy::utils::HashMap;
 
-pub use {item::*, item_recipe::*, production_module::*, shipyard_module::*};
+pub use {
+    hot_reload::*, item::*, item_recipe::*, loading::*, production_module::*, shipyard_module::*,
+};
 
-/// Constant Data which is parsed from files at game start and doesn't change without a restart.
+/// Data which is parsed from files at game start. See [GameData::load_from_directory] and the files inside [DEFAULT_GAME_DATA_DIRECTORY].
+/// Edits to those files are applied while the game is running by [GameDataHotReloadPlugin].
 #[derive(Resource)]
 pub struct GameData {
     pub items: HashMap<ItemId, ItemDefinition>,
@@ -1,4 +1,5 @@
 use crate::game_data::RecipeId;
+use serde::{Deserialize, Serialize};
 
 pub type ProductionModuleId = u32;
 
@@ -7,6 +8,7 @@ pub const PRODUCTION_MODULE_B_ID: ProductionModuleId = 2;
 pub const PRODUCTION_MODULE_C_ID: ProductionModuleId = 3;
 
 /// Defines the costs and capabilities of a single Production Line
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ProductionModuleDefinition {
     /// Unique ID to differentiate between recipes
     pub id: ProductionModuleId,
@@ -1,8 +1,11 @@
+use serde::{Deserialize, Serialize};
+
 pub type ShipyardModuleId = u32;
 
 pub const SHIPYARD_MODULE_ID: ShipyardModuleId = 1;
 
 /// Defines the costs and capabilities of a single ship production line
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ShipyardModuleDefinition {
     /// Unique ID to differentiate between recipes
     pub id: ShipyardModuleId,
@@ -1,25 +1,34 @@
 use bevy::app::App;
 use bevy::prelude::{
-    AppExtStates, AssetServer, Commands, Entity, IntoSystemConfigs, Name, NextState, Plugin,
-    PreUpdate, Query, Res, ResMut, Resource, Startup, State, States, Update, With,
+    in_state, AppExtStates, AssetServer, Commands, Entity, EventReader, EventWriter,
+    IntoSystemConfigs, Local, Name, NextState, Plugin, PreUpdate, Query, Res, ResMut, Resource,
+    Startup, State, States, Time, Update, Virtual, With,
 };
 use bevy_egui::egui::load::SizedTexture;
 use bevy_egui::egui::{Align2, Shadow, Ui};
 use bevy_egui::{egui, EguiContexts, EguiStartupSet};
 
 use crate::components::{
-    Asteroid, BuyOrders, Gate, InSector, InteractionQueue, Inventory, SelectableEntity, SellOrders,
-    TradeOrder,
+    Asteroid, BuyOrders, Gate, InSector, InteractionQueue, Inventory, Owner, SelectableEntity,
+    SellOrders, TradeOrder, Wallet,
 };
 use crate::entity_selection::{MouseCursor, Selected};
-use crate::game_data::GameData;
+use crate::game_data::{GameData, ItemId};
 use crate::map_layout::MapLayout;
+use crate::persistence::autosave::AutosaveStatus;
+use crate::persistence::save_files::QUICK_SAVE_SLOT_NAME;
+use crate::persistence::{
+    LoadGameEvent, LoadingPhaseProgress, LoadingProgress, RunningSaveTasks, SaveDirectory,
+    SaveFileFormat, SaveGameEvent, SaveGameStatusEvent,
+};
 use crate::session_data::SessionData;
 use crate::simulation::physics::ShipVelocity;
 use crate::simulation::prelude::SimulationTime;
 use crate::simulation::production::{ProductionComponent, ShipyardComponent};
 use crate::simulation::ship_ai::TaskInsideQueue;
 use crate::simulation::ship_ai::TaskQueue;
+use crate::simulation::speed::{SimulationSpeedEvent, SIMULATION_SPEEDS};
+use crate::states::{ApplicationState, SimulationState};
 use crate::utils::ExchangeWareData;
 use crate::SpriteHandles;
 
@@ -40,6 +49,9 @@ impl Plugin for GUIPlugin {
                     draw_sector_info,
                     list_selection_icons_and_counts,
                     list_selection_details,
+                    draw_save_slot_menu.run_if(in_state(ApplicationState::InGame)),
+                    draw_simulation_speed_controls.run_if(in_state(ApplicationState::InGame)),
+                    draw_loading_progress.run_if(in_state(ApplicationState::Loading)),
                 ),
             );
     }
@@ -108,8 +120,8 @@ impl UiIcons {
             TaskInsideQueue::UseGate { .. } => self.move_to,
             TaskInsideQueue::MoveToEntity { .. } => self.move_to,
             TaskInsideQueue::ExchangeWares { data, .. } => match data {
-                ExchangeWareData::Buy(_, _) => self.buy,
-                ExchangeWareData::Sell(_, _) => self.sell,
+                ExchangeWareData::Buy(..) => self.buy,
+                ExchangeWareData::Sell(..) => self.sell,
             },
             TaskInsideQueue::MineAsteroid { .. } => self.asteroid,
             TaskInsideQueue::HarvestGas { .. } => self.planet,
@@ -252,6 +264,7 @@ pub fn list_selection_details(
             Option<&Gate>,
             Option<&InSector>,
             Option<&InteractionQueue>,
+            (Option<&Wallet>, Option<&Owner>),
         ),
         With<Selected>,
     >,
@@ -288,6 +301,7 @@ pub fn list_selection_details(
                     _,
                     in_sector,
                     interaction_queue,
+                    (wallet, owner),
                 ) = selected.single();
                 draw_ship_summary_row(
                     &images, ui, selectable, name, inventory, velocity, task_queue,
@@ -308,6 +322,21 @@ pub fn list_selection_details(
                     ));
                 }
 
+                if let Some(wallet) = wallet {
+                    ui.label(format!("Credits: {}C", wallet.credits()));
+                }
+
+                if let Some(owner) = owner {
+                    match session_data.factions.get(&owner.faction) {
+                        Some(faction) => ui.label(format!(
+                            "Owned by {} ({}C)",
+                            faction.name,
+                            faction.wallet.credits()
+                        )),
+                        None => ui.label(format!("Owned by unknown faction {}", owner.faction)),
+                    };
+                }
+
                 if let Some(inventory) = inventory {
                     ui.heading("Inventory");
                     let inventory = inventory.inventory();
@@ -315,10 +344,9 @@ pub fn list_selection_details(
                         ui.label("Empty");
                     } else {
                         for (item_id, amount) in inventory {
-                            let item = game_data.items.get(item_id).unwrap();
                             ui.label(format!(
                                 "{} x {} (+{}, -{}, +{}(Prod))",
-                                item.name,
+                                item_name(&game_data, item_id),
                                 amount.currently_available,
                                 amount.planned_buying,
                                 amount.planned_selling,
@@ -339,10 +367,16 @@ pub fn list_selection_details(
                 if let Some(production) = production_module {
                     ui.heading("Production");
                     for (id, module) in &production.modules {
-                        let definition = game_data.production_modules.get(id).unwrap();
-                        ui.label(format!("  {}x {}", module.amount, definition.name));
-                        let recipe = game_data.item_recipes.get(&module.recipe).unwrap();
-                        ui.label(format!("    Active Recipe: {}", recipe.name));
+                        let name = game_data
+                            .production_modules
+                            .get(id)
+                            .map_or(UNKNOWN_DEFINITION_NAME, |x| x.name.as_str());
+                        ui.label(format!("  {}x {}", module.amount, name));
+                        let recipe_name = game_data
+                            .item_recipes
+                            .get(&module.recipe)
+                            .map_or(UNKNOWN_DEFINITION_NAME, |x| x.name.as_str());
+                        ui.label(format!("    Active Recipe: {}", recipe_name));
                         if let Some(finished_at) = module.current_run_finished_at {
                             ui.label(format!(
                                 "      Done in {}",
@@ -360,7 +394,7 @@ pub fn list_selection_details(
                         ui.label(format!(
                             "Buying {}x{} for {}C",
                             data.amount,
-                            game_data.items.get(item_id).unwrap().name,
+                            item_name(&game_data, item_id),
                             data.price
                         ));
                     }
@@ -371,7 +405,7 @@ pub fn list_selection_details(
                         ui.label(format!(
                             "Selling {}x{} for {}C",
                             data.amount,
-                            game_data.items.get(item_id).unwrap().name,
+                            item_name(&game_data, item_id),
                             data.price
                         ));
                     }
@@ -380,8 +414,11 @@ pub fn list_selection_details(
                 if let Some(shipyard) = shipyard {
                     ui.heading("Ship Construction");
                     for (id, module) in &shipyard.modules {
-                        let definition = game_data.shipyard_modules.get(id).unwrap();
-                        ui.label(format!("{}x {}", module.amount, definition.name));
+                        let name = game_data
+                            .shipyard_modules
+                            .get(id)
+                            .map_or(UNKNOWN_DEFINITION_NAME, |x| x.name.as_str());
+                        ui.label(format!("{}x {}", module.amount, name));
 
                         for order in &module.active {
                             let definition = session_data
@@ -426,16 +463,16 @@ pub fn list_selection_details(
                                     }
                                     TaskInsideQueue::Undock => "Undock".to_string(),
                                     TaskInsideQueue::ExchangeWares { data, .. } => match data {
-                                        ExchangeWareData::Buy(item_id, amount) => {
+                                        ExchangeWareData::Buy(item_id, amount, _) => {
                                             format!(
                                                 "Buy {amount}x{}",
-                                                game_data.items.get(item_id).unwrap().name
+                                                item_name(&game_data, item_id)
                                             )
                                         }
-                                        ExchangeWareData::Sell(item_id, amount) => {
+                                        ExchangeWareData::Sell(item_id, amount, _) => {
                                             format!(
                                                 "Sell {amount}x{}",
-                                                game_data.items.get(item_id).unwrap().name
+                                                item_name(&game_data, item_id)
                                             )
                                         }
                                     },
@@ -471,11 +508,155 @@ pub fn list_selection_details(
         .resizable(false)
         .vscroll(true)
         .show(context.ctx_mut(), |ui| {
-            for (_, selectable, name, storage, _, velocity, task_queue, _, _, _, _, _, _, _) in
+            let mut total_credits = 0;
+            for (_, selectable, name, storage, _, velocity, task_queue, .., (wallet, _)) in
                 selected.iter()
             {
                 draw_ship_summary_row(&images, ui, selectable, name, storage, velocity, task_queue);
+                total_credits += wallet.map_or(0, Wallet::credits);
             }
+            ui.label(format!("Total Credits: {total_credits}C"));
+        });
+}
+
+pub fn draw_loading_progress(mut context: EguiContexts, progress: Res<LoadingProgress>) {
+    fn draw_phase(ui: &mut Ui, name: &str, phase: &LoadingPhaseProgress) {
+        ui.label(format!("{name}: {}/{}", phase.spawned, phase.total));
+    }
+
+    egui::Window::new("Loading")
+        .anchor(Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .show(context.ctx_mut(), |ui| {
+            ui.heading("Loading...");
+            ui.add(egui::ProgressBar::new(progress.fraction()).show_percentage());
+            draw_phase(ui, "Sectors", &progress.sectors);
+            draw_phase(ui, "Gates", &progress.gates);
+            draw_phase(ui, "Stations", &progress.stations);
+            draw_phase(ui, "Ships", &progress.ships);
+            draw_phase(ui, "Tasks", &progress.tasks);
+        });
+}
+
+pub fn draw_save_slot_menu(
+    mut context: EguiContexts,
+    save_directory: Res<SaveDirectory>,
+    running_save_tasks: Res<RunningSaveTasks>,
+    autosave_status: Option<Res<AutosaveStatus>>,
+    mut save_status_events: EventReader<SaveGameStatusEvent>,
+    mut save_events: EventWriter<SaveGameEvent>,
+    mut load_events: EventWriter<LoadGameEvent>,
+    mut slot_names: Local<Option<Vec<String>>>,
+) {
+    // Only access the file system when something might have changed
+    if save_status_events.read().count() > 0 {
+        *slot_names = None;
+    }
+    let slot_names = slot_names.get_or_insert_with(|| {
+        save_directory
+            .list_slots()
+            .map(|slots| slots.into_iter().map(|x| x.name).collect())
+            .unwrap_or_default()
+    });
+
+    egui::Window::new("Save Slots")
+        .anchor(Align2::RIGHT_TOP, egui::Vec2::ZERO)
+        .default_open(false)
+        .resizable(false)
+        .show(context.ctx_mut(), |ui| {
+            if running_save_tasks.is_saving() {
+                ui.label("Saving...");
+            } else if ui.button("Quicksave (F5)").clicked() {
+                save_events.send(SaveGameEvent {
+                    slot_name: QUICK_SAVE_SLOT_NAME.to_string(),
+                    format: SaveFileFormat::default(),
+                });
+            }
+
+            if let Some(autosave_status) = &autosave_status {
+                draw_autosave_status(ui, autosave_status);
+            }
+
+            for slot_name in slot_names.iter() {
+                ui.horizontal(|ui| {
+                    ui.label(slot_name);
+                    if ui.button("Load").clicked() {
+                        load_events.send(LoadGameEvent {
+                            slot_name: slot_name.clone(),
+                        });
+                    }
+                });
+            }
+        });
+}
+
+fn draw_autosave_status(ui: &mut Ui, status: &AutosaveStatus) {
+    if status.is_saving() {
+        ui.label("Autosaving...");
+    } else if let Some(result) = &status.last_result {
+        let time = result.simulation_time.as_secs();
+        match &result.outcome {
+            Ok(()) => ui.label(format!("Autosaved into {} at {time}s", result.slot_name)),
+            Err(e) => ui.colored_label(
+                egui::Color32::RED,
+                format!("Autosave into {} at {time}s failed: {e}", result.slot_name),
+            ),
+        };
+    }
+
+    if status.skipped > 0 {
+        ui.label(format!(
+            "Skipped {} autosaves while other saves were still running",
+            status.skipped
+        ));
+    }
+}
+
+pub fn draw_simulation_speed_controls(
+    mut context: EguiContexts,
+    simulation_state: Res<State<SimulationState>>,
+    simulation_time: Res<SimulationTime>,
+    time: Res<Time<Virtual>>,
+    mut speed_events: EventWriter<SimulationSpeedEvent>,
+) {
+    let is_paused = simulation_state.get() == &SimulationState::Paused;
+
+    egui::Window::new("Simulation Speed")
+        .anchor(Align2::LEFT_BOTTOM, egui::Vec2::ZERO)
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .show(context.ctx_mut(), |ui| {
+            ui.label(format!(
+                "Tick {} ({:.1}s)",
+                simulation_time.tick(),
+                simulation_time.total().as_secs_f32()
+            ));
+            ui.horizontal(|ui| {
+                let label = if is_paused { "Resume" } else { "Pause" };
+                if ui.button(format!("{label} (Space)")).clicked() {
+                    speed_events.send(SimulationSpeedEvent::TogglePause);
+                }
+                if ui
+                    .add_enabled(is_paused, egui::Button::new("Step (.)"))
+                    .clicked()
+                {
+                    speed_events.send(SimulationSpeedEvent::AdvanceSingleTick);
+                }
+            });
+            ui.horizontal(|ui| {
+                for (index, speed) in SIMULATION_SPEEDS.into_iter().enumerate() {
+                    let is_selected = time.relative_speed() == speed;
+                    if ui
+                        .selectable_label(is_selected, format!("{speed}x ({})", index + 1))
+                        .clicked()
+                    {
+                        speed_events.send(SimulationSpeedEvent::SetSpeed(speed));
+                    }
+                }
+            });
         });
 }
 
@@ -518,6 +699,16 @@ fn draw_ship_summary_row(
     });
 }
 
+/// Displayed instead of names of definitions which have been removed from [GameData] while the game is running.
+const UNKNOWN_DEFINITION_NAME: &str = "<Unknown>";
+
+fn item_name<'a>(game_data: &'a GameData, item_id: &ItemId) -> &'a str {
+    game_data
+        .items
+        .get(item_id)
+        .map_or(UNKNOWN_DEFINITION_NAME, |x| x.name.as_str())
+}
+
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
 pub enum MouseCursorOverUiState {
     #[default]
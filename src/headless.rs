@@ -0,0 +1,20 @@
+use crate::constants;
+use bevy::app::{App, Plugin};
+use bevy::input::InputPlugin;
+use bevy::prelude::MinimalPlugins;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use std::time::Duration;
+
+/// Replaces everything related to windows, rendering and UI, so the simulation can run on machines without a GPU.
+///
+/// Every frame advances the simulation by exactly one fixed tick, and frames are run as fast as possible.
+pub struct HeadlessPlugin;
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((MinimalPlugins, StatesPlugin, InputPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+                1.0 / constants::TICKS_PER_SECOND,
+            )));
+    }
+}
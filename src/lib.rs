@@ -0,0 +1,78 @@
+//! An economy focused space sandbox. Everything except for assembling the app lives in here,
+//! so benchmarks are able to run the real systems.
+
+use bevy::asset::AssetServer;
+use bevy::core::Name;
+use bevy::prelude::{Camera2dBundle, Commands, Handle, Image, Res, Resource};
+use bevy::render::camera::ScalingMode;
+
+pub mod camera;
+pub mod components;
+pub mod constants;
+pub mod diagnostics;
+pub mod entity_selection;
+pub mod game_data;
+pub mod gizmos;
+pub mod gui;
+pub mod headless;
+pub mod map_layout;
+pub mod pathfinding;
+pub mod persistence;
+pub mod session_data;
+pub mod simulation;
+pub mod states;
+pub mod trade_plan;
+pub mod utils;
+
+/// Only exists with a renderer. Without one (see [headless::HeadlessPlugin]), entities are spawned without any sprites.
+#[derive(Resource)]
+pub struct SpriteHandles {
+    asteroid: Handle<Image>,
+    asteroid_selected: Handle<Image>,
+    gate: Handle<Image>,
+    gate_selected: Handle<Image>,
+    planet: Handle<Image>,
+    planet_selected: Handle<Image>,
+    ship: Handle<Image>,
+    ship_selected: Handle<Image>,
+    star: Handle<Image>,
+    star_selected: Handle<Image>,
+    station: Handle<Image>,
+    station_selected: Handle<Image>,
+    icon_item_a: Handle<Image>,
+    icon_item_b: Handle<Image>,
+    icon_item_c: Handle<Image>,
+    icon_unknown: Handle<Image>,
+    icon_ship: Handle<Image>,
+}
+
+pub fn initialize_data(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let sprites = SpriteHandles {
+        asteroid: asset_server.load("asteroid.png"),
+        asteroid_selected: asset_server.load("asteroid_selected.png"),
+        gate: asset_server.load("gate.png"),
+        gate_selected: asset_server.load("gate_selected.png"),
+        planet: asset_server.load("planet.png"),
+        planet_selected: asset_server.load("planet_selected.png"),
+        ship: asset_server.load("ship.png"),
+        ship_selected: asset_server.load("ship_selected.png"),
+        star: asset_server.load("star.png"),
+        star_selected: asset_server.load("star_selected.png"),
+        station: asset_server.load("station.png"),
+        station_selected: asset_server.load("station_selected.png"),
+        icon_item_a: asset_server.load("ui_icons/items/a.png"),
+        icon_item_b: asset_server.load("ui_icons/items/b.png"),
+        icon_item_c: asset_server.load("ui_icons/items/c.png"),
+        icon_unknown: asset_server.load("ui_icons/items/unknown.png"),
+        icon_ship: asset_server.load("ui_icons/items/ship.png"),
+    };
+    commands.insert_resource(sprites);
+
+    let mut camera_bundle = Camera2dBundle::default();
+    camera_bundle.projection.scaling_mode = ScalingMode::WindowSize(1.0);
+    commands.spawn((
+        Name::new("Camera"),
+        camera::MainCameraBundle::default(),
+        camera_bundle,
+    ));
+}
@@ -1,37 +1,143 @@
-use crate::game_data::GameData;
-use crate::session_data::SessionData;
-use bevy::asset::AssetServer;
-use bevy::core::Name;
-use bevy::prelude::{
-    App, Camera2dBundle, Commands, Handle, Image, ImagePlugin, PluginGroup, Res, Resource, Startup,
-    Window, WindowPlugin,
-};
-use bevy::render::camera::ScalingMode;
+mod cli;
+
+use crate::cli::UniverseSource;
+use bevy::prelude::{App, ImagePlugin, PluginGroup, Startup, Time, Window, WindowPlugin};
+use bevy::time::{Fixed, TimeUpdateStrategy};
 use bevy::DefaultPlugins;
-mod camera;
-mod components;
-mod constants;
-mod diagnostics;
-mod entity_selection;
-mod game_data;
-mod gizmos;
-mod gui;
-mod map_layout;
-mod pathfinding;
-mod persistence;
-mod session_data;
-mod simulation;
-mod states;
-mod trade_plan;
-mod utils;
+use bevy_space::game_data::GameData;
+use bevy_space::persistence::replay::{
+    Replay, ReplayPlaybackPlugin, ReplayRecorder, ReplayRecordingPlugin,
+};
+use bevy_space::persistence::scenario::Scenario;
+use bevy_space::persistence::universe_generator::UniverseGenerator;
+use bevy_space::persistence::UniverseSaveData;
+use bevy_space::session_data::SessionData;
+use bevy_space::{
+    camera, diagnostics, entity_selection, game_data, gizmos, gui, headless, persistence,
+    simulation, states,
+};
+use std::path::Path;
+use std::time::Duration;
 
 fn main() {
+    let mut arguments = match cli::Command::from_env() {
+        Ok(cli::Command::Run(arguments)) => arguments,
+        Ok(cli::Command::CompareChecksums(a, b)) => {
+            compare_checksum_logs(&a, &b);
+            return;
+        }
+        Ok(cli::Command::PrintHelp) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{e}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let mut replay_inputs = None;
+    let (game_data, universe) = if let UniverseSource::Replay(path) = &arguments.universe {
+        let replay =
+            Replay::read_from_file(path).unwrap_or_else(|e| panic!("Unable to load replay: {e}"));
+        arguments.seed = replay.seed;
+        arguments.ticks_per_second = replay.ticks_per_second;
+        arguments.deterministic = true;
+        replay_inputs = Some(replay.inputs);
+        (replay.game_data, replay.initial_state)
+    } else {
+        let game_data = load_game_data();
+        let universe = load_universe_data(&arguments, &game_data);
+        (game_data, universe)
+    };
+
     let mut app = App::new();
+    if arguments.headless {
+        app.add_plugins(headless::HeadlessPlugin);
+    } else {
+        // Game data changes can't be recorded or played back
+        let hot_reload = arguments.record_replay.is_none() && replay_inputs.is_none();
+        add_graphical_plugins(&mut app, universe.ships.data.len(), hot_reload);
+    }
+
+    app.add_plugins((
+        persistence::SaveFilePlugin,
+        persistence::UniverseSaveDataLoadingPlugin,
+        simulation::plugin::SimulationPlugin,
+        states::StatePlugin,
+    ))
+    .insert_resource(game_data)
+    .insert_resource(SessionData::mock_data());
+
+    set_tick_rate(&mut app, arguments.ticks_per_second, arguments.headless);
+
+    if arguments.deterministic {
+        app.add_plugins(simulation::determinism::DeterministicSimulationPlugin {
+            seed: arguments.seed,
+        });
+    }
+
+    if let Some(duration) = arguments.run_for {
+        app.add_plugins(simulation::prelude::ExitAfterSimulatedTimePlugin { duration });
+    }
+
+    if let Some(interval) = arguments.autosave_interval {
+        app.add_plugins(persistence::AutosavePlugin {
+            interval,
+            slot_count: arguments.autosave_slots,
+        });
+    }
+
+    if let Some(path) = &arguments.record_replay {
+        let recorder = ReplayRecorder::create(
+            path,
+            arguments.seed,
+            arguments.ticks_per_second,
+            app.world().resource::<GameData>(),
+            &universe,
+        )
+        .unwrap_or_else(|e| panic!("Unable to record replay: {e}"));
+        app.insert_resource(recorder)
+            .add_plugins(ReplayRecordingPlugin);
+    }
+
+    if let Some(inputs) = replay_inputs {
+        app.add_plugins(ReplayPlaybackPlugin {
+            inputs,
+            stop_at_tick: arguments.stop_at_tick,
+            exit_at_stop: arguments.headless,
+        });
+    }
+
+    if let Some(output) = arguments.checksum_log {
+        app.add_plugins(simulation::state_checksum::StateChecksumPlugin {
+            interval: arguments.checksum_interval,
+            output,
+        });
+    }
+
+    universe.insert_as_resources(app.world_mut());
+
+    app.run();
+}
+
+/// Overrides the default of [bevy_space::constants::TICKS_PER_SECOND].
+/// Headless runs advance by exactly one tick per frame, so their frame duration has to change as well.
+fn set_tick_rate(app: &mut App, ticks_per_second: f64, headless: bool) {
+    app.insert_resource(Time::<Fixed>::from_hz(ticks_per_second));
+    if headless {
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / ticks_per_second,
+        )));
+    }
+}
+
+fn add_graphical_plugins(app: &mut App, ship_count: usize, hot_reload: bool) {
     app.add_plugins(
         DefaultPlugins
             .set(WindowPlugin {
                 primary_window: Some(Window {
-                    title: get_window_title(),
+                    title: get_window_title(ship_count),
                     ..Default::default()
                 }),
                 ..Default::default()
@@ -45,82 +151,69 @@ fn main() {
         entity_selection::EntitySelectionPlugin,
         gizmos::GizmoPlugin,
         gui::GUIPlugin,
-        persistence::UniverseSaveDataLoadingOnStartupPlugin,
-        persistence::test_universe::TestUniverseDataPlugin,
-        simulation::plugin::SimulationPlugin,
-        states::StatePlugin,
     ))
-    .insert_resource(GameData::mock_data())
-    .insert_resource(SessionData::mock_data())
-    .add_systems(Startup, initialize_data);
+    .add_systems(Startup, bevy_space::initialize_data);
 
-    app.run();
+    if hot_reload {
+        app.add_plugins(game_data::GameDataHotReloadPlugin);
+    }
 }
 
-fn get_window_title() -> String {
-    let config = if cfg!(debug_assertions) {
-        "DEBUG"
-    } else {
-        "RELEASE"
-    };
+fn load_game_data() -> GameData {
+    let directory = Path::new(game_data::DEFAULT_GAME_DATA_DIRECTORY);
+    GameData::load_from_directory(directory)
+        .unwrap_or_else(|e| panic!("Unable to load game data: {e}"))
+}
 
-    format!(
-        "{} ships [{config}]",
-        constants::TRADE_SHIP_COUNT
-            + constants::MINING_SHIP_COUNT
-            + constants::HARVESTING_SHIP_COUNT
-    )
+fn load_universe_data(
+    arguments: &cli::CommandLineArguments,
+    game_data: &GameData,
+) -> UniverseSaveData {
+    match &arguments.universe {
+        UniverseSource::SaveSlot(slot_name) => load_save_slot(slot_name),
+        UniverseSource::Scenario(path) => Scenario::read_from_file(path)
+            .and_then(|scenario| scenario.into_universe_save_data(game_data))
+            .unwrap_or_else(|e| panic!("Unable to load scenario: {e}")),
+        UniverseSource::Generated { rings, ships } => {
+            UniverseGenerator::new(arguments.seed, *rings)
+                .with_ship_counts(*ships)
+                .generate()
+        }
+        UniverseSource::Replay(_) => unreachable!("Replays are loaded separately"),
+    }
+}
+
+/// Compares the checksum logs written by two runs with `--checksum-log <path>`.
+fn compare_checksum_logs(a: &Path, b: &Path) {
+    match simulation::state_checksum::compare_checksum_logs(a, b) {
+        Ok(None) => println!("No divergence found."),
+        Ok(Some(divergence)) => println!("{divergence}"),
+        Err(e) => panic!("Unable to compare checksum logs: {e}"),
+    }
 }
 
-#[derive(Resource)]
-#[cfg_attr(test, derive(Default))]
-pub struct SpriteHandles {
-    asteroid: Handle<Image>,
-    asteroid_selected: Handle<Image>,
-    gate: Handle<Image>,
-    gate_selected: Handle<Image>,
-    planet: Handle<Image>,
-    planet_selected: Handle<Image>,
-    ship: Handle<Image>,
-    ship_selected: Handle<Image>,
-    star: Handle<Image>,
-    star_selected: Handle<Image>,
-    station: Handle<Image>,
-    station_selected: Handle<Image>,
-    icon_item_a: Handle<Image>,
-    icon_item_b: Handle<Image>,
-    icon_item_c: Handle<Image>,
-    icon_unknown: Handle<Image>,
-    icon_ship: Handle<Image>,
+fn load_save_slot(slot_name: &str) -> UniverseSaveData {
+    let save_directory = persistence::SaveDirectory::default();
+    match save_directory.read(slot_name) {
+        Ok(data) => data,
+        Err(e) => {
+            let available_slots = save_directory
+                .list_slots()
+                .map(|slots| slots.into_iter().map(|x| x.name).collect::<Vec<_>>())
+                .unwrap_or_default();
+            panic!(
+                "Unable to load save slot {slot_name}: {e}\nAvailable slots: {available_slots:?}"
+            );
+        }
+    }
 }
 
-pub fn initialize_data(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let sprites = SpriteHandles {
-        asteroid: asset_server.load("asteroid.png"),
-        asteroid_selected: asset_server.load("asteroid_selected.png"),
-        gate: asset_server.load("gate.png"),
-        gate_selected: asset_server.load("gate_selected.png"),
-        planet: asset_server.load("planet.png"),
-        planet_selected: asset_server.load("planet_selected.png"),
-        ship: asset_server.load("ship.png"),
-        ship_selected: asset_server.load("ship_selected.png"),
-        star: asset_server.load("star.png"),
-        star_selected: asset_server.load("star_selected.png"),
-        station: asset_server.load("station.png"),
-        station_selected: asset_server.load("station_selected.png"),
-        icon_item_a: asset_server.load("ui_icons/items/a.png"),
-        icon_item_b: asset_server.load("ui_icons/items/b.png"),
-        icon_item_c: asset_server.load("ui_icons/items/c.png"),
-        icon_unknown: asset_server.load("ui_icons/items/unknown.png"),
-        icon_ship: asset_server.load("ui_icons/items/ship.png"),
+fn get_window_title(ship_count: usize) -> String {
+    let config = if cfg!(debug_assertions) {
+        "DEBUG"
+    } else {
+        "RELEASE"
     };
-    commands.insert_resource(sprites);
-
-    let mut camera_bundle = Camera2dBundle::default();
-    camera_bundle.projection.scaling_mode = ScalingMode::WindowSize(1.0);
-    commands.spawn((
-        Name::new("Camera"),
-        camera::MainCameraBundle::default(),
-        camera_bundle,
-    ));
+
+    format!("{ship_count} ships [{config}]")
 }
@@ -66,7 +66,10 @@ where
                 }
             }
 
-            for (to_sector, _) in &sector.gates {
+            // Gates are stored in a HashMap, sorting them keeps the search order reproducible
+            let mut connected_sectors: Vec<_> = sector.gates.keys().collect();
+            connected_sectors.sort();
+            for to_sector in connected_sectors {
                 if !visited.contains(to_sector) {
                     next_next.push(to_sector);
                 }
@@ -0,0 +1,212 @@
+//! Regularly saves the universe while the simulation is running, driven by [SimulationTime].
+//!
+//! Autosaves rotate through a fixed amount of slots, so a save which got corrupted by a crash never
+//! replaces the only copy of a long running universe.
+
+use crate::persistence::save_file_format::SaveFileFormat;
+use crate::persistence::save_files::{
+    RunningSaveTasks, SaveDirectory, SaveGameEvent, SaveGameStatus, SaveGameStatusEvent, SaveSlot,
+};
+use crate::simulation::prelude::SimulationTime;
+use crate::states::ApplicationState;
+use bevy::app::{App, Plugin, Update};
+use bevy::log::warn;
+use bevy::prelude::{
+    in_state, EventReader, EventWriter, IntoSystemConfigs, OnEnter, Res, ResMut, Resource,
+};
+use std::time::Duration;
+
+pub const AUTOSAVE_SLOT_PREFIX: &str = "autosave_";
+pub const DEFAULT_AUTOSAVE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+pub const DEFAULT_AUTOSAVE_SLOT_COUNT: usize = 3;
+
+/// Sends a [SaveGameEvent] whenever `interval` has passed in simulated time, cycling through `slot_count` slots.
+/// Requires [crate::persistence::SaveFilePlugin] to actually write anything.
+pub struct AutosavePlugin {
+    pub interval: Duration,
+    pub slot_count: usize,
+}
+
+impl Default for AutosavePlugin {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_AUTOSAVE_INTERVAL,
+            slot_count: DEFAULT_AUTOSAVE_SLOT_COUNT,
+        }
+    }
+}
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        assert!(self.slot_count > 0, "Autosaves require at least one slot!");
+
+        app.insert_resource(AutosaveTimer {
+            interval: self.interval,
+            slot_count: self.slot_count,
+            next_save: None,
+            next_slot: None,
+        })
+        .init_resource::<AutosaveStatus>()
+        .add_systems(OnEnter(ApplicationState::InGame), reset_autosave_timer)
+        .add_systems(
+            Update,
+            (send_autosave_event, update_autosave_status)
+                .run_if(in_state(ApplicationState::InGame)),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct AutosaveTimer {
+    interval: Duration,
+    slot_count: usize,
+    /// Unset until the universe has been loaded, since loaded universes don't start at zero.
+    next_save: Option<Duration>,
+    /// Unset until the first autosave, which continues after the most recently written slot.
+    next_slot: Option<usize>,
+}
+
+/// Keeps track of the latest autosave, so it can be displayed.
+#[derive(Resource, Default)]
+pub struct AutosaveStatus {
+    in_progress: Option<(String, Duration)>,
+    pub last_result: Option<AutosaveResult>,
+    /// How many autosaves were skipped because a different save was still being written.
+    pub skipped: u32,
+}
+
+pub struct AutosaveResult {
+    pub slot_name: String,
+    /// The simulated time at which this autosave was taken.
+    pub simulation_time: Duration,
+    pub outcome: Result<(), String>,
+}
+
+impl AutosaveStatus {
+    #[inline]
+    pub fn is_saving(&self) -> bool {
+        self.in_progress.is_some()
+    }
+}
+
+pub fn autosave_slot_name(index: usize) -> String {
+    format!("{AUTOSAVE_SLOT_PREFIX}{}", index + 1)
+}
+
+/// Returns the index of the slot following the most recently modified autosave slot.
+fn next_autosave_slot(existing_slots: &[SaveSlot], slot_count: usize) -> usize {
+    (0..slot_count)
+        .filter_map(|index| {
+            let name = autosave_slot_name(index);
+            existing_slots
+                .iter()
+                .find(|slot| slot.name == name)
+                .and_then(|slot| slot.last_modified)
+                .map(|last_modified| (last_modified, index))
+        })
+        .max()
+        .map_or(0, |(_, index)| (index + 1) % slot_count)
+}
+
+fn reset_autosave_timer(mut timer: ResMut<AutosaveTimer>, mut status: ResMut<AutosaveStatus>) {
+    timer.next_save = None;
+    // Save events are only handled while in game, so an autosave sent right before a different universe
+    // started loading will never report back.
+    status.in_progress = None;
+}
+
+fn send_autosave_event(
+    simulation_time: Res<SimulationTime>,
+    save_directory: Res<SaveDirectory>,
+    running_save_tasks: Res<RunningSaveTasks>,
+    mut timer: ResMut<AutosaveTimer>,
+    mut status: ResMut<AutosaveStatus>,
+    mut save_events: EventWriter<SaveGameEvent>,
+) {
+    let now = simulation_time.total();
+    let Some(next_save) = timer.next_save else {
+        timer.next_save = Some(now + timer.interval);
+        return;
+    };
+
+    if now < next_save {
+        return;
+    }
+
+    timer.next_save = Some(now + timer.interval);
+    if running_save_tasks.is_saving() || status.is_saving() {
+        warn!("Skipped autosave, since another save is still being written.");
+        status.skipped += 1;
+        return;
+    }
+
+    let slot_count = timer.slot_count;
+    let slot = *timer.next_slot.get_or_insert_with(|| {
+        let existing_slots = save_directory.list_slots().unwrap_or_default();
+        next_autosave_slot(&existing_slots, slot_count)
+    });
+    timer.next_slot = Some((slot + 1) % slot_count);
+
+    let slot_name = autosave_slot_name(slot);
+    status.in_progress = Some((slot_name.clone(), now));
+    save_events.send(SaveGameEvent {
+        slot_name,
+        format: SaveFileFormat::default(),
+    });
+}
+
+fn update_autosave_status(
+    mut status_events: EventReader<SaveGameStatusEvent>,
+    mut status: ResMut<AutosaveStatus>,
+) {
+    for event in status_events.read() {
+        let outcome = match &event.status {
+            SaveGameStatus::Started => continue,
+            SaveGameStatus::Finished => Ok(()),
+            SaveGameStatus::Failed(e) => Err(e.to_string()),
+        };
+
+        let is_current_autosave = status
+            .in_progress
+            .as_ref()
+            .is_some_and(|(slot_name, _)| slot_name == &event.slot_name);
+        if !is_current_autosave {
+            continue;
+        }
+
+        let (slot_name, simulation_time) = status.in_progress.take().unwrap();
+        status.last_result = Some(AutosaveResult {
+            slot_name,
+            simulation_time,
+            outcome,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::SystemTime;
+
+    fn slot(index: usize, seconds_since_epoch: u64) -> SaveSlot {
+        SaveSlot {
+            name: autosave_slot_name(index),
+            path: PathBuf::new(),
+            last_modified: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds_since_epoch)),
+        }
+    }
+
+    #[test]
+    fn autosaves_continue_after_most_recent_slot() {
+        assert_eq!(next_autosave_slot(&[], 3), 0);
+        assert_eq!(next_autosave_slot(&[slot(0, 10), slot(1, 20)], 3), 2);
+        assert_eq!(
+            next_autosave_slot(&[slot(0, 30), slot(1, 20), slot(2, 10)], 3),
+            1
+        );
+        assert_eq!(next_autosave_slot(&[slot(0, 10), slot(2, 20)], 3), 0);
+        // Slots beyond the current count are left over from previous configurations
+        assert_eq!(next_autosave_slot(&[slot(4, 50), slot(0, 10)], 3), 1);
+    }
+}
@@ -1,16 +1,18 @@
 use crate::components::{Sector, SectorStarComponent, Star};
-use crate::persistence::data::v1::*;
+use crate::persistence::data::latest::*;
+use crate::persistence::loading_plugin::LoadingProgress;
 use crate::persistence::local_hex_position::LocalHexPosition;
 use crate::persistence::{GateIdMap, PersistentGateId, SectorIdMap};
+use crate::states::LoadingState;
 use crate::utils::spawn_helpers::spawn_gate_pair;
-use crate::SpriteHandles;
+use crate::{constants, SpriteHandles};
 use bevy::ecs::system::SystemParam;
-use bevy::prelude::{Commands, Query, Res};
+use bevy::prelude::{Commands, NextState, Query, Res, ResMut};
 
 #[derive(SystemParam)]
 pub struct Args<'w, 's> {
     commands: Commands<'w, 's>,
-    sprites: Res<'w, SpriteHandles>,
+    sprites: Option<Res<'w, SpriteHandles>>,
     sectors: Query<'w, 's, (&'static mut Sector, Option<&'static SectorStarComponent>)>,
     stars: Query<'w, 's, &'static Star>,
 
@@ -19,14 +21,24 @@ pub struct Args<'w, 's> {
 
 type SaveData = SaveDataCollection<GatePairSaveData>;
 
-pub fn spawn_all(data: Res<SaveData>, mut args: Args) {
-    let mut gate_id_map = GateIdMap::new();
-    for builder in &data.data {
+pub fn spawn_batch(
+    data: Res<SaveData>,
+    mut progress: ResMut<LoadingProgress>,
+    mut next_state: ResMut<NextState<LoadingState>>,
+    mut gate_id_map: ResMut<GateIdMap>,
+    mut args: Args,
+) {
+    let batch = progress
+        .gates
+        .next_batch(&data.data, constants::ENTITIES_LOADED_PER_FRAME);
+    for builder in batch {
         builder.build(&mut args, &mut gate_id_map);
     }
 
-    args.commands.remove_resource::<SaveData>();
-    args.commands.insert_resource(gate_id_map);
+    if progress.gates.is_done() {
+        args.commands.remove_resource::<SaveData>();
+        next_state.set(LoadingState::Stations);
+    }
 }
 
 impl SaveData {
@@ -50,7 +62,7 @@ impl GatePairSaveData {
             gate_id_map,
             &mut args.sectors,
             &args.stars,
-            &args.sprites,
+            args.sprites.as_deref(),
             self.from_id,
             self.from_position.to_sector_position(&args.sector_id_map),
             self.to_id,
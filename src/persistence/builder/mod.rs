@@ -4,38 +4,52 @@
 pub mod gate;
 pub mod planet;
 pub mod sector;
+pub mod session;
 pub mod ship;
 pub mod station;
+pub mod task;
 
-pub use crate::persistence::loading_plugin::UniverseSaveDataLoadingOnStartupPlugin;
+pub use crate::persistence::loading_plugin::UniverseSaveDataLoadingPlugin;
 
 #[cfg(test)]
 mod test_helpers {
     use super::*;
     use crate::game_data::GameData;
     use crate::map_layout::MapLayout;
-    use crate::persistence::data::v1::UniverseSaveData;
+    use crate::persistence::data::latest::UniverseSaveData;
+    use crate::session_data::SessionData;
     use crate::simulation::precomputed_orbit_directions::PrecomputedOrbitDirections;
-    use crate::SpriteHandles;
+    use crate::simulation::prelude::SimulationTime;
+    use crate::states::{ApplicationState, StatePlugin};
     use bevy::prelude::*;
+    use bevy::state::app::StatesPlugin;
+
+    /// Loading is spread across multiple frames, this should be more than enough for any test.
+    const MAX_LOADING_UPDATES: usize = 100;
 
     impl UniverseSaveData {
         pub fn build_test_app(self) -> App {
             let mut app = App::new();
             app.init_resource::<MapLayout>();
-            app.init_resource::<SpriteHandles>();
             app.init_resource::<PrecomputedOrbitDirections>();
             app.insert_resource(GameData::mock_data());
-            app.insert_resource(self.sectors);
-            app.insert_resource(self.gate_pairs);
-            app.insert_resource(self.stations);
-            app.insert_resource(self.ships);
+            app.init_resource::<SessionData>();
+            app.init_resource::<SimulationTime>();
+            self.insert_as_resources(app.world_mut());
 
-            app.add_plugins(UniverseSaveDataLoadingOnStartupPlugin);
+            app.add_plugins((StatesPlugin, StatePlugin, UniverseSaveDataLoadingPlugin));
             app.finish();
-            app.update();
 
-            app
+            for _ in 0..MAX_LOADING_UPDATES {
+                app.update();
+                if app.world().resource::<State<ApplicationState>>().get()
+                    == &ApplicationState::InGame
+                {
+                    return app;
+                }
+            }
+
+            panic!("Universe did not finish loading within {MAX_LOADING_UPDATES} updates!");
         }
     }
 }
@@ -1,4 +1,4 @@
-use crate::persistence::data::v1::*;
+use crate::persistence::data::latest::*;
 use crate::persistence::PersistentPlanetId;
 use crate::utils::{EarthMass, SolarMass};
 
@@ -1,15 +1,19 @@
 use crate::map_layout::MapLayout;
-use crate::persistence::data::v1::{SaveDataCollection, SectorAsteroidSaveData, SectorSaveData};
+use crate::persistence::data::latest::{
+    SaveDataCollection, SectorAsteroidSaveData, SectorSaveData,
+};
+use crate::persistence::loading_plugin::LoadingProgress;
 use crate::persistence::{
     AsteroidIdMap, AsteroidSaveData, PersistentAsteroidId, PlanetIdMap, SectorFeatureSaveData,
     SectorIdMap, SectorPlanetSaveData, SectorStarSaveData,
 };
 use crate::simulation::precomputed_orbit_directions::PrecomputedOrbitDirections;
 use crate::simulation::time::SimulationTimestamp;
+use crate::states::LoadingState;
 use crate::utils::{spawn_helpers, SectorEntity};
 use crate::{constants, SpriteHandles};
 use bevy::ecs::system::SystemParam;
-use bevy::prelude::{Circle, Commands, Res, ShapeSample, Vec2};
+use bevy::prelude::{Circle, Commands, NextState, Res, ResMut, ShapeSample, Vec2};
 use hexx::Hex;
 use rand::distributions::Distribution;
 use rand::prelude::StdRng;
@@ -18,27 +22,35 @@ use rand::{Rng, SeedableRng};
 #[derive(SystemParam)]
 pub struct Args<'w, 's> {
     commands: Commands<'w, 's>,
-    sprites: Res<'w, SpriteHandles>,
+    sprites: Option<Res<'w, SpriteHandles>>,
     map_layout: Res<'w, MapLayout>,
     orbit_directions: Res<'w, PrecomputedOrbitDirections>,
 }
 
 type SaveData = SaveDataCollection<SectorSaveData>;
 
-pub fn spawn_all(data: Res<SaveData>, mut args: Args) {
-    let mut sector_id_map = SectorIdMap::new();
-    let mut asteroid_id_map = AsteroidIdMap::new();
-    let mut planet_id_map = PlanetIdMap::new();
-    for builder in &data.data {
+pub fn spawn_batch(
+    data: Res<SaveData>,
+    mut progress: ResMut<LoadingProgress>,
+    mut next_state: ResMut<NextState<LoadingState>>,
+    mut sector_id_map: ResMut<SectorIdMap>,
+    mut asteroid_id_map: ResMut<AsteroidIdMap>,
+    mut planet_id_map: ResMut<PlanetIdMap>,
+    mut args: Args,
+) {
+    let batch = progress
+        .sectors
+        .next_batch(&data.data, constants::SECTORS_LOADED_PER_FRAME);
+    for builder in batch {
         let coordinate = builder.coordinate;
         let entity = builder.build(&mut args, &mut asteroid_id_map, &mut planet_id_map);
         sector_id_map.insert(coordinate, entity);
     }
 
-    args.commands.remove_resource::<SaveData>();
-    args.commands.insert_resource(sector_id_map);
-    args.commands.insert_resource(asteroid_id_map);
-    args.commands.insert_resource(planet_id_map);
+    if progress.sectors.is_done() {
+        args.commands.remove_resource::<SaveData>();
+        next_state.set(LoadingState::Gates);
+    }
 }
 
 impl SaveData {
@@ -63,7 +75,7 @@ impl SectorSaveData {
             &args.map_layout.hex_layout,
             self.coordinate,
             &self.features,
-            &args.sprites,
+            args.sprites.as_deref(),
             asteroid_id_map,
             planet_id_map,
             &args.orbit_directions,
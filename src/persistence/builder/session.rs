@@ -0,0 +1,99 @@
+use crate::components::Wallet;
+use crate::game_data::ItemRecipeElement;
+use crate::persistence::data::latest::*;
+use crate::session_data::{Faction, FactionRelations, Relation, SessionData, ShipConfiguration};
+use crate::simulation::prelude::SimulationTime;
+use crate::simulation::ship_ai::DeliveryDeadlines;
+use bevy::prelude::{Commands, Res};
+use bevy::utils::HashMap;
+
+/// Applies the global state which has been stored alongside the loaded universe.
+/// Anything without save data, such as universes built inside tests, keeps whatever was set up during app initialization.
+pub fn restore(
+    mut commands: Commands,
+    session: Option<Res<SessionSaveData>>,
+    simulation_time: Option<Res<SimulationTimeSaveData>>,
+    persistent_id_counters: Option<Res<PersistentIdCountersSaveData>>,
+    ships: Option<Res<SaveDataCollection<ShipSaveData>>>,
+) {
+    if let Some(session) = session {
+        commands.insert_resource(session.parse());
+        commands.remove_resource::<SessionSaveData>();
+    }
+
+    if let Some(simulation_time) = simulation_time {
+        commands.insert_resource(simulation_time.parse());
+        commands.remove_resource::<SimulationTimeSaveData>();
+    }
+
+    // Deadlines are registered again while the ships' tasks are being built
+    commands.insert_resource(DeliveryDeadlines::default());
+
+    // Counters are always reset, so they don't depend on any previously loaded universe
+    let ship_ids = ships.iter().flat_map(|x| x.data.iter().map(|ship| ship.id));
+    if let Some(persistent_id_counters) = persistent_id_counters {
+        commands.insert_resource(persistent_id_counters.parse(ship_ids));
+        commands.remove_resource::<PersistentIdCountersSaveData>();
+    } else {
+        commands.insert_resource(PersistentIdCountersSaveData::default().parse(ship_ids));
+    }
+}
+
+impl SessionSaveData {
+    pub fn parse(&self) -> SessionData {
+        let mut relations = FactionRelations::default();
+        for x in &self.relations {
+            relations.set(
+                x.faction,
+                x.towards,
+                Relation {
+                    favor: x.favor,
+                    perception: x.perception,
+                },
+            );
+        }
+
+        SessionData {
+            ship_configurations: HashMap::from_iter(
+                self.ship_configurations.iter().map(|x| (x.id, x.parse())),
+            ),
+            factions: HashMap::from_iter(self.factions.iter().map(|x| (x.id, x.parse()))),
+            relations,
+        }
+    }
+}
+
+impl ShipConfigurationSaveData {
+    pub fn parse(&self) -> ShipConfiguration {
+        ShipConfiguration {
+            id: self.id,
+            name: self.name.clone(),
+            duration: self.duration,
+            materials: self
+                .materials
+                .iter()
+                .map(|(item_id, amount)| ItemRecipeElement {
+                    item_id: *item_id,
+                    amount: *amount,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl FactionSaveData {
+    pub fn parse(&self) -> Faction {
+        Faction {
+            id: self.id,
+            name: self.name.clone(),
+            wallet: Wallet::new(self.credits),
+            required_favor: self.required_favor,
+        }
+    }
+}
+
+impl SimulationTimeSaveData {
+    pub fn parse(&self) -> SimulationTime {
+        SimulationTime::new(self.total, self.tick)
+    }
+}
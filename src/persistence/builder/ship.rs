@@ -1,31 +1,91 @@
-use crate::components::Sector;
-use crate::persistence::data::v1::*;
+use crate::components::{
+    Asteroid, BuyOrders, Inventory, IsDocked, Owner, Sector, SellOrders, ShipAccount, Wallet,
+};
+use crate::persistence::data::latest::*;
+use crate::persistence::loading_plugin::LoadingProgress;
 use crate::persistence::local_hex_position::LocalHexPosition;
-use crate::persistence::{PersistentShipId, SectorIdMap, ShipIdMap};
-use crate::simulation::ship_ai::BehaviorBuilder;
-use crate::utils::spawn_helpers;
-use crate::SpriteHandles;
+use crate::persistence::{AllEntityIdMaps, PersistentShipId, SectorIdMap, ShipIdMap};
+use crate::session_data::{FactionId, SessionData};
+use crate::simulation::physics::ShipVelocity;
+use crate::simulation::prelude::SimulationTime;
+use crate::simulation::ship_ai::{BehaviorBuilder, DeliveryDeadlines, TaskInsideQueue, TaskQueue};
+use crate::states::{ApplicationState, LoadingState};
+use crate::utils::{spawn_helpers, ExchangeWareData, ShipEntity, TradeIntent, TypedEntity};
+use crate::{constants, utils, SpriteHandles};
 use bevy::ecs::system::SystemParam;
-use bevy::prelude::{Commands, Query, Res};
+use bevy::prelude::{Commands, Mut, NextState, Query, Res, ResMut, Visibility};
 
 type SaveData = SaveDataCollection<ShipSaveData>;
 
 #[derive(SystemParam)]
 pub struct Args<'w, 's> {
     commands: Commands<'w, 's>,
-    sprites: Res<'w, SpriteHandles>,
+    sprites: Option<Res<'w, SpriteHandles>>,
     sectors: Query<'w, 's, &'static mut Sector>,
     sector_id_map: Res<'w, SectorIdMap>,
 }
 
-pub fn spawn_all(data: Res<SaveData>, mut args: Args) {
-    let mut ship_id_map = ShipIdMap::new();
-    for builder in &data.data {
+#[derive(SystemParam)]
+pub struct TaskArgs<'w, 's> {
+    commands: Commands<'w, 's>,
+    simulation_time: Res<'w, SimulationTime>,
+    all_entity_id_maps: AllEntityIdMaps<'w>,
+    task_queues: Query<'w, 's, &'static mut TaskQueue>,
+    visibilities: Query<'w, 's, &'static mut Visibility>,
+    sectors: Query<'w, 's, &'static mut Sector>,
+    asteroids: Query<'w, 's, &'static mut Asteroid>,
+    inventories: Query<
+        'w,
+        's,
+        (
+            &'static mut Inventory,
+            Option<&'static mut Wallet>,
+            Option<&'static mut BuyOrders>,
+            Option<&'static mut SellOrders>,
+        ),
+    >,
+    owners: Query<'w, 's, &'static Owner>,
+    session_data: ResMut<'w, SessionData>,
+    delivery_deadlines: ResMut<'w, DeliveryDeadlines>,
+}
+
+pub fn spawn_batch(
+    data: Res<SaveData>,
+    mut progress: ResMut<LoadingProgress>,
+    mut next_state: ResMut<NextState<LoadingState>>,
+    mut ship_id_map: ResMut<ShipIdMap>,
+    mut args: Args,
+) {
+    let batch = progress
+        .ships
+        .next_batch(&data.data, constants::ENTITIES_LOADED_PER_FRAME);
+    for builder in batch {
         builder.build(&mut args, &mut ship_id_map);
     }
 
-    args.commands.remove_resource::<SaveData>();
-    args.commands.insert_resource(ship_id_map);
+    if progress.ships.is_done() {
+        next_state.set(LoadingState::Tasks);
+    }
+}
+
+/// Tasks may reference any other entity, including other ships, so they are restored once everything has been spawned.
+pub fn restore_task_batch(
+    data: Res<SaveData>,
+    mut progress: ResMut<LoadingProgress>,
+    mut next_state: ResMut<NextState<ApplicationState>>,
+    mut args: TaskArgs,
+) {
+    let batch = progress
+        .tasks
+        .next_batch(&data.data, constants::ENTITIES_LOADED_PER_FRAME);
+    for builder in batch {
+        builder.build_tasks(&mut args);
+    }
+
+    if progress.tasks.is_done() {
+        args.commands.remove_resource::<SaveData>();
+        next_state.set(ApplicationState::InGame);
+    }
 }
 
 impl SaveData {
@@ -44,19 +104,29 @@ impl SaveData {
             behavior,
             forward_velocity: 0.0,
             angular_velocity: 0.0,
-            task_queue: Vec::new(), // TODO
+            task_queue: Vec::new(),
             inventory: InventorySaveData { items: Vec::new() },
+            docked_at: None,
+            owner: None,
+            credits: constants::SHIP_STARTING_CREDITS,
         });
         self.data.last_mut().unwrap()
     }
 }
 
 impl ShipSaveData {
+    /// Owned ships trade on their faction's account, so they don't carry any credits of their own.
+    pub fn with_owner(&mut self, faction: FactionId) -> &mut Self {
+        self.owner = Some(faction);
+        self.credits = 0;
+        self
+    }
+
     pub fn build(&self, args: &mut Args, ship_id_map: &mut ShipIdMap) {
         let sector_entity = args.sector_id_map.id_to_entity()[&self.position.sector];
-        spawn_helpers::spawn_ship(
+        let entity = spawn_helpers::spawn_ship(
             &mut args.commands,
-            &args.sprites,
+            args.sprites.as_deref(),
             self.id,
             self.name.clone(),
             &mut args.sectors,
@@ -64,31 +134,145 @@ impl ShipSaveData {
             self.position.position,
             self.rotation_degrees,
             &BehaviorBuilder::from(self.behavior),
+            match self.owner {
+                Some(faction) => ShipAccount::Owner(Owner { faction }),
+                None => ShipAccount::Wallet(Wallet::new(self.credits)),
+            },
             ship_id_map,
         );
+
+        args.commands.entity(entity.into()).insert((
+            ShipVelocity {
+                forward: self.forward_velocity,
+                angular: self.angular_velocity,
+            },
+            Inventory::new_with_content(
+                constants::SHIP_INVENTORY_SIZE,
+                self.inventory.items.clone(),
+            ),
+        ));
+    }
+
+    pub fn build_tasks(&self, args: &mut TaskArgs) {
+        let maps = &args.all_entity_id_maps;
+        let entity = maps.ships.id_to_entity()[&self.id];
+        let mut entity_commands = args.commands.entity(entity.into());
+
+        if let Some(docked_at) = &self.docked_at {
+            entity_commands.insert(IsDocked::new(maps.get_entity_unchecked(docked_at)));
+            if let Ok(mut visibility) = args.visibilities.get_mut(entity.into()) {
+                *visibility = Visibility::Hidden;
+            }
+        }
+
+        let mut task_queue = args.task_queues.get_mut(entity.into()).unwrap();
+        task_queue.extend(self.task_queue.iter().map(|x| x.parse(maps)));
+
+        let Some(active_task) = self.task_queue.first() else {
+            return;
+        };
+
+        active_task.build_active_component(
+            &task_queue[0],
+            &mut entity_commands,
+            args.simulation_time.now(),
+        );
+
+        if let TaskSaveData::UseGate { .. } = active_task {
+            // Ships inside a gate aren't part of any sector until they leave it on the other side
+            let sector_entity = maps.sectors.id_to_entity()[&self.position.sector];
+            let mut sector = args.sectors.get_mut(sector_entity.into()).unwrap();
+            sector.remove_ship(&mut args.commands, entity);
+        }
+
+        // Anything which has been reserved by this ship needs to be reserved again
+        for (index, task) in task_queue.iter().enumerate() {
+            match task {
+                TaskInsideQueue::ExchangeWares {
+                    target,
+                    data,
+                    deadline,
+                } => {
+                    args.delivery_deadlines.add(*deadline, entity);
+                    reserve_exchanged_wares(
+                        &mut args.inventories,
+                        &args.owners,
+                        &mut args.session_data,
+                        entity,
+                        *target,
+                        data,
+                    );
+                }
+                TaskInsideQueue::MineAsteroid { target, reserved } => {
+                    let reserved = match &self.task_queue[index] {
+                        TaskSaveData::MineAsteroid {
+                            progress: Some(progress),
+                            ..
+                        } => progress.reserved_ore_amount,
+                        _ => *reserved,
+                    };
+
+                    if let Ok(mut asteroid) = args.asteroids.get_mut((*target).into()) {
+                        asteroid.try_to_reserve(reserved);
+                    }
+                }
+                _ => {}
+            }
+        }
     }
 }
 
-impl From<ShipBehaviorSaveData> for BehaviorBuilder {
-    fn from(value: ShipBehaviorSaveData) -> Self {
-        match value {
-            ShipBehaviorSaveData::AutoTrade { next_idle_update } => {
-                BehaviorBuilder::AutoTrade { next_idle_update }
+/// Reserves both the wares and the credits to pay for them.
+#[allow(clippy::type_complexity)]
+fn reserve_exchanged_wares(
+    inventories: &mut Query<(
+        &mut Inventory,
+        Option<&mut Wallet>,
+        Option<&mut BuyOrders>,
+        Option<&mut SellOrders>,
+    )>,
+    owners: &Query<&Owner>,
+    session_data: &mut SessionData,
+    ship: ShipEntity,
+    target: TypedEntity,
+    data: &ExchangeWareData,
+) {
+    let Ok([ship_components, target_components]) =
+        inventories.get_many_mut([ship.into(), target.into()])
+    else {
+        return;
+    };
+    let (mut ship_inventory, ship_wallet, ..) = ship_components;
+    let (mut target_inventory, target_wallet, buy_orders, sell_orders) = target_components;
+
+    let ship_wallet = match owners.get(ship.into()) {
+        Ok(owner) => session_data
+            .factions
+            .get_mut(&owner.faction)
+            .map(|faction| &mut faction.wallet),
+        Err(_) => ship_wallet.map(Mut::into_inner),
+    };
+    let mut target_wallet = target_wallet.map(Mut::into_inner);
+    match *data {
+        ExchangeWareData::Buy(item_id, amount, price) => {
+            ship_inventory.create_order(item_id, TradeIntent::Buy, amount);
+            target_inventory.create_order(item_id, TradeIntent::Sell, amount);
+            if let Some(wallet) = ship_wallet {
+                wallet.reserve(price as i64 * amount as i64);
+            }
+        }
+        ExchangeWareData::Sell(item_id, amount, price) => {
+            ship_inventory.create_order(item_id, TradeIntent::Sell, amount);
+            target_inventory.create_order(item_id, TradeIntent::Buy, amount);
+            if let Some(wallet) = target_wallet.as_deref_mut() {
+                wallet.reserve(price as i64 * amount as i64);
             }
-            ShipBehaviorSaveData::AutoMine {
-                next_idle_update,
-                state,
-            } => BehaviorBuilder::AutoMine {
-                next_idle_update,
-                state,
-            },
-            ShipBehaviorSaveData::AutoHarvest {
-                next_idle_update,
-                state,
-            } => BehaviorBuilder::AutoHarvest {
-                next_idle_update,
-                state,
-            },
         }
     }
+    utils::update_orders(
+        &target_inventory,
+        target_wallet.as_deref(),
+        buy_orders,
+        sell_orders,
+    );
 }
@@ -1,24 +1,28 @@
-use crate::components::Sector;
+use crate::components::{InteractionQueue, Owner, Sector, Wallet};
 use crate::game_data::{
     GameData, ItemDefinition, ItemId, ProductionModuleId, RecipeId, ShipyardModuleId,
 };
-use crate::persistence::data::v1::*;
+use crate::persistence::data::latest::*;
+use crate::persistence::loading_plugin::LoadingProgress;
 use crate::persistence::local_hex_position::LocalHexPosition;
-use crate::persistence::{PersistentStationId, SectorIdMap, StationIdMap};
+use crate::persistence::{PersistentStationId, SectorIdMap, ShipIdMap, StationIdMap};
+use crate::session_data::FactionId;
 use crate::simulation::production::{
     OngoingShipConstructionOrder, ProductionComponent, ProductionModule, ShipyardComponent,
     ShipyardModule,
 };
+use crate::states::LoadingState;
 use crate::utils::{spawn_helpers, PriceRange, PriceSetting};
 use crate::{constants, SpriteHandles};
 use bevy::ecs::system::SystemParam;
-use bevy::prelude::{Commands, Query, Res};
+use bevy::log::warn;
+use bevy::prelude::{Commands, NextState, Query, Res, ResMut};
 use bevy::utils::hashbrown::HashMap;
 
 #[derive(SystemParam)]
 pub struct Args<'w, 's> {
     commands: Commands<'w, 's>,
-    sprites: Res<'w, SpriteHandles>,
+    sprites: Option<Res<'w, SpriteHandles>>,
     sectors: Query<'w, 's, &'static mut Sector>,
     sector_id_map: Res<'w, SectorIdMap>,
     game_data: Res<'w, GameData>,
@@ -26,14 +30,40 @@ pub struct Args<'w, 's> {
 
 type SaveData = SaveDataCollection<StationSaveData>;
 
-pub fn spawn_all(data: Res<SaveData>, mut args: Args) {
-    let mut station_id_map = StationIdMap::new();
-    for builder in &data.data {
+pub fn spawn_batch(
+    data: Res<SaveData>,
+    mut progress: ResMut<LoadingProgress>,
+    mut next_state: ResMut<NextState<LoadingState>>,
+    mut station_id_map: ResMut<StationIdMap>,
+    mut args: Args,
+) {
+    let batch = progress
+        .stations
+        .next_batch(&data.data, constants::ENTITIES_LOADED_PER_FRAME);
+    for builder in batch {
         builder.build(&mut args, &mut station_id_map);
     }
 
-    args.commands.remove_resource::<SaveData>();
-    args.commands.insert_resource(station_id_map);
+    if progress.stations.is_done() {
+        next_state.set(LoadingState::Ships);
+    }
+}
+
+/// Interaction queues contain ships, so they can only be restored once those have been spawned.
+pub fn restore_interaction_queues(
+    mut commands: Commands,
+    data: Res<SaveData>,
+    station_id_map: Res<StationIdMap>,
+    ship_id_map: Res<ShipIdMap>,
+    mut interaction_queues: Query<&mut InteractionQueue>,
+) {
+    for station in &data.data {
+        let entity = station_id_map.id_to_entity()[&station.id];
+        let mut interaction_queue = interaction_queues.get_mut(entity.into()).unwrap();
+        *interaction_queue = station.interaction_queue.parse(&ship_id_map);
+    }
+
+    commands.remove_resource::<SaveData>();
 }
 
 impl SaveData {
@@ -54,9 +84,17 @@ impl StationSaveData {
             production_modules: None,
             shipyard_modules: None,
             inventory: InventorySaveData { items: Vec::new() },
+            interaction_queue: InteractionQueueSaveData::default(),
+            owner: None,
+            credits: constants::STATION_STARTING_CREDITS,
         }
     }
 
+    pub fn with_owner(&mut self, faction: FactionId) -> &mut Self {
+        self.owner = Some(faction);
+        self
+    }
+
     pub fn with_buys(&mut self, buys: Vec<ItemId>) -> &mut Self {
         if self.buy_orders.is_none() {
             self.buy_orders = Some(SerializedBuyOrder { orders: Vec::new() })
@@ -156,14 +194,17 @@ impl StationSaveData {
             .clone()
             .map_or_else(Vec::new, |x| x.parse(&args.game_data));
 
-        let production = self.production_modules.clone().map(|x| x.parse());
+        let production = self
+            .production_modules
+            .clone()
+            .map(|x| x.parse(&args.game_data));
         let shipyard = self.shipyard_modules.clone().map(|x| x.parse());
 
         spawn_helpers::spawn_station(
             &mut args.commands,
             &mut args.sectors,
             station_id_map,
-            &args.sprites,
+            args.sprites.as_deref(),
             self.id,
             &self.name,
             self.position.position,
@@ -172,6 +213,8 @@ impl StationSaveData {
             sells,
             production,
             shipyard,
+            self.owner.map(|faction| Owner { faction }),
+            Wallet::new(self.credits),
         )
     }
 }
@@ -195,21 +238,36 @@ impl SerializedSellOrder {
 }
 
 impl ProductionSaveData {
-    pub fn parse(&self) -> ProductionComponent {
+    pub fn parse(&self, game_data: &GameData) -> ProductionComponent {
         ProductionComponent {
-            modules: HashMap::from_iter(self.modules.iter().map(|x| x.parse())),
+            modules: HashMap::from_iter(self.modules.iter().map(|x| x.parse(game_data))),
         }
     }
 }
 
 impl ProductionModuleSaveData {
-    pub fn parse(&self) -> (ProductionModuleId, ProductionModule) {
+    pub fn parse(&self, game_data: &GameData) -> (ProductionModuleId, ProductionModule) {
+        // The yield of a running production isn't persisted, so it's assumed to match the current recipe.
+        let recipe = game_data.item_recipes.get(&self.recipe);
+        let current_run_yield = match (self.finished_at, recipe) {
+            (Some(_), Some(recipe)) => recipe.output.clone(),
+            (Some(_), None) => {
+                warn!(
+                    "Unable to find recipe {} for production module {}, its current run won't yield anything.",
+                    self.recipe, self.module_id
+                );
+                Vec::new()
+            }
+            (None, _) => Vec::new(),
+        };
+
         (
             self.module_id,
             ProductionModule {
                 amount: self.amount,
                 recipe: self.recipe,
                 current_run_finished_at: self.finished_at,
+                current_run_yield,
             },
         )
     }
@@ -244,3 +302,16 @@ impl ActiveShipyardOrderSaveData {
         }
     }
 }
+
+impl InteractionQueueSaveData {
+    pub fn parse(&self, ship_id_map: &ShipIdMap) -> InteractionQueue {
+        InteractionQueue::restore(
+            constants::SIMULTANEOUS_STATION_INTERACTIONS,
+            self.currently_interacting,
+            self.waiting
+                .iter()
+                .map(|x| ship_id_map.id_to_entity()[x])
+                .collect(),
+        )
+    }
+}
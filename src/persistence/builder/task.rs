@@ -0,0 +1,156 @@
+use crate::persistence::data::latest::*;
+use crate::persistence::AllEntityIdMaps;
+use crate::simulation::prelude::CurrentSimulationTimestamp;
+use crate::simulation::ship_ai::{
+    ExchangeWares, GateTraversalState, HarvestGas, MineAsteroid, TaskInsideQueue, Undock, UseGate,
+};
+use crate::utils::ExchangeWareData;
+use bevy::ecs::system::EntityCommands;
+
+impl TaskSaveData {
+    pub fn parse(&self, all_entity_id_maps: &AllEntityIdMaps) -> TaskInsideQueue {
+        match self {
+            TaskSaveData::AwaitingSignal => TaskInsideQueue::AwaitingSignal,
+            TaskSaveData::RequestAccess { target } => TaskInsideQueue::RequestAccess {
+                target: all_entity_id_maps.get_entity_unchecked(target),
+            },
+            TaskSaveData::DockAtEntity { target } => TaskInsideQueue::DockAtEntity {
+                target: all_entity_id_maps.get_entity_unchecked(target),
+            },
+            TaskSaveData::Undock { .. } => TaskInsideQueue::Undock,
+            TaskSaveData::ExchangeWares {
+                target,
+                data,
+                deadline,
+                ..
+            } => TaskInsideQueue::ExchangeWares {
+                target: all_entity_id_maps.get_entity_unchecked(target),
+                data: data.into(),
+                deadline: *deadline,
+            },
+            TaskSaveData::MoveToEntity {
+                target,
+                stop_at_target,
+                distance_to_target,
+            } => TaskInsideQueue::MoveToEntity {
+                target: all_entity_id_maps.get_entity_unchecked(target),
+                stop_at_target: *stop_at_target,
+                distance_to_target: *distance_to_target,
+            },
+            TaskSaveData::UseGate {
+                enter_gate,
+                exit_sector,
+                ..
+            } => TaskInsideQueue::UseGate {
+                enter_gate: all_entity_id_maps.gates.id_to_entity()[enter_gate],
+                exit_sector: all_entity_id_maps.sectors.id_to_entity()[exit_sector],
+            },
+            TaskSaveData::MineAsteroid {
+                target, reserved, ..
+            } => TaskInsideQueue::MineAsteroid {
+                target: all_entity_id_maps.asteroids.id_to_entity()[target],
+                reserved: *reserved,
+            },
+            TaskSaveData::HarvestGas { target, .. } => TaskInsideQueue::HarvestGas {
+                target: all_entity_id_maps.planets.id_to_entity()[target],
+            },
+        }
+    }
+
+    /// Inserts the component for this task, including its stored progress.
+    /// Should only be called for the first task inside a ship's queue.
+    pub fn build_active_component(
+        &self,
+        task: &TaskInsideQueue,
+        entity_commands: &mut EntityCommands,
+        now: CurrentSimulationTimestamp,
+    ) {
+        match (self, task) {
+            (
+                TaskSaveData::ExchangeWares { finishes_at, .. },
+                TaskInsideQueue::ExchangeWares {
+                    target,
+                    data,
+                    deadline,
+                },
+            ) => {
+                entity_commands.insert(ExchangeWares {
+                    finishes_at: *finishes_at,
+                    target: *target,
+                    data: *data,
+                    deadline: *deadline,
+                });
+            }
+            (
+                TaskSaveData::UseGate {
+                    progress,
+                    traversal_state,
+                    ..
+                },
+                TaskInsideQueue::UseGate {
+                    enter_gate,
+                    exit_sector,
+                },
+            ) => {
+                entity_commands.insert(UseGate {
+                    progress: *progress,
+                    traversal_state: traversal_state.parse(),
+                    enter_gate: *enter_gate,
+                    exit_sector: *exit_sector,
+                });
+            }
+            (
+                TaskSaveData::MineAsteroid {
+                    progress: Some(progress),
+                    ..
+                },
+                TaskInsideQueue::MineAsteroid { target, .. },
+            ) => {
+                entity_commands.insert(MineAsteroid {
+                    target: *target,
+                    next_update: progress.next_update,
+                    reserved_ore_amount: progress.reserved_ore_amount,
+                });
+            }
+            (
+                TaskSaveData::HarvestGas {
+                    next_update: Some(next_update),
+                    ..
+                },
+                TaskInsideQueue::HarvestGas { target },
+            ) => {
+                entity_commands.insert(HarvestGas {
+                    target: *target,
+                    next_update: *next_update,
+                });
+            }
+            (TaskSaveData::Undock { start_position }, TaskInsideQueue::Undock) => {
+                entity_commands.insert(Undock {
+                    start_position: *start_position,
+                });
+            }
+            _ => task.create_and_insert_component(entity_commands, now),
+        }
+    }
+}
+
+impl GateTraversalStateSaveData {
+    pub fn parse(&self) -> GateTraversalState {
+        match self {
+            GateTraversalStateSaveData::JustCreated => GateTraversalState::JustCreated,
+            GateTraversalStateSaveData::BlendingIntoMotion { origin } => {
+                GateTraversalState::BlendingIntoMotion { origin: *origin }
+            }
+            GateTraversalStateSaveData::TraversingLine => GateTraversalState::TraversingLine,
+        }
+    }
+}
+
+impl From<&ExchangeWareSaveData> for ExchangeWareData {
+    fn from(value: &ExchangeWareSaveData) -> Self {
+        match value {
+            ExchangeWareSaveData::Buy(item, amount, price) => Self::Buy(*item, *amount, *price),
+            ExchangeWareSaveData::Sell(item, amount, price) => Self::Sell(*item, *amount, *price),
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::constants;
+use crate::persistence::data::v1::SaveDataCollection;
+use crate::persistence::data::{v1, v2};
+use crate::persistence::TypedPersistentEntityId;
+use crate::session_data::SessionData;
+use crate::simulation::prelude::SimulationTimestamp;
+use bevy::prelude::Component;
+
+/// Converts save data from one version into the next one.
+///
+/// Migrations are chained by [`super::VersionedUniverseSaveData::into_latest`], so every version
+/// only needs to know how to upgrade into its direct successor.
+pub trait MigrateToNextVersion {
+    type NextVersion;
+
+    fn migrate(self) -> Self::NextVersion;
+}
+
+impl MigrateToNextVersion for v1::UniverseSaveData {
+    type NextVersion = v2::UniverseSaveData;
+
+    fn migrate(self) -> Self::NextVersion {
+        let persistent_id_counters = v2::PersistentIdCountersSaveData {
+            asteroid: next_counter_value(self.sectors.data.iter().flat_map(|x| {
+                x.features.asteroids.iter().flat_map(|asteroids| {
+                    asteroids
+                        .live_asteroids
+                        .iter()
+                        .map(|x| x.id)
+                        .chain(asteroids.respawning_asteroids.iter().map(|x| x.id))
+                })
+            })),
+            gate: next_counter_value(
+                self.gate_pairs
+                    .data
+                    .iter()
+                    .flat_map(|x| [x.from_id, x.to_id]),
+            ),
+            planet: next_counter_value(
+                self.sectors
+                    .data
+                    .iter()
+                    .flat_map(|x| x.features.planets.iter().flatten().map(|x| x.id)),
+            ),
+            ship: next_counter_value(self.ships.data.iter().map(|x| x.id)),
+            station: next_counter_value(self.stations.data.iter().map(|x| x.id)),
+        };
+
+        v2::UniverseSaveData {
+            gate_pairs: self.gate_pairs,
+            sectors: self.sectors,
+            ships: migrate_collection(self.ships),
+            stations: migrate_collection(self.stations),
+            // v1 didn't persist any session data, the mock data was always used instead.
+            session: v2::SessionSaveData::from(&SessionData::mock_data()),
+            // The timeline of v1 saves is unknown, so all timestamps are treated as if the
+            // simulation had just started.
+            simulation_time: v2::SimulationTimeSaveData::default(),
+            persistent_id_counters,
+        }
+    }
+}
+
+impl MigrateToNextVersion for v1::ShipSaveData {
+    type NextVersion = v2::ShipSaveData;
+
+    fn migrate(self) -> Self::NextVersion {
+        v2::ShipSaveData {
+            id: self.id,
+            name: self.name,
+            position: self.position,
+            forward_velocity: self.forward_velocity,
+            rotation_degrees: self.rotation_degrees,
+            angular_velocity: self.angular_velocity,
+            behavior: self.behavior.migrate(),
+            task_queue: self
+                .task_queue
+                .into_iter()
+                .map(MigrateToNextVersion::migrate)
+                .collect(),
+            inventory: self.inventory,
+            // v1 didn't persist docking, so ships are always loaded floating in space.
+            docked_at: None,
+            // v1 didn't have any factions, so everything stays independent.
+            owner: None,
+            // v1 didn't have any money, so everyone starts from scratch.
+            credits: constants::SHIP_STARTING_CREDITS,
+        }
+    }
+}
+
+impl MigrateToNextVersion for v1::ShipBehaviorSaveData {
+    type NextVersion = v2::ShipBehaviorSaveData;
+
+    fn migrate(self) -> Self::NextVersion {
+        match self {
+            v1::ShipBehaviorSaveData::AutoTrade { next_idle_update } => {
+                v2::ShipBehaviorSaveData::AutoTrade {
+                    next_idle_update,
+                    max_jump_range: constants::DEFAULT_MAX_TRADE_JUMP_RANGE,
+                }
+            }
+            v1::ShipBehaviorSaveData::AutoMine {
+                next_idle_update,
+                state,
+            } => v2::ShipBehaviorSaveData::AutoMine {
+                next_idle_update,
+                state,
+            },
+            v1::ShipBehaviorSaveData::AutoHarvest {
+                next_idle_update,
+                state,
+            } => v2::ShipBehaviorSaveData::AutoHarvest {
+                next_idle_update,
+                state,
+            },
+        }
+    }
+}
+
+impl MigrateToNextVersion for v1::TaskSaveData {
+    type NextVersion = v2::TaskSaveData;
+
+    fn migrate(self) -> Self::NextVersion {
+        // v1 didn't persist any task progress, so every task starts from scratch.
+        match self {
+            v1::TaskSaveData::ExchangeWares { target, data } => v2::TaskSaveData::ExchangeWares {
+                target,
+                data: data.migrate(),
+                finishes_at: SimulationTimestamp::MAX,
+                // Nobody promised anything back then.
+                deadline: SimulationTimestamp::MAX,
+            },
+            v1::TaskSaveData::MoveToEntity {
+                target,
+                stop_at_target,
+                distance_to_target,
+            } => v2::TaskSaveData::MoveToEntity {
+                target,
+                stop_at_target,
+                distance_to_target,
+            },
+            v1::TaskSaveData::UseGate {
+                enter_gate,
+                exit_sector,
+            } => v2::TaskSaveData::UseGate {
+                enter_gate,
+                exit_sector,
+                progress: 0.0,
+                traversal_state: v2::GateTraversalStateSaveData::JustCreated,
+            },
+            v1::TaskSaveData::MineAsteroid { target, reserved } => v2::TaskSaveData::MineAsteroid {
+                target,
+                reserved,
+                progress: None,
+            },
+            v1::TaskSaveData::HarvestGas { target } => v2::TaskSaveData::HarvestGas {
+                target,
+                next_update: None,
+            },
+        }
+    }
+}
+
+impl MigrateToNextVersion for v1::ExchangeWareSaveData {
+    type NextVersion = v2::ExchangeWareSaveData;
+
+    fn migrate(self) -> Self::NextVersion {
+        // v1 didn't have any money, so trades which were planned back then stay free of charge.
+        match self {
+            v1::ExchangeWareSaveData::Buy(item_id, amount) => {
+                v2::ExchangeWareSaveData::Buy(item_id, amount, 0)
+            }
+            v1::ExchangeWareSaveData::Sell(item_id, amount) => {
+                v2::ExchangeWareSaveData::Sell(item_id, amount, 0)
+            }
+        }
+    }
+}
+
+impl MigrateToNextVersion for v1::StationSaveData {
+    type NextVersion = v2::StationSaveData;
+
+    fn migrate(self) -> Self::NextVersion {
+        v2::StationSaveData {
+            id: self.id,
+            name: self.name,
+            position: self.position,
+            inventory: self.inventory,
+            production_modules: self.production_modules,
+            shipyard_modules: self.shipyard_modules,
+            buy_orders: self.buy_orders,
+            sell_orders: self.sell_orders,
+            interaction_queue: v2::InteractionQueueSaveData::default(),
+            owner: None,
+            credits: constants::STATION_STARTING_CREDITS,
+        }
+    }
+}
+
+fn migrate_collection<T: MigrateToNextVersion>(
+    collection: SaveDataCollection<T>,
+) -> SaveDataCollection<T::NextVersion> {
+    SaveDataCollection {
+        data: collection
+            .data
+            .into_iter()
+            .map(MigrateToNextVersion::migrate)
+            .collect(),
+    }
+}
+
+fn next_counter_value<T: Component>(ids: impl Iterator<Item = TypedPersistentEntityId<T>>) -> u32 {
+    ids.map(|x| x.next_counter_value())
+        .max()
+        .unwrap_or_default()
+}
@@ -1 +1,13 @@
+//! Contains all versions of our persistent data.
+//!
+//! Older versions are frozen and must never change, since they describe what's already stored
+//! inside existing save files. These get upgraded through [MigrateToNextVersion] on load.
+//! The rest of the codebase should only ever deal with the [latest] version.
+
+mod migration;
 pub mod v1;
+pub mod v2;
+mod versioned_universe_save_data;
+
+pub use v2 as latest;
+pub use {migration::MigrateToNextVersion, versioned_universe_save_data::*};
@@ -0,0 +1,53 @@
+//! The latest save data version.
+//!
+//! Types which haven't changed since [`super::v1`] are simply re-exported from there.
+//! Before changing any of them, copy the type into this module (leaving the v1 version untouched)
+//! and extend the [`super::MigrateToNextVersion`] implementation for `v1::UniverseSaveData` accordingly.
+//!
+//! Once a save file containing this version has been shared, this module is frozen as well and a
+//! new version should be introduced instead.
+//!
+//! Changes since v1:
+//! - [UniverseSaveData] now also stores [SessionSaveData], [SimulationTimeSaveData] and
+//!   [PersistentIdCountersSaveData], so loaded games continue within the same timeline.
+//! - [TaskSaveData] covers all tasks, including the progress of the one currently being executed.
+//! - [ShipSaveData] stores where a ship is docked and [StationSaveData] its [InteractionQueueSaveData].
+//! - [ShipBehaviorSaveData::AutoTrade] stores how many gates a ship may jump through for a single trade run.
+//! - [ShipSaveData] and [StationSaveData] store the credits inside their wallet, as well as the faction owning them.
+//! - [SessionSaveData] stores all [FactionSaveData] and the [RelationSaveData] between them.
+//! - [ExchangeWareSaveData] stores the price per item which was agreed upon when the trade was planned.
+//! - [TaskSaveData::ExchangeWares] stores the deadline until which the exchange should be completed.
+
+use serde::{Deserialize, Serialize};
+
+mod persistent_id_counters_save_data;
+mod session_save_data;
+mod ship_save_data;
+mod simulation_time_save_data;
+mod station_save_data;
+mod task_save_data;
+
+// Types defined or re-exported explicitly shadow their v1 counterparts
+pub use crate::persistence::data::v1::*;
+pub use persistent_id_counters_save_data::PersistentIdCountersSaveData;
+pub use session_save_data::{
+    FactionSaveData, RelationSaveData, SessionSaveData, ShipConfigurationSaveData,
+};
+pub use ship_save_data::{ShipBehaviorSaveData, ShipSaveData};
+pub use simulation_time_save_data::SimulationTimeSaveData;
+pub use station_save_data::{InteractionQueueSaveData, StationSaveData};
+pub use task_save_data::{
+    ExchangeWareSaveData, GateTraversalStateSaveData, MineAsteroidProgressSaveData, TaskSaveData,
+};
+
+#[derive(Default, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+pub struct UniverseSaveData {
+    pub gate_pairs: SaveDataCollection<GatePairSaveData>,
+    pub sectors: SaveDataCollection<SectorSaveData>,
+    pub ships: SaveDataCollection<ShipSaveData>,
+    pub stations: SaveDataCollection<StationSaveData>,
+    pub session: SessionSaveData,
+    pub simulation_time: SimulationTimeSaveData,
+    pub persistent_id_counters: PersistentIdCountersSaveData,
+}
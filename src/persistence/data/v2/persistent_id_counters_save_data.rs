@@ -0,0 +1,14 @@
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+
+/// The values from which [`crate::persistence::TypedPersistentEntityId::next`] continues
+/// handing out IDs for each entity type.
+#[derive(Resource, Default, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+pub struct PersistentIdCountersSaveData {
+    pub asteroid: u32,
+    pub gate: u32,
+    pub planet: u32,
+    pub ship: u32,
+    pub station: u32,
+}
@@ -0,0 +1,41 @@
+use crate::game_data::ItemId;
+use crate::session_data::{FactionId, ShipConfigId};
+use crate::simulation::prelude::Milliseconds;
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+pub struct SessionSaveData {
+    pub ship_configurations: Vec<ShipConfigurationSaveData>,
+    pub factions: Vec<FactionSaveData>,
+    pub relations: Vec<RelationSaveData>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+pub struct ShipConfigurationSaveData {
+    pub id: ShipConfigId,
+    pub name: String,
+    pub duration: Milliseconds,
+    pub materials: Vec<(ItemId, u32)>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+pub struct FactionSaveData {
+    pub id: FactionId,
+    pub name: String,
+    pub credits: i64,
+    pub required_favor: i64,
+}
+
+/// What `faction` thinks about `towards`.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+pub struct RelationSaveData {
+    pub faction: FactionId,
+    pub towards: FactionId,
+    pub favor: i64,
+    pub perception: i64,
+}
@@ -0,0 +1,45 @@
+use crate::persistence::data::v1::InventorySaveData;
+use crate::persistence::data::v2::TaskSaveData;
+use crate::persistence::local_hex_position::LocalHexPosition;
+use crate::persistence::{PersistentEntityId, PersistentShipId};
+use crate::session_data::FactionId;
+use crate::simulation::prelude::SimulationTimestamp;
+use crate::simulation::ship_ai::AutoMineState;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+pub struct ShipSaveData {
+    pub id: PersistentShipId,
+    pub name: String,
+    /// Ships which are currently traversing a gate aren't inside any sector,
+    /// in which case this is relative to the sector of the gate they entered.
+    pub position: LocalHexPosition,
+    pub forward_velocity: f32,
+    pub rotation_degrees: f32,
+    pub angular_velocity: f32,
+    pub behavior: ShipBehaviorSaveData,
+    pub task_queue: Vec<TaskSaveData>,
+    pub inventory: InventorySaveData,
+    pub docked_at: Option<PersistentEntityId>,
+    pub owner: Option<FactionId>,
+    /// Only used by ships without an owner, since owned ones trade on their faction's account.
+    pub credits: i64,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum ShipBehaviorSaveData {
+    AutoTrade {
+        next_idle_update: SimulationTimestamp,
+        max_jump_range: u8,
+    },
+    AutoMine {
+        next_idle_update: SimulationTimestamp,
+        state: AutoMineState,
+    },
+    AutoHarvest {
+        next_idle_update: SimulationTimestamp,
+        state: AutoMineState,
+    },
+}
@@ -0,0 +1,10 @@
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+pub struct SimulationTimeSaveData {
+    pub total: Duration,
+    pub tick: u32,
+}
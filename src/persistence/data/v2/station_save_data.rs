@@ -0,0 +1,31 @@
+use crate::persistence::data::v1::{
+    InventorySaveData, ProductionSaveData, SerializedBuyOrder, SerializedSellOrder,
+    ShipyardSaveData,
+};
+use crate::persistence::local_hex_position::LocalHexPosition;
+use crate::persistence::{PersistentShipId, PersistentStationId};
+use crate::session_data::FactionId;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+pub struct StationSaveData {
+    pub id: PersistentStationId,
+    pub name: String,
+    pub position: LocalHexPosition,
+    pub inventory: InventorySaveData,
+    pub production_modules: Option<ProductionSaveData>,
+    pub shipyard_modules: Option<ShipyardSaveData>,
+    pub buy_orders: Option<SerializedBuyOrder>,
+    pub sell_orders: Option<SerializedSellOrder>,
+    pub interaction_queue: InteractionQueueSaveData,
+    pub owner: Option<FactionId>,
+    pub credits: i64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+pub struct InteractionQueueSaveData {
+    pub currently_interacting: u32,
+    pub waiting: Vec<PersistentShipId>,
+}
@@ -0,0 +1,81 @@
+use crate::game_data::ItemId;
+use crate::persistence::{
+    PersistentAsteroidId, PersistentEntityId, PersistentGateId, PersistentPlanetId,
+};
+use crate::simulation::prelude::SimulationTimestamp;
+use bevy::math::Vec2;
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+/// A single task inside a ship's task queue.
+///
+/// Fields describing the progress of a task are only relevant for the first task in a queue,
+/// since that's the one which is currently being executed. All queued tasks store their initial values.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+pub enum TaskSaveData {
+    AwaitingSignal,
+    RequestAccess {
+        target: PersistentEntityId,
+    },
+    DockAtEntity {
+        target: PersistentEntityId,
+    },
+    Undock {
+        /// World position at which undocking started, None if it hasn't started yet.
+        start_position: Option<Vec2>,
+    },
+    ExchangeWares {
+        target: PersistentEntityId,
+        data: ExchangeWareSaveData,
+        finishes_at: SimulationTimestamp,
+        deadline: SimulationTimestamp,
+    },
+    MoveToEntity {
+        target: PersistentEntityId,
+        stop_at_target: bool,
+        distance_to_target: f32,
+    },
+    UseGate {
+        enter_gate: PersistentGateId,
+        exit_sector: Hex,
+        progress: f32,
+        traversal_state: GateTraversalStateSaveData,
+    },
+    MineAsteroid {
+        target: PersistentAsteroidId,
+        reserved: u32,
+        progress: Option<MineAsteroidProgressSaveData>,
+    },
+    HarvestGas {
+        target: PersistentPlanetId,
+        next_update: Option<SimulationTimestamp>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+pub enum GateTraversalStateSaveData {
+    #[default]
+    JustCreated,
+    BlendingIntoMotion {
+        /// World position from which the ship started blending into the transit curve.
+        origin: Vec2,
+    },
+    TraversingLine,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+pub struct MineAsteroidProgressSaveData {
+    pub next_update: SimulationTimestamp,
+    pub reserved_ore_amount: u32,
+}
+
+/// Item, amount and the agreed upon price per item.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+pub enum ExchangeWareSaveData {
+    Buy(ItemId, u32, u32),
+    Sell(ItemId, u32, u32),
+}
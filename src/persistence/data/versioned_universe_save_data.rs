@@ -0,0 +1,123 @@
+use crate::persistence::data::{latest, v1, v2, MigrateToNextVersion};
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Wraps [`latest::UniverseSaveData`] of any version, so the version gets recorded inside save files.
+///
+/// Variants must never be reordered or removed, since some formats identify them by their index.
+#[derive(Deserialize)]
+pub enum VersionedUniverseSaveData {
+    V1(v1::UniverseSaveData),
+    V2(v2::UniverseSaveData),
+}
+
+const LATEST_VARIANT_INDEX: u32 = 1;
+const LATEST_VARIANT_NAME: &str = "V2";
+
+impl VersionedUniverseSaveData {
+    /// Migrates the contained data through all versions until it reaches the latest one.
+    pub fn into_latest(self) -> latest::UniverseSaveData {
+        match self {
+            Self::V1(data) => data.migrate(),
+            Self::V2(data) => data,
+        }
+    }
+
+    /// Parses JSON save data of any version.
+    /// Files written before save data was versioned contain plain [`v1::UniverseSaveData`].
+    pub fn from_json_slice(bytes: &[u8]) -> Result<latest::UniverseSaveData, serde_json::Error> {
+        match serde_json::from_slice::<Self>(bytes) {
+            Ok(data) => Ok(data.into_latest()),
+            Err(e) => serde_json::from_slice::<v1::UniverseSaveData>(bytes)
+                .map(MigrateToNextVersion::migrate)
+                .map_err(|_| e),
+        }
+    }
+}
+
+/// Serializes the latest [`latest::UniverseSaveData`] exactly like the respective
+/// [VersionedUniverseSaveData] variant, without having to take ownership of it.
+pub struct LatestVersionedUniverseSaveData<'a>(pub &'a latest::UniverseSaveData);
+
+impl Serialize for LatestVersionedUniverseSaveData<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_variant(
+            "VersionedUniverseSaveData",
+            LATEST_VARIANT_INDEX,
+            LATEST_VARIANT_NAME,
+            self.0,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::components::Ship;
+    use crate::persistence::data::latest::*;
+    use crate::persistence::data::{LatestVersionedUniverseSaveData, VersionedUniverseSaveData};
+    use crate::persistence::local_hex_position::LocalHexPosition;
+    use bevy::prelude::{Vec2, With};
+    use hexx::Hex;
+
+    const V1_FIXTURE: &[u8] = include_bytes!("fixtures/v1.json");
+    const UNVERSIONED_FIXTURE: &[u8] = include_bytes!("fixtures/unversioned.json");
+
+    fn assert_fixture_content(data: &UniverseSaveData) {
+        assert_eq!(2, data.sectors.data.len());
+        assert_eq!(Hex::new(1, 0), data.sectors.data[1].coordinate);
+        assert_eq!(1, data.gate_pairs.data.len());
+        assert_eq!(1, data.stations.data.len());
+        assert_eq!("Fixture Station", data.stations.data[0].name);
+        assert_eq!(1, data.ships.data.len());
+        assert_eq!("Fixture Ship", data.ships.data[0].name);
+        assert_eq!(
+            LocalHexPosition::new(Hex::new(0, 0), Vec2::new(0.0, 1.0)),
+            data.ships.data[0].position
+        );
+        assert_eq!(vec![(1, 5)], data.ships.data[0].inventory.items);
+    }
+
+    #[test]
+    fn v1_fixture_should_be_migrated_to_latest_version() {
+        let data = VersionedUniverseSaveData::from_json_slice(V1_FIXTURE).unwrap();
+        assert_fixture_content(&data);
+    }
+
+    #[test]
+    fn v1_migration_should_continue_id_counters_after_highest_persisted_ids() {
+        let data = VersionedUniverseSaveData::from_json_slice(V1_FIXTURE).unwrap();
+
+        let counters = &data.persistent_id_counters;
+        assert_eq!(0, counters.asteroid);
+        assert_eq!(2, counters.gate);
+        assert_eq!(0, counters.planet);
+        assert_eq!(1, counters.ship);
+        assert_eq!(1, counters.station);
+    }
+
+    #[test]
+    fn unversioned_fixture_should_be_treated_as_v1() {
+        let data = VersionedUniverseSaveData::from_json_slice(UNVERSIONED_FIXTURE).unwrap();
+        assert_fixture_content(&data);
+    }
+
+    #[test]
+    fn migrated_fixture_can_be_loaded_into_the_world() {
+        let data = VersionedUniverseSaveData::from_json_slice(V1_FIXTURE).unwrap();
+        let mut app = data.build_test_app();
+
+        let world = app.world_mut();
+        let ship_count = world.query_filtered::<(), With<Ship>>().iter(world).count();
+        assert_eq!(1, ship_count);
+    }
+
+    #[test]
+    fn latest_version_should_survive_round_trip() {
+        let data = VersionedUniverseSaveData::from_json_slice(V1_FIXTURE).unwrap();
+
+        let json = serde_json::to_vec(&LatestVersionedUniverseSaveData(&data)).unwrap();
+        let parsed: VersionedUniverseSaveData = serde_json::from_slice(&json).unwrap();
+
+        assert!(matches!(parsed, VersionedUniverseSaveData::V2(_)));
+        assert_eq!(data, parsed.into_latest());
+    }
+}
@@ -47,6 +47,21 @@ impl<'w> AllEntityIdMaps<'w> {
             }
         }
     }
+
+    /// # Panics
+    /// If no entity is found for the given id
+    pub fn get_entity_unchecked(&self, id: &PersistentEntityId) -> TypedEntity {
+        match id {
+            PersistentEntityId::Asteroid(id) => {
+                TypedEntity::Asteroid(self.asteroids.id_to_entity[id])
+            }
+            PersistentEntityId::Gate(id) => TypedEntity::Gate(self.gates.id_to_entity[id]),
+            PersistentEntityId::Planet(id) => TypedEntity::Planet(self.planets.id_to_entity[id]),
+            PersistentEntityId::Ship(id) => TypedEntity::Ship(self.ships.id_to_entity[id]),
+            PersistentEntityId::Station(id) => TypedEntity::Station(self.stations.id_to_entity[id]),
+            PersistentEntityId::Sector(id) => TypedEntity::Sector(self.sectors.id_to_entity[id]),
+        }
+    }
 }
 
 pub type AsteroidIdMap = EntityIdMap<PersistentAsteroidId, AsteroidEntity>;
@@ -1,25 +1,158 @@
-use crate::initialize_data;
-use crate::persistence::builder::{gate, sector, ship, station};
-use bevy::app::{App, Plugin, Startup};
-use bevy::prelude::IntoSystemConfigs;
+use crate::components::{
+    Asteroid, Gate, GateConnectionComponent, Planet, Sector, Ship, Star, Station, StationIcon,
+};
+use crate::persistence::builder::{gate, sector, session, ship, station};
+use crate::persistence::data::latest::*;
+use crate::persistence::{
+    AsteroidIdMap, GateIdMap, PersistentIdCounters, PlanetIdMap, SectorIdMap, ShipIdMap, StarIdMap,
+    StationIdMap,
+};
+use crate::states::{ApplicationState, LoadingState};
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::{
+    in_state, Commands, DespawnRecursiveExt, Entity, IntoSystemConfigs, OnEnter, Or, Query, Res,
+    Resource, With,
+};
 
-pub struct UniverseSaveDataLoadingOnStartupPlugin;
-
-// TODO: Instead of doing this all at once (and chained), separate loading into multiple States and
-//       only load X entities per frame, so the app remains responsive and we can have a fancy
-//       looking loading bar... plus we can then invoke it multiple times per run, yay!
-impl Plugin for UniverseSaveDataLoadingOnStartupPlugin {
+/// Spawns everything stored inside the [SaveDataCollection] resources whenever
+/// [ApplicationState::Loading] is entered, which also happens on startup.
+///
+/// Loading is split into multiple [LoadingState]s which only spawn a limited amount of entities
+/// per frame, so the app remains responsive and we can display a fancy looking loading bar.
+/// Entering [ApplicationState::Loading] at runtime despawns the previously loaded universe.
+pub struct UniverseSaveDataLoadingPlugin;
+impl Plugin for UniverseSaveDataLoadingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Startup,
-            (
-                sector::spawn_all,
-                gate::spawn_all,
-                station::spawn_all,
-                ship::spawn_all,
+        app.init_resource::<LoadingProgress>()
+            .init_resource::<PersistentIdCounters>()
+            .add_systems(
+                OnEnter(ApplicationState::Loading),
+                (despawn_previous_universe, prepare_loading, session::restore).chain(),
+            )
+            .add_systems(
+                OnEnter(LoadingState::Tasks),
+                station::restore_interaction_queues,
             )
-                .after(initialize_data)
-                .chain(),
-        );
+            .add_systems(
+                Update,
+                (
+                    sector::spawn_batch.run_if(in_state(LoadingState::Sectors)),
+                    gate::spawn_batch.run_if(in_state(LoadingState::Gates)),
+                    station::spawn_batch.run_if(in_state(LoadingState::Stations)),
+                    ship::spawn_batch.run_if(in_state(LoadingState::Ships)),
+                    ship::restore_task_batch.run_if(in_state(LoadingState::Tasks)),
+                ),
+            );
+    }
+}
+
+/// Keeps track of how many entities have already been spawned or restored during each [LoadingState].
+#[derive(Resource, Default)]
+pub struct LoadingProgress {
+    pub sectors: LoadingPhaseProgress,
+    pub gates: LoadingPhaseProgress,
+    pub stations: LoadingPhaseProgress,
+    pub ships: LoadingPhaseProgress,
+    pub tasks: LoadingPhaseProgress,
+}
+
+#[derive(Default, Copy, Clone)]
+pub struct LoadingPhaseProgress {
+    pub spawned: usize,
+    pub total: usize,
+}
+
+impl LoadingPhaseProgress {
+    fn new(total: usize) -> Self {
+        Self { spawned: 0, total }
+    }
+
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        self.spawned >= self.total
+    }
+
+    /// Returns the next `batch_size` elements which haven't been spawned yet and marks them as spawned.
+    pub fn next_batch<'a, T>(&mut self, data: &'a [T], batch_size: usize) -> &'a [T] {
+        let start = self.spawned.min(data.len());
+        let end = (start + batch_size).min(data.len());
+        self.spawned = end;
+        &data[start..end]
+    }
+}
+
+impl LoadingProgress {
+    /// Returns the overall loading progress in [0,1].
+    pub fn fraction(&self) -> f32 {
+        let phases = [
+            self.sectors,
+            self.gates,
+            self.stations,
+            self.ships,
+            self.tasks,
+        ];
+        let total: usize = phases.iter().map(|x| x.total).sum();
+        if total == 0 {
+            return 1.0;
+        }
+
+        let spawned: usize = phases.iter().map(|x| x.spawned).sum();
+        spawned as f32 / total as f32
+    }
+}
+
+/// Despawns all entities which belong to a previously loaded universe.
+/// Only entities with simulation marker components are removed, so things like cameras or UI survive.
+#[allow(clippy::type_complexity)]
+fn despawn_previous_universe(
+    mut commands: Commands,
+    entities: Query<
+        Entity,
+        Or<(
+            With<Ship>,
+            With<Station>,
+            With<StationIcon>,
+            With<Gate>,
+            With<GateConnectionComponent>,
+            With<Asteroid>,
+            With<Planet>,
+            With<Star>,
+            With<Sector>,
+        )>,
+    >,
+) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
     }
 }
+
+fn prepare_loading(
+    mut commands: Commands,
+    sectors: Option<Res<SaveDataCollection<SectorSaveData>>>,
+    gate_pairs: Option<Res<SaveDataCollection<GatePairSaveData>>>,
+    stations: Option<Res<SaveDataCollection<StationSaveData>>>,
+    ships: Option<Res<SaveDataCollection<ShipSaveData>>>,
+) {
+    let ship_count = ships.map(|x| x.data.len()).unwrap_or_default();
+    commands.insert_resource(LoadingProgress {
+        sectors: LoadingPhaseProgress::new(sectors.map(|x| x.data.len()).unwrap_or_default()),
+        gates: LoadingPhaseProgress::new(gate_pairs.map(|x| x.data.len()).unwrap_or_default()),
+        stations: LoadingPhaseProgress::new(stations.map(|x| x.data.len()).unwrap_or_default()),
+        ships: LoadingPhaseProgress::new(ship_count),
+        tasks: LoadingPhaseProgress::new(ship_count),
+    });
+
+    // Missing collections are treated like empty ones
+    commands.init_resource::<SaveDataCollection<SectorSaveData>>();
+    commands.init_resource::<SaveDataCollection<GatePairSaveData>>();
+    commands.init_resource::<SaveDataCollection<StationSaveData>>();
+    commands.init_resource::<SaveDataCollection<ShipSaveData>>();
+
+    commands.insert_resource(AsteroidIdMap::new());
+    commands.insert_resource(GateIdMap::new());
+    commands.insert_resource(PlanetIdMap::new());
+    commands.insert_resource(SectorIdMap::new());
+    commands.insert_resource(ShipIdMap::new());
+    commands.insert_resource(StarIdMap::new());
+    commands.insert_resource(StationIdMap::new());
+}
@@ -1,14 +1,26 @@
+pub mod autosave;
 mod builder;
 mod data;
 mod entity_id_map;
 mod loading_plugin;
 pub mod local_hex_position;
 mod persistent_entity_id;
+pub mod replay;
+mod save_file_format;
+pub mod save_files;
 mod saving;
-pub mod test_universe;
+pub mod scenario;
+pub mod universe_generator;
 mod writer;
 
-pub use builder::UniverseSaveDataLoadingOnStartupPlugin;
-pub use data::v1::*;
+pub use autosave::AutosavePlugin;
+pub use builder::UniverseSaveDataLoadingPlugin;
+pub use data::latest::*;
 pub use entity_id_map::*;
+pub use loading_plugin::{LoadingPhaseProgress, LoadingProgress};
 pub use persistent_entity_id::*;
+pub use save_file_format::SaveFileFormat;
+pub use save_files::{
+    LoadGameEvent, RunningSaveTasks, SaveDirectory, SaveFilePlugin, SaveGameEvent, SaveGameStatus,
+    SaveGameStatusEvent,
+};
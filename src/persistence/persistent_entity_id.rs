@@ -1,5 +1,6 @@
 use crate::components::{Asteroid, Gate, Planet, Ship, Station};
-use bevy::prelude::Component;
+use crate::persistence::data::latest::PersistentIdCountersSaveData;
+use bevy::prelude::{Component, Resource};
 use hexx::Hex;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -9,8 +10,8 @@ use std::marker::PhantomData;
 use std::sync::atomic::{AtomicU32, Ordering};
 /// A unique ID that's the same between session and across different clients in multiplayer sessions.
 /// (Sectors are just represented as Hex coordinates)
-#[derive(Serialize, Deserialize)]
-#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(test, derive(Debug))]
 pub enum PersistentEntityId {
     Asteroid(PersistentAsteroidId),
     Gate(PersistentGateId),
@@ -20,6 +21,44 @@ pub enum PersistentEntityId {
     Sector(Hex),
 }
 
+impl PersistentEntityId {
+    /// Orders by entity kind first, then by ID. Sectors are ordered by their coordinates.
+    fn sort_key(&self) -> (u8, i64, i64) {
+        match self {
+            PersistentEntityId::Asteroid(id) => (0, id.0 as i64, 0),
+            PersistentEntityId::Gate(id) => (1, id.0 as i64, 0),
+            PersistentEntityId::Planet(id) => (2, id.0 as i64, 0),
+            PersistentEntityId::Ship(id) => (3, id.0 as i64, 0),
+            PersistentEntityId::Station(id) => (4, id.0 as i64, 0),
+            PersistentEntityId::Sector(hex) => (5, hex.x as i64, hex.y as i64),
+        }
+    }
+}
+
+impl Ord for PersistentEntityId {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+impl PartialOrd<Self> for PersistentEntityId {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for PersistentEntityId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PersistentEntityId::Asteroid(id) => write!(f, "Asteroid {id}"),
+            PersistentEntityId::Gate(id) => write!(f, "Gate {id}"),
+            PersistentEntityId::Planet(id) => write!(f, "Planet {id}"),
+            PersistentEntityId::Ship(id) => write!(f, "Ship {id}"),
+            PersistentEntityId::Station(id) => write!(f, "Station {id}"),
+            PersistentEntityId::Sector(hex) => write!(f, "Sector [{},{}]", hex.x, hex.y),
+        }
+    }
+}
+
 impl From<PersistentAsteroidId> for PersistentEntityId {
     fn from(value: PersistentAsteroidId) -> Self {
         Self::Asteroid(value)
@@ -134,6 +173,80 @@ impl TypedPersistentEntityId<Station> {
     }
 }
 
+impl<T: Component> TypedPersistentEntityId<T> {
+    /// Returns the smallest counter value from which [Self::next] can continue without handing out this ID again.
+    pub(crate) fn next_counter_value(&self) -> u32 {
+        self.0 + 1
+    }
+}
+
+impl PersistentIdCountersSaveData {
+    /// Captures the values from which IDs are currently being handed out.
+    pub fn from_current_counters() -> Self {
+        Self {
+            asteroid: NEXT_ASTEROID_ID.load(Ordering::Relaxed),
+            gate: NEXT_GATE_ID.load(Ordering::Relaxed),
+            planet: NEXT_PLANET_ID.load(Ordering::Relaxed),
+            ship: NEXT_SHIP_ID.load(Ordering::Relaxed),
+            station: NEXT_STATION_ID.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Hands out IDs for entities which are spawned while the simulation is running.
+///
+/// Unlike the global counters used by [TypedPersistentEntityId::next] while save data is being built,
+/// this is replaced whenever a universe is loaded. New IDs therefore only depend on the loaded universe
+/// and on what happened since, and not on whatever else has been loaded within the same process.
+#[derive(Resource, Default)]
+pub struct PersistentIdCounters {
+    asteroid: u32,
+    gate: u32,
+    planet: u32,
+    ship: u32,
+    station: u32,
+}
+
+impl PersistentIdCounters {
+    pub fn next_ship(&mut self) -> PersistentShipId {
+        let id = TypedPersistentEntityId(self.ship, PhantomData);
+        self.ship += 1;
+        id
+    }
+}
+
+impl PersistentIdCountersSaveData {
+    /// Continues exactly where the saved counters left off.
+    /// Universes which have been built without any counters continue after the highest ship ID in `ship_ids`,
+    /// since ships are the only thing which gets spawned while the simulation is running.
+    pub fn parse(&self, ship_ids: impl Iterator<Item = PersistentShipId>) -> PersistentIdCounters {
+        let ship = ship_ids
+            .map(|x| x.next_counter_value())
+            .max()
+            .unwrap_or_default();
+
+        PersistentIdCounters {
+            asteroid: self.asteroid,
+            gate: self.gate,
+            planet: self.planet,
+            ship: self.ship.max(ship),
+            station: self.station,
+        }
+    }
+}
+
+impl From<&PersistentIdCounters> for PersistentIdCountersSaveData {
+    fn from(value: &PersistentIdCounters) -> Self {
+        Self {
+            asteroid: value.asteroid,
+            gate: value.gate,
+            planet: value.planet,
+            ship: value.ship,
+            station: value.station,
+        }
+    }
+}
+
 impl<T: Component> Copy for TypedPersistentEntityId<T> {}
 impl<T: Component> Clone for TypedPersistentEntityId<T> {
     fn clone(&self) -> Self {
@@ -0,0 +1,486 @@
+//! Records everything which influences a deterministic run from the outside, so it can be reproduced later on.
+//!
+//! A replay consists of a snapshot of the initial universe, stored as a regular save file, and a log file
+//! with one RON encoded [ReplayHeader] followed by one [RecordedInput] per line. The log is flushed after
+//! every line, so replays of runs which crashed remain usable.
+//!
+//! The header also contains the [GameData] the run was started with, since the files it was loaded from
+//! may have changed by the time the replay is played back. Game data hot reloading is not recorded,
+//! so it needs to be disabled while recording.
+//!
+//! Anything which isn't part of the simulation itself but changes what happens within it (such as player orders)
+//! needs to be added to [ReplayInput] and recorded through [ReplayRecordingPlugin], or replays will silently
+//! diverge from the recorded run. Right now, the only such inputs are the simulation speed controls.
+//!
+//! During playback, inputs are applied right before the tick following the one they were recorded at,
+//! no matter how many ticks are simulated within a single frame.
+
+use crate::game_data::{
+    GameData, GameDataError, ItemDefinition, ItemRecipe, ProductionModuleDefinition,
+    ShipyardModuleDefinition,
+};
+use crate::persistence::data::latest::UniverseSaveData;
+use crate::persistence::save_file_format::SaveFileFormat;
+use crate::persistence::save_files::{LoadGameEvent, SaveFileError};
+use crate::simulation::prelude::{advance_simulation_time, SimulationTime};
+use crate::simulation::speed::{pause_immediately, SimulationSpeedEvent};
+use crate::states::{ApplicationState, SimulationState};
+use bevy::app::{App, AppExit, FixedFirst, FixedLast, Plugin, PostUpdate, Update};
+use bevy::log::{error, info, warn};
+use bevy::prelude::{
+    in_state, on_event, resource_exists, Commands, Event, EventReader, IntoSystemConfigs, Res,
+    ResMut, Resource, State, Time, Virtual, World,
+};
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{LineWriter, Write};
+use std::path::{Path, PathBuf};
+
+const SNAPSHOT_EXTENSION: &str = "snapshot.sav";
+
+/// The first line of every replay log.
+#[derive(Serialize, Deserialize)]
+pub struct ReplayHeader {
+    pub seed: u64,
+    pub ticks_per_second: f64,
+    /// File name of the initial snapshot, relative to the replay log.
+    pub snapshot: PathBuf,
+    pub game_data: ReplayGameData,
+}
+
+/// The definitions inside [GameData], sorted by ID.
+#[derive(Serialize, Deserialize)]
+pub struct ReplayGameData {
+    pub items: Vec<ItemDefinition>,
+    pub recipes: Vec<ItemRecipe>,
+    pub production_modules: Vec<ProductionModuleDefinition>,
+    pub shipyard_modules: Vec<ShipyardModuleDefinition>,
+}
+
+impl From<&GameData> for ReplayGameData {
+    fn from(game_data: &GameData) -> Self {
+        Self {
+            items: sorted_by_id(&game_data.items, |x| x.id),
+            recipes: sorted_by_id(&game_data.item_recipes, |x| x.id),
+            production_modules: sorted_by_id(&game_data.production_modules, |x| x.id),
+            shipyard_modules: sorted_by_id(&game_data.shipyard_modules, |x| x.id),
+        }
+    }
+}
+
+fn sorted_by_id<T: Clone>(definitions: &HashMap<u32, T>, get_id: impl Fn(&T) -> u32) -> Vec<T> {
+    let mut result: Vec<T> = definitions.values().cloned().collect();
+    result.sort_by_key(get_id);
+    result
+}
+
+impl ReplayGameData {
+    pub fn into_game_data(self) -> Result<GameData, GameDataError> {
+        GameData::from_definitions(
+            self.items,
+            self.recipes,
+            self.production_modules,
+            self.shipyard_modules,
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum ReplayInput {
+    SimulationSpeed(SimulationSpeedEvent),
+}
+
+impl From<SimulationSpeedEvent> for ReplayInput {
+    fn from(value: SimulationSpeedEvent) -> Self {
+        Self::SimulationSpeed(value)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct RecordedInput {
+    /// The amount of ticks which had been simulated when this input was received.
+    pub tick: u32,
+    pub input: ReplayInput,
+}
+
+/// A fully loaded replay, ready to be played back.
+pub struct Replay {
+    pub seed: u64,
+    pub ticks_per_second: f64,
+    pub game_data: GameData,
+    pub initial_state: UniverseSaveData,
+    pub inputs: Vec<RecordedInput>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(PathBuf, std::io::Error),
+    Parse {
+        path: PathBuf,
+        line: usize,
+        error: ron::error::SpannedError,
+    },
+    MissingHeader(PathBuf),
+    Snapshot(PathBuf, SaveFileError),
+    GameData(PathBuf, GameDataError),
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Io(path, e) => write!(f, "Unable to access {}: {e}", path.display()),
+            ReplayError::Parse { path, line, error } => {
+                write!(f, "Line {line} of {} is malformed: {error}", path.display())
+            }
+            ReplayError::MissingHeader(path) => {
+                write!(f, "{} doesn't start with a replay header", path.display())
+            }
+            ReplayError::Snapshot(path, e) => {
+                write!(f, "Unable to access snapshot {}: {e}", path.display())
+            }
+            ReplayError::GameData(path, e) => {
+                write!(f, "Game data inside {} is invalid: {e}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl Replay {
+    pub fn read_from_file(path: &Path) -> Result<Self, ReplayError> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| ReplayError::Io(path.to_path_buf(), e))?;
+
+        let mut lines = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        let header: ReplayHeader = match lines.next() {
+            Some((index, line)) => parse_line(path, index, line)?,
+            None => return Err(ReplayError::MissingHeader(path.to_path_buf())),
+        };
+        let inputs = lines
+            .map(|(index, line)| parse_line(path, index, line))
+            .collect::<Result<Vec<RecordedInput>, _>>()?;
+
+        let snapshot_path = path.with_file_name(&header.snapshot);
+        let initial_state = UniverseSaveData::read_from_file(&snapshot_path)
+            .map_err(|e| ReplayError::Snapshot(snapshot_path, e))?;
+
+        let game_data = header
+            .game_data
+            .into_game_data()
+            .map_err(|e| ReplayError::GameData(path.to_path_buf(), e))?;
+
+        Ok(Self {
+            seed: header.seed,
+            ticks_per_second: header.ticks_per_second,
+            game_data,
+            initial_state,
+            inputs,
+        })
+    }
+}
+
+fn parse_line<T: for<'a> Deserialize<'a>>(
+    path: &Path,
+    index: usize,
+    line: &str,
+) -> Result<T, ReplayError> {
+    ron::from_str(line).map_err(|error| ReplayError::Parse {
+        path: path.to_path_buf(),
+        line: index + 1,
+        error,
+    })
+}
+
+/// Appends every [RecordedInput] to a replay log. Recording stops once this resource is removed.
+#[derive(Resource)]
+pub struct ReplayRecorder {
+    path: PathBuf,
+    writer: LineWriter<File>,
+}
+
+impl ReplayRecorder {
+    /// Writes `initial_state` into a snapshot next to `path` and starts a new replay log at `path`.
+    pub fn create(
+        path: &Path,
+        seed: u64,
+        ticks_per_second: f64,
+        game_data: &GameData,
+        initial_state: &UniverseSaveData,
+    ) -> Result<Self, ReplayError> {
+        let snapshot_path = path.with_extension(SNAPSHOT_EXTENSION);
+        initial_state
+            .write_to_file(&snapshot_path, SaveFileFormat::default())
+            .map_err(|e| ReplayError::Snapshot(snapshot_path.clone(), e))?;
+
+        let file = File::create(path).map_err(|e| ReplayError::Io(path.to_path_buf(), e))?;
+        let mut recorder = Self {
+            path: path.to_path_buf(),
+            writer: LineWriter::new(file),
+        };
+
+        let header = ReplayHeader {
+            seed,
+            ticks_per_second,
+            snapshot: PathBuf::from(snapshot_path.file_name().unwrap()),
+            game_data: ReplayGameData::from(game_data),
+        };
+        recorder
+            .write_line(&header)
+            .map_err(|e| ReplayError::Io(path.to_path_buf(), e))?;
+        Ok(recorder)
+    }
+
+    pub fn record(&mut self, tick: u32, input: ReplayInput) -> std::io::Result<()> {
+        self.write_line(&RecordedInput { tick, input })
+    }
+
+    fn write_line<T: Serialize>(&mut self, value: &T) -> std::io::Result<()> {
+        let line = ron::to_string(value).expect("Replay data should always be serializable!");
+        writeln!(self.writer, "{line}")
+    }
+}
+
+/// Records all [ReplayInput]s while a [ReplayRecorder] resource exists.
+pub struct ReplayRecordingPlugin;
+impl Plugin for ReplayRecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (
+                record_inputs::<SimulationSpeedEvent>,
+                stop_recording_on_load.run_if(on_event::<LoadGameEvent>()),
+            )
+                .chain()
+                .run_if(resource_exists::<ReplayRecorder>),
+        );
+    }
+}
+
+/// Events are handled within the same frame they were sent in, so they take effect before the next tick.
+fn record_inputs<T: Event + Copy + Into<ReplayInput>>(
+    mut events: EventReader<T>,
+    simulation_time: Res<SimulationTime>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    for event in events.read() {
+        if let Err(e) = recorder.record(simulation_time.tick(), (*event).into()) {
+            error!(
+                "Unable to record input into {}: {e}",
+                recorder.path.display()
+            );
+        }
+    }
+}
+
+fn stop_recording_on_load(mut commands: Commands, recorder: Res<ReplayRecorder>) {
+    warn!(
+        "Stopped recording into {}, since a different universe is being loaded.",
+        recorder.path.display()
+    );
+    commands.remove_resource::<ReplayRecorder>();
+}
+
+/// Feeds the inputs of a [Replay] back into the simulation at the ticks they were recorded at.
+/// The rest of the replay (seed, tick rate and initial state) needs to be applied when building the app.
+pub struct ReplayPlaybackPlugin {
+    pub inputs: Vec<RecordedInput>,
+    /// Pauses the simulation once this tick has been simulated.
+    pub stop_at_tick: Option<u32>,
+    /// Exits the app instead of pausing at `stop_at_tick`, for runs without GUI.
+    pub exit_at_stop: bool,
+}
+
+impl Plugin for ReplayPlaybackPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ReplayPlayback {
+            inputs: self.inputs.iter().copied().collect(),
+            stop_at_tick: self.stop_at_tick,
+            exit_at_stop: self.exit_at_stop,
+        })
+        .add_systems(
+            FixedFirst,
+            apply_recorded_inputs
+                .before(advance_simulation_time)
+                .run_if(in_state(ApplicationState::InGame)),
+        )
+        // Nothing is simulated while paused, so inputs which resume the simulation are applied here
+        .add_systems(
+            Update,
+            apply_recorded_inputs.run_if(in_state(ApplicationState::InGame)),
+        )
+        .add_systems(
+            FixedLast,
+            stop_at_requested_tick.run_if(in_state(SimulationState::Running)),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct ReplayPlayback {
+    inputs: VecDeque<RecordedInput>,
+    stop_at_tick: Option<u32>,
+    exit_at_stop: bool,
+}
+
+/// Applies every input which has been recorded at the current tick, before the next one is simulated.
+fn apply_recorded_inputs(world: &mut World) {
+    // Single ticks pretend the simulation is running, but any inputs for them have already been applied
+    if world.resource::<Time<Virtual>>().is_paused()
+        && world.resource::<State<SimulationState>>().get() == &SimulationState::Running
+    {
+        return;
+    }
+
+    let tick = world.resource::<SimulationTime>().tick();
+    loop {
+        let mut playback = world.resource_mut::<ReplayPlayback>();
+        if !playback.inputs.front().is_some_and(|x| x.tick <= tick) {
+            return;
+        }
+
+        let recorded = playback.inputs.pop_front().unwrap();
+        if recorded.tick < tick {
+            warn!(
+                "Input recorded at tick {} is applied late at tick {tick}, the replay will diverge.",
+                recorded.tick
+            );
+        }
+        apply_recorded_input(world, recorded.input);
+    }
+}
+
+fn apply_recorded_input(world: &mut World, input: ReplayInput) {
+    match input {
+        ReplayInput::SimulationSpeed(SimulationSpeedEvent::TogglePause)
+            if world.resource::<State<SimulationState>>().get() == &SimulationState::Running =>
+        {
+            // The event would only be handled after any remaining ticks within this frame have been simulated
+            pause_immediately(world);
+        }
+        ReplayInput::SimulationSpeed(event) => {
+            world.send_event(event);
+        }
+    }
+}
+
+fn stop_at_requested_tick(world: &mut World) {
+    let playback = world.resource::<ReplayPlayback>();
+    let Some(stop_at_tick) = playback.stop_at_tick else {
+        return;
+    };
+    let exit_at_stop = playback.exit_at_stop;
+
+    if world.resource::<SimulationTime>().tick() < stop_at_tick {
+        return;
+    }
+
+    info!("Replay reached tick {stop_at_tick}.");
+    // Resuming afterward should be possible
+    world.resource_mut::<ReplayPlayback>().stop_at_tick = None;
+    pause_immediately(world);
+    if exit_at_stop {
+        world.send_event(AppExit::Success);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::speed::SimulationSpeedPlugin;
+    use crate::simulation::time::SimulationTimePlugin;
+    use crate::states::StatePlugin;
+    use bevy::input::InputPlugin;
+    use bevy::prelude::{MinimalPlugins, NextState};
+    use bevy::state::app::StatesPlugin;
+    use bevy::time::{Fixed, TimeUpdateStrategy};
+    use std::time::Duration;
+
+    #[test]
+    fn recorded_inputs_can_be_read_back() {
+        let path = std::env::temp_dir().join("bevy_space_recorded_inputs_test.replay");
+        let inputs = [
+            RecordedInput {
+                tick: 5,
+                input: ReplayInput::SimulationSpeed(SimulationSpeedEvent::SetSpeed(5.0)),
+            },
+            RecordedInput {
+                tick: 20,
+                input: ReplayInput::SimulationSpeed(SimulationSpeedEvent::TogglePause),
+            },
+        ];
+
+        let game_data = GameData::mock_data();
+        let mut recorder =
+            ReplayRecorder::create(&path, 42, 10.0, &game_data, &UniverseSaveData::default())
+                .unwrap();
+        for recorded in inputs {
+            recorder.record(recorded.tick, recorded.input).unwrap();
+        }
+        drop(recorder);
+
+        let replay = Replay::read_from_file(&path).unwrap();
+        assert_eq!(replay.seed, 42);
+        assert_eq!(replay.ticks_per_second, 10.0);
+        assert_eq!(replay.initial_state, UniverseSaveData::default());
+        assert_eq!(replay.game_data.items.len(), game_data.items.len());
+        assert_eq!(
+            replay.game_data.item_recipes.len(),
+            game_data.item_recipes.len()
+        );
+        assert_eq!(replay.inputs, inputs);
+    }
+
+    #[test]
+    fn inputs_are_applied_at_their_exact_tick_while_running_many_ticks_per_frame() {
+        const TIMESTEP: Duration = Duration::from_millis(10);
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            StatesPlugin,
+            InputPlugin,
+            StatePlugin,
+            SimulationTimePlugin,
+            SimulationSpeedPlugin,
+            ReplayPlaybackPlugin {
+                inputs: vec![
+                    RecordedInput {
+                        tick: 7,
+                        input: ReplayInput::SimulationSpeed(SimulationSpeedEvent::TogglePause),
+                    },
+                    RecordedInput {
+                        tick: 7,
+                        input: ReplayInput::SimulationSpeed(
+                            SimulationSpeedEvent::AdvanceSingleTick,
+                        ),
+                    },
+                ],
+                stop_at_tick: None,
+                exit_at_stop: false,
+            },
+        ))
+        .insert_resource(Time::<Fixed>::from_duration(TIMESTEP))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(TIMESTEP * 20));
+        app.world_mut()
+            .resource_mut::<NextState<ApplicationState>>()
+            .set(ApplicationState::InGame);
+
+        for _ in 0..5 {
+            app.update();
+        }
+
+        assert_eq!(app.world().resource::<SimulationTime>().tick(), 8);
+        assert_eq!(
+            app.world().resource::<State<SimulationState>>().get(),
+            &SimulationState::Paused
+        );
+    }
+}
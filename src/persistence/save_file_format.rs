@@ -0,0 +1,91 @@
+use crate::persistence::data::latest::UniverseSaveData;
+use crate::persistence::data::{LatestVersionedUniverseSaveData, VersionedUniverseSaveData};
+use crate::persistence::save_files::SaveFileError;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::io::Write;
+
+/// Every save file starts with these bytes, followed by a single byte identifying its [SaveFileFormat].
+/// Files without this header are treated as JSON, since that's what we wrote before the header existed.
+const HEADER_MAGIC: &[u8; 4] = b"RSSV";
+const HEADER_LENGTH: usize = HEADER_MAGIC.len() + 1;
+
+/// The encodings which can be used to store [UniverseSaveData] on disk.
+#[derive(Copy, Clone, Default, Debug, Eq, PartialEq)]
+pub enum SaveFileFormat {
+    /// Human-readable, but huge and slow. Useful for debugging.
+    Json,
+    /// Compact binary encoding using bincode.
+    Binary,
+    /// [SaveFileFormat::Binary], compressed with deflate.
+    #[default]
+    CompressedBinary,
+}
+
+impl SaveFileFormat {
+    pub const ALL: [SaveFileFormat; 3] = [
+        SaveFileFormat::Json,
+        SaveFileFormat::Binary,
+        SaveFileFormat::CompressedBinary,
+    ];
+
+    fn id(&self) -> u8 {
+        match self {
+            SaveFileFormat::Json => 0,
+            SaveFileFormat::Binary => 1,
+            SaveFileFormat::CompressedBinary => 2,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self, SaveFileError> {
+        match id {
+            0 => Ok(SaveFileFormat::Json),
+            1 => Ok(SaveFileFormat::Binary),
+            2 => Ok(SaveFileFormat::CompressedBinary),
+            _ => Err(SaveFileError::UnknownFormat(id)),
+        }
+    }
+
+    /// Writes the file header followed by `data` encoded in this format into `writer`.
+    pub fn encode<W: Write>(
+        &self,
+        data: &UniverseSaveData,
+        mut writer: W,
+    ) -> Result<(), SaveFileError> {
+        writer.write_all(HEADER_MAGIC)?;
+        writer.write_all(&[self.id()])?;
+
+        let data = LatestVersionedUniverseSaveData(data);
+        match self {
+            SaveFileFormat::Json => serde_json::to_writer(writer, &data)?,
+            SaveFileFormat::Binary => bincode::serialize_into(writer, &data)?,
+            SaveFileFormat::CompressedBinary => {
+                let mut encoder = DeflateEncoder::new(writer, Compression::fast());
+                bincode::serialize_into(&mut encoder, &data)?;
+                encoder.finish()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Detects the format of `bytes` through its header, decodes them and migrates the result
+    /// to the latest version.
+    pub fn decode(bytes: &[u8]) -> Result<UniverseSaveData, SaveFileError> {
+        if !bytes.starts_with(HEADER_MAGIC) || bytes.len() < HEADER_LENGTH {
+            return Ok(VersionedUniverseSaveData::from_json_slice(bytes)?);
+        }
+
+        let content = &bytes[HEADER_LENGTH..];
+        let data: VersionedUniverseSaveData = match Self::from_id(bytes[HEADER_MAGIC.len()])? {
+            SaveFileFormat::Json => serde_json::from_slice(content)?,
+            SaveFileFormat::Binary => bincode::deserialize(content)?,
+            SaveFileFormat::CompressedBinary => {
+                bincode::deserialize_from(DeflateDecoder::new(content))?
+            }
+        };
+
+        Ok(data.into_latest())
+    }
+}
@@ -0,0 +1,494 @@
+//! Reading and writing [UniverseSaveData] from and to the file system.
+
+use crate::persistence::data::latest::*;
+use crate::persistence::save_file_format::SaveFileFormat;
+use crate::persistence::saving::parse_session_data_into_universe_save_data;
+use crate::states::ApplicationState;
+use bevy::app::{App, Plugin, Update};
+use bevy::input::ButtonInput;
+use bevy::log::{error, info};
+use bevy::prelude::{
+    in_state, on_event, Commands, Event, EventReader, EventWriter, IntoSystemConfigs, KeyCode,
+    NextState, Res, ResMut, Resource, World,
+};
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+const DEFAULT_SAVE_DIRECTORY: &str = "saves";
+const SAVE_FILE_EXTENSION: &str = "sav";
+/// Save files written before [SaveFileFormat] existed always contain plain JSON.
+const LEGACY_SAVE_FILE_EXTENSION: &str = "json";
+pub const QUICK_SAVE_SLOT_NAME: &str = "quicksave";
+
+/// Registers [SaveGameEvent] and writes the current universe to disk whenever it is received.
+/// Pressing F5 creates a quicksave.
+/// Sending a [LoadGameEvent] replaces the current universe with the contents of a save slot.
+///
+/// Saving is split into two steps, similar to the render extract phase:
+/// All relevant component data is first copied into [SaveDataCollection]s on the main thread,
+/// after which serialization and file IO happen on the [AsyncComputeTaskPool].
+/// [SaveGameStatusEvent]s are sent to notify about the progress.
+pub struct SaveFilePlugin;
+impl Plugin for SaveFilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SaveDirectory>()
+            .init_resource::<RunningSaveTasks>()
+            .add_event::<SaveGameEvent>()
+            .add_event::<SaveGameStatusEvent>()
+            .add_event::<LoadGameEvent>()
+            .add_systems(
+                Update,
+                (
+                    send_quick_save_event_on_hotkey,
+                    (
+                        parse_session_data_into_universe_save_data,
+                        start_writing_save_data_in_background,
+                    )
+                        .chain()
+                        .run_if(on_event::<SaveGameEvent>())
+                        .run_if(in_state(ApplicationState::InGame)),
+                    check_for_finished_save_tasks,
+                    load_save_file_on_event.run_if(on_event::<LoadGameEvent>()),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Send this event to save the current universe into the save slot with the given name.
+/// Existing files inside that slot will be overwritten.
+#[derive(Event)]
+pub struct SaveGameEvent {
+    pub slot_name: String,
+    pub format: SaveFileFormat,
+}
+
+/// Send this event to despawn the current universe and load the save slot with the given name instead.
+#[derive(Event)]
+pub struct LoadGameEvent {
+    pub slot_name: String,
+}
+
+/// Sent whenever the status of a save slot which is currently being written changes.
+#[derive(Event)]
+pub struct SaveGameStatusEvent {
+    pub slot_name: String,
+    pub status: SaveGameStatus,
+}
+
+pub enum SaveGameStatus {
+    /// All data has been extracted from the ECS and is now being written in the background.
+    Started,
+    /// The save file has been written successfully.
+    Finished,
+    /// Something went wrong while writing the save file.
+    Failed(SaveFileError),
+}
+
+/// Contains the background tasks for all save slots which are currently being written.
+#[derive(Resource, Default)]
+pub struct RunningSaveTasks {
+    tasks: Vec<(String, Task<Result<(), SaveFileError>>)>,
+}
+
+impl RunningSaveTasks {
+    #[inline]
+    pub fn is_saving(&self) -> bool {
+        !self.tasks.is_empty()
+    }
+}
+
+/// The directory in which all save slots are stored.
+#[derive(Resource)]
+pub struct SaveDirectory {
+    path: PathBuf,
+}
+
+impl Default for SaveDirectory {
+    fn default() -> Self {
+        Self::new(PathBuf::from(DEFAULT_SAVE_DIRECTORY))
+    }
+}
+
+/// A single save file inside a [SaveDirectory].
+pub struct SaveSlot {
+    pub name: String,
+    pub path: PathBuf,
+    pub last_modified: Option<SystemTime>,
+}
+
+impl SaveDirectory {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Returns the path of the file representing the save slot with the given name.
+    /// Slot names must not point outside of this directory.
+    pub fn slot_path(&self, slot_name: &str) -> Result<PathBuf, SaveFileError> {
+        if slot_name.is_empty() || slot_name.contains(['/', '\\']) || slot_name.contains("..") {
+            return Err(SaveFileError::InvalidSlotName(slot_name.to_string()));
+        }
+
+        Ok(self.path.join(format!("{slot_name}.{SAVE_FILE_EXTENSION}")))
+    }
+
+    /// Same as [Self::slot_path], but falls back to legacy save files if they exist.
+    fn existing_slot_path(&self, slot_name: &str) -> Result<PathBuf, SaveFileError> {
+        let path = self.slot_path(slot_name)?;
+        if path.exists() {
+            return Ok(path);
+        }
+
+        let legacy_path = path.with_extension(LEGACY_SAVE_FILE_EXTENSION);
+        if legacy_path.exists() {
+            Ok(legacy_path)
+        } else {
+            Ok(path)
+        }
+    }
+
+    /// Lists all save slots inside this directory, ordered by their name.
+    /// Returns an empty Vec if the directory doesn't exist yet.
+    pub fn list_slots(&self) -> Result<Vec<SaveSlot>, SaveFileError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut result = Vec::new();
+        for entry in std::fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            let extension = path.extension().and_then(|x| x.to_str());
+            if !path.is_file()
+                || (extension != Some(SAVE_FILE_EXTENSION)
+                    && extension != Some(LEGACY_SAVE_FILE_EXTENSION))
+            {
+                continue;
+            }
+
+            let Some(name) = path.file_stem().and_then(|x| x.to_str()) else {
+                continue;
+            };
+
+            result.push(SaveSlot {
+                name: name.to_string(),
+                last_modified: path.metadata().and_then(|x| x.modified()).ok(),
+                path,
+            });
+        }
+
+        // Legacy files sharing their name with a new one are hidden by them
+        result.sort_by(|a, b| {
+            a.name
+                .cmp(&b.name)
+                .then_with(|| b.path.extension().cmp(&a.path.extension()))
+        });
+        result.dedup_by(|a, b| a.name == b.name);
+        Ok(result)
+    }
+
+    pub fn write(
+        &self,
+        slot_name: &str,
+        data: &UniverseSaveData,
+        format: SaveFileFormat,
+    ) -> Result<(), SaveFileError> {
+        data.write_to_file(&self.slot_path(slot_name)?, format)
+    }
+
+    pub fn read(&self, slot_name: &str) -> Result<UniverseSaveData, SaveFileError> {
+        UniverseSaveData::read_from_file(&self.existing_slot_path(slot_name)?)
+    }
+}
+
+#[derive(Debug)]
+pub enum SaveFileError {
+    Io(std::io::Error),
+    Serialization(serde_json::Error),
+    BinarySerialization(bincode::Error),
+    UnknownFormat(u8),
+    InvalidSlotName(String),
+}
+
+impl Display for SaveFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveFileError::Io(e) => write!(f, "Unable to access save file: {e}"),
+            SaveFileError::Serialization(e) => write!(f, "Save file is malformed: {e}"),
+            SaveFileError::BinarySerialization(e) => write!(f, "Save file is malformed: {e}"),
+            SaveFileError::UnknownFormat(id) => write!(f, "Unknown save file format: {id}"),
+            SaveFileError::InvalidSlotName(name) => write!(f, "Invalid save slot name: {name}"),
+        }
+    }
+}
+
+impl std::error::Error for SaveFileError {}
+
+impl From<std::io::Error> for SaveFileError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for SaveFileError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Serialization(value)
+    }
+}
+
+impl From<bincode::Error> for SaveFileError {
+    fn from(value: bincode::Error) -> Self {
+        Self::BinarySerialization(value)
+    }
+}
+
+impl UniverseSaveData {
+    /// Serializes this into the file at `path` with its version attached,
+    /// creating all missing parent directories.
+    pub fn write_to_file(&self, path: &Path, format: SaveFileFormat) -> Result<(), SaveFileError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        format.encode(self, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads save data of any version and format from the file at `path`
+    /// and migrates it to the latest version.
+    pub fn read_from_file(path: &Path) -> Result<Self, SaveFileError> {
+        let bytes = std::fs::read(path)?;
+        SaveFileFormat::decode(&bytes)
+    }
+
+    /// Inserts all [SaveDataCollection]s and global state as resources, so they will be picked up by
+    /// [`crate::persistence::UniverseSaveDataLoadingPlugin`].
+    pub fn insert_as_resources(self, world: &mut World) {
+        world.insert_resource(self.sectors);
+        world.insert_resource(self.gate_pairs);
+        world.insert_resource(self.stations);
+        world.insert_resource(self.ships);
+        world.insert_resource(self.session);
+        world.insert_resource(self.simulation_time);
+        world.insert_resource(self.persistent_id_counters);
+    }
+}
+
+fn send_quick_save_event_on_hotkey(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut save_events: EventWriter<SaveGameEvent>,
+) {
+    if keys.just_pressed(KeyCode::F5) {
+        save_events.send(SaveGameEvent {
+            slot_name: QUICK_SAVE_SLOT_NAME.to_string(),
+            format: SaveFileFormat::default(),
+        });
+    }
+}
+
+/// Takes the [SaveDataCollection]s created by [parse_session_data_into_universe_save_data]
+/// and starts writing them into all requested save slots on the [AsyncComputeTaskPool].
+#[allow(clippy::too_many_arguments)]
+fn start_writing_save_data_in_background(
+    mut commands: Commands,
+    mut events: EventReader<SaveGameEvent>,
+    mut status_events: EventWriter<SaveGameStatusEvent>,
+    mut running_tasks: ResMut<RunningSaveTasks>,
+    save_directory: Res<SaveDirectory>,
+    mut sectors: ResMut<SaveDataCollection<SectorSaveData>>,
+    mut gate_pairs: ResMut<SaveDataCollection<GatePairSaveData>>,
+    mut stations: ResMut<SaveDataCollection<StationSaveData>>,
+    mut ships: ResMut<SaveDataCollection<ShipSaveData>>,
+    mut session: ResMut<SessionSaveData>,
+    mut simulation_time: ResMut<SimulationTimeSaveData>,
+    mut persistent_id_counters: ResMut<PersistentIdCountersSaveData>,
+) {
+    let data = Arc::new(UniverseSaveData {
+        gate_pairs: std::mem::take(&mut *gate_pairs),
+        sectors: std::mem::take(&mut *sectors),
+        ships: std::mem::take(&mut *ships),
+        stations: std::mem::take(&mut *stations),
+        session: std::mem::take(&mut *session),
+        simulation_time: std::mem::take(&mut *simulation_time),
+        persistent_id_counters: std::mem::take(&mut *persistent_id_counters),
+    });
+
+    let task_pool = AsyncComputeTaskPool::get();
+    for event in events.read() {
+        let path = match save_directory.slot_path(&event.slot_name) {
+            Ok(path) => path,
+            Err(e) => {
+                error!("Failed to save slot {}: {e}", event.slot_name);
+                status_events.send(SaveGameStatusEvent {
+                    slot_name: event.slot_name.clone(),
+                    status: SaveGameStatus::Failed(e),
+                });
+                continue;
+            }
+        };
+        let data = data.clone();
+        let format = event.format;
+        let task = task_pool.spawn(async move { data.write_to_file(&path, format) });
+
+        running_tasks.tasks.push((event.slot_name.clone(), task));
+        status_events.send(SaveGameStatusEvent {
+            slot_name: event.slot_name.clone(),
+            status: SaveGameStatus::Started,
+        });
+    }
+
+    commands.remove_resource::<SaveDataCollection<SectorSaveData>>();
+    commands.remove_resource::<SaveDataCollection<GatePairSaveData>>();
+    commands.remove_resource::<SaveDataCollection<StationSaveData>>();
+    commands.remove_resource::<SaveDataCollection<ShipSaveData>>();
+    commands.remove_resource::<SessionSaveData>();
+    commands.remove_resource::<SimulationTimeSaveData>();
+    commands.remove_resource::<PersistentIdCountersSaveData>();
+}
+
+fn check_for_finished_save_tasks(
+    mut running_tasks: ResMut<RunningSaveTasks>,
+    mut status_events: EventWriter<SaveGameStatusEvent>,
+) {
+    if !running_tasks.is_saving() {
+        return;
+    }
+
+    let (finished, running): (Vec<_>, Vec<_>) = std::mem::take(&mut running_tasks.tasks)
+        .into_iter()
+        .partition(|(_, task)| task.is_finished());
+    running_tasks.tasks = running;
+
+    for (slot_name, task) in finished {
+        let status = match block_on(task) {
+            Ok(_) => {
+                info!("Saved universe into slot {slot_name}.");
+                SaveGameStatus::Finished
+            }
+            Err(e) => {
+                error!("Failed to save slot {slot_name}: {e}");
+                SaveGameStatus::Failed(e)
+            }
+        };
+
+        status_events.send(SaveGameStatusEvent { slot_name, status });
+    }
+}
+
+fn load_save_file_on_event(
+    mut commands: Commands,
+    mut events: EventReader<LoadGameEvent>,
+    save_directory: Res<SaveDirectory>,
+    mut next_state: ResMut<NextState<ApplicationState>>,
+) {
+    let Some(event) = events.read().last() else {
+        return;
+    };
+
+    match save_directory.read(&event.slot_name) {
+        Ok(data) => {
+            info!("Loading universe from slot {}.", event.slot_name);
+            commands.add(move |world: &mut World| data.insert_as_resources(world));
+            next_state.set(ApplicationState::Loading);
+        }
+        Err(e) => error!("Failed to load slot {}: {e}", event.slot_name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::persistence::local_hex_position::LocalHexPosition;
+    use crate::persistence::save_file_format::SaveFileFormat;
+    use crate::persistence::save_files::{SaveDirectory, SaveFileError};
+    use crate::persistence::{ShipBehaviorSaveData, UniverseSaveData};
+    use crate::simulation::prelude::SimulationTimestamp;
+    use bevy::prelude::Vec2;
+    use hexx::Hex;
+    use std::path::PathBuf;
+
+    const CENTER: Hex = Hex::new(0, 0);
+    const RIGHT: Hex = Hex::new(1, 0);
+
+    /// A fresh directory for every test and process, so parallel and repeated runs don't interfere.
+    struct TestDirectory(PathBuf);
+
+    impl TestDirectory {
+        fn new(test_name: &str) -> Self {
+            let path = std::env::temp_dir()
+                .join(format!("rusty_space_{test_name}_{}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TestDirectory {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn writing_and_reading_a_save_slot_should_yield_equal_results() {
+        let mut data = UniverseSaveData::default();
+        data.sectors.add(CENTER);
+        data.sectors.add(RIGHT);
+        data.gate_pairs.add(
+            LocalHexPosition::new(CENTER, Vec2::X),
+            LocalHexPosition::new(RIGHT, Vec2::NEG_X),
+        );
+        data.ships.add(
+            LocalHexPosition::new(CENTER, Vec2::Y),
+            2.0,
+            String::from("Fancy test ship"),
+            ShipBehaviorSaveData::AutoTrade {
+                next_idle_update: SimulationTimestamp::from(249),
+                max_jump_range: 2,
+            },
+        );
+        data.stations.add(
+            LocalHexPosition::new(RIGHT, Vec2::NEG_Y),
+            String::from("Fancy test station"),
+        );
+
+        let test_directory = TestDirectory::new("save_slot_round_trip");
+        let directory = SaveDirectory::new(test_directory.0.clone());
+        assert!(directory.list_slots().unwrap().is_empty());
+
+        directory
+            .write("test", &data, SaveFileFormat::default())
+            .unwrap();
+        let slots = directory.list_slots().unwrap();
+        let loaded = directory.read("test").unwrap();
+
+        assert_eq!(1, slots.len());
+        assert_eq!("test", slots[0].name);
+        assert_eq!(data, loaded);
+    }
+
+    #[test]
+    fn slot_names_must_not_leave_the_save_directory() {
+        let test_directory = TestDirectory::new("save_slot_names");
+        let directory = SaveDirectory::new(test_directory.0.clone());
+
+        for slot_name in ["", "../escape", "nested/slot", "nested\\slot", ".."] {
+            assert!(
+                matches!(
+                    directory.write(
+                        slot_name,
+                        &UniverseSaveData::default(),
+                        SaveFileFormat::default()
+                    ),
+                    Err(SaveFileError::InvalidSlotName(_))
+                ),
+                "Slot name {slot_name:?} should be rejected!"
+            );
+        }
+        assert!(directory.slot_path("quicksave").is_ok());
+    }
+}
@@ -1,43 +1,54 @@
 use crate::components::{
-    Asteroid, BuyOrders, Gate, InSector, Inventory, Sector, SellOrders, Ship, Star, Station,
+    Asteroid, BuyOrders, ConstantOrbit, GasGiant, Gate, InSector, InteractionQueue, Inventory,
+    IsDocked, Owner, Planet, Sector, SellOrders, Ship, Star, Station, Wallet,
 };
-use crate::persistence::data::v1::*;
+use crate::persistence::data::latest::*;
 use crate::persistence::writer::sectors::SectorSaveDataQuery;
-use crate::persistence::AllEntityIdMaps;
+use crate::persistence::writer::tasks::ActiveTaskQuery;
+use crate::persistence::{AllEntityIdMaps, PersistentIdCounters};
+use crate::session_data::SessionData;
 use crate::simulation::physics::{ConstantVelocity, ShipVelocity};
+use crate::simulation::prelude::SimulationTime;
 use crate::simulation::production::{ProductionComponent, ShipyardComponent};
-use crate::simulation::ship_ai::{AutoMineBehavior, AutoTradeBehavior, TaskQueue};
+use crate::simulation::ship_ai::{
+    AutoHarvestBehavior, AutoMineBehavior, AutoTradeBehavior, TaskQueue,
+};
 use crate::simulation::transform::simulation_transform::SimulationTransform;
 use bevy::core::Name;
-use bevy::prelude::{Commands, Query};
+use bevy::prelude::{Commands, Has, Query, Res};
 
-/// Stores all relevant entities in SaveDataCollection Resources.
-/// Ideally, later on this should be completely decoupled from the main loop, maybe start an async
-/// task running in the background after copying all relevant data to write stuff to disk and such.
+/// Stores all relevant entities in SaveDataCollection Resources, alongside global state such as [SessionData],
+/// [SimulationTime] and the persistent ID counters.
+/// This is the extract step of saving, [`crate::persistence::save_files`] then writes those to disk
+/// in the background.
 ///
-/// For as long as there is no "public test build", save data is not guaranteed to be compatible
-/// with older/newer versions of the game - implementing and maintaining versioning for rapidly
-/// changing data structures is way too much work.
+/// Data is always written in the latest version, see [`crate::persistence::data`] for how older
+/// save files are migrated.
 #[allow(clippy::type_complexity)]
 #[allow(clippy::too_many_arguments)] // Haha, like, uh, yeah. No.
-#[allow(unused)] // That's gonna fix itself as soon as we actually load stuff from disk
 pub fn parse_session_data_into_universe_save_data(
     mut commands: Commands,
     all_sectors: Query<&Sector>,
     stars: Query<&Star>,
+    planets: Query<(&Planet, &Name, &ConstantOrbit, Has<GasGiant>)>,
     asteroids: Query<(&Asteroid, &SimulationTransform, &ConstantVelocity)>,
     gates: Query<(&Gate, &InSector, &SimulationTransform)>,
     sectors_to_save: Query<SectorSaveDataQuery>,
     ships: Query<(
         &Ship,
         &Name,
-        &InSector,
+        Option<&InSector>,
         &SimulationTransform,
         &TaskQueue,
         &ShipVelocity,
         &Inventory,
         Option<&AutoTradeBehavior>,
         Option<&AutoMineBehavior>,
+        Option<&AutoHarvestBehavior>,
+        ActiveTaskQuery,
+        Option<&IsDocked>,
+        Option<&Owner>,
+        Option<&Wallet>,
     )>,
     stations: Query<(
         &Station,
@@ -49,47 +60,62 @@ pub fn parse_session_data_into_universe_save_data(
         Option<&ShipyardComponent>,
         Option<&BuyOrders>,
         Option<&SellOrders>,
+        &InteractionQueue,
+        Option<&Owner>,
+        &Wallet,
     )>,
     all_entity_id_maps: AllEntityIdMaps,
+    session_data: Res<SessionData>,
+    simulation_time: Res<SimulationTime>,
+    persistent_id_counters: Res<PersistentIdCounters>,
 ) {
     let gate_pairs = GatePairSaveData::extract_from_sector_query(&all_sectors, &gates);
 
-    let stations = stations
-        .iter()
-        .map(|query_content| StationSaveData::from(query_content, &all_sectors));
+    let stations = stations.iter().map(|query_content| {
+        StationSaveData::from(query_content, &all_sectors, &all_entity_id_maps)
+    });
 
-    let ships = ships
-        .iter()
-        .map(|query_content| ShipSaveData::from(query_content, &all_sectors, &all_entity_id_maps));
+    let ships = ships.iter().map(|query_content| {
+        ShipSaveData::from(query_content, &all_sectors, &gates, &all_entity_id_maps)
+    });
 
     let sectors = sectors_to_save
         .iter()
-        .map(|x| SectorSaveData::from(x, &asteroids, &stars));
+        .map(|x| SectorSaveData::from(x, &asteroids, &stars, &planets));
 
     commands.insert_resource(SaveDataCollection::<SectorSaveData>::from(sectors));
     commands.insert_resource(SaveDataCollection::<GatePairSaveData>::from(gate_pairs));
     commands.insert_resource(SaveDataCollection::<ShipSaveData>::from(ships));
     commands.insert_resource(SaveDataCollection::<StationSaveData>::from(stations));
+    commands.insert_resource(SessionSaveData::from(session_data.as_ref()));
+    commands.insert_resource(SimulationTimeSaveData::from(simulation_time.as_ref()));
+    commands.insert_resource(PersistentIdCountersSaveData::from(
+        persistent_id_counters.as_ref(),
+    ));
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::game_data::DEBUG_ITEM_ID_A;
     use crate::persistence::local_hex_position::LocalHexPosition;
     use crate::persistence::saving::parse_session_data_into_universe_save_data;
     use crate::persistence::{
-        GatePairSaveData, SaveDataCollection, SectorSaveData, ShipBehaviorSaveData, ShipSaveData,
-        StationSaveData, UniverseSaveData,
+        ExchangeWareSaveData, FactionSaveData, GatePairSaveData, GateTraversalStateSaveData,
+        InteractionQueueSaveData, PersistentIdCountersSaveData, RelationSaveData,
+        SaveDataCollection, SaveFileFormat, SectorSaveData, SessionSaveData, ShipBehaviorSaveData,
+        ShipConfigurationSaveData, ShipSaveData, SimulationTimeSaveData, StationSaveData,
+        TaskSaveData, UniverseSaveData,
     };
     use crate::simulation::prelude::SimulationTimestamp;
     use bevy::ecs::system::RunSystemOnce;
     use bevy::prelude::Vec2;
     use hexx::Hex;
+    use std::time::Duration;
 
     const CENTER: Hex = Hex::new(0, 0);
     const RIGHT: Hex = Hex::new(1, 0);
 
-    #[test]
-    fn test_loading_then_saving_should_yield_equal_results() {
+    fn build_test_data() -> UniverseSaveData {
         let mut loaded_data = UniverseSaveData::default();
         loaded_data.sectors.add(CENTER);
         loaded_data.sectors.add(RIGHT);
@@ -97,24 +123,67 @@ mod tests {
             LocalHexPosition::new(CENTER, Vec2::X),
             LocalHexPosition::new(RIGHT, Vec2::NEG_X),
         );
-        loaded_data.ships.add(
-            LocalHexPosition::new(CENTER, Vec2::Y),
-            2.0,
-            String::from("Fancy test ship"),
-            ShipBehaviorSaveData::AutoTrade {
-                next_idle_update: SimulationTimestamp::from(249),
-            },
-        );
-        loaded_data.stations.add(
-            LocalHexPosition::new(RIGHT, Vec2::NEG_Y),
-            String::from("Fancy test station"),
-        );
+        loaded_data
+            .ships
+            .add(
+                LocalHexPosition::new(CENTER, Vec2::Y),
+                2.0,
+                String::from("Fancy test ship"),
+                ShipBehaviorSaveData::AutoTrade {
+                    next_idle_update: SimulationTimestamp::from(249),
+                    max_jump_range: 2,
+                },
+            )
+            .with_owner(3);
+        loaded_data
+            .stations
+            .add(
+                LocalHexPosition::new(RIGHT, Vec2::NEG_Y),
+                String::from("Fancy test station"),
+            )
+            .with_owner(3);
+        loaded_data.session = SessionSaveData {
+            ship_configurations: vec![ShipConfigurationSaveData {
+                id: 7,
+                name: String::from("Fancy test configuration"),
+                duration: 1234,
+                materials: vec![(1, 5), (2, 10)],
+            }],
+            factions: vec![
+                FactionSaveData {
+                    id: 3,
+                    name: String::from("Fancy test faction"),
+                    credits: 4321,
+                    required_favor: -1000,
+                },
+                FactionSaveData {
+                    id: 4,
+                    name: String::from("Fancy rival faction"),
+                    credits: 1234,
+                    required_favor: 500,
+                },
+            ],
+            relations: vec![RelationSaveData {
+                faction: 4,
+                towards: 3,
+                favor: -200,
+                perception: 800,
+            }],
+        };
+        loaded_data.simulation_time = SimulationTimeSaveData {
+            total: Duration::from_millis(12345),
+            tick: 42,
+        };
+        loaded_data.persistent_id_counters = PersistentIdCountersSaveData::from_current_counters();
+        loaded_data
+    }
 
+    fn load_then_save(loaded_data: &UniverseSaveData) -> UniverseSaveData {
         let mut app = loaded_data.clone().build_test_app();
         let world = app.world_mut();
 
         world.run_system_once(parse_session_data_into_universe_save_data);
-        let saved_data = UniverseSaveData {
+        let mut saved_data = UniverseSaveData {
             sectors: world
                 .remove_resource::<SaveDataCollection<SectorSaveData>>()
                 .unwrap(),
@@ -127,11 +196,119 @@ mod tests {
             ships: world
                 .remove_resource::<SaveDataCollection<ShipSaveData>>()
                 .unwrap(),
+            session: world.remove_resource::<SessionSaveData>().unwrap(),
+            simulation_time: world.remove_resource::<SimulationTimeSaveData>().unwrap(),
+            persistent_id_counters: world
+                .remove_resource::<PersistentIdCountersSaveData>()
+                .unwrap(),
         };
 
+        // Query iteration order depends on archetypes, which differ depending on what ships are doing
+        saved_data.ships.data.sort_by_key(|x| x.id);
+        saved_data.stations.data.sort_by_key(|x| x.id);
+
+        saved_data
+    }
+
+    #[test]
+    fn test_loading_then_saving_should_yield_equal_results() {
+        let loaded_data = build_test_data();
+        let saved_data = load_then_save(&loaded_data);
+
         assert_eq!(
             loaded_data, saved_data,
             "Save data wasn't equal after loading and saving. Maybe stuff isn't ordered correctly?"
         );
     }
+
+    #[test]
+    fn test_loading_then_saving_should_yield_equal_results_for_all_file_formats() {
+        let loaded_data = build_test_data();
+        let saved_data = load_then_save(&loaded_data);
+
+        for format in SaveFileFormat::ALL {
+            let mut bytes = Vec::new();
+            format.encode(&saved_data, &mut bytes).unwrap();
+            let decoded_data = SaveFileFormat::decode(&bytes).unwrap();
+
+            assert_eq!(
+                loaded_data, decoded_data,
+                "Save data wasn't equal after encoding and decoding it as {format:?}."
+            );
+        }
+    }
+
+    #[test]
+    fn test_loading_then_saving_should_preserve_task_and_interaction_state() {
+        let mut loaded_data = build_test_data();
+        let enter_gate = loaded_data.gate_pairs.data[0].from_id;
+        let station = loaded_data.stations.data[0].id;
+        loaded_data.stations.data[0].inventory.items = vec![(DEBUG_ITEM_ID_A, 0)];
+
+        let trade_behavior = ShipBehaviorSaveData::AutoTrade {
+            next_idle_update: SimulationTimestamp::from(0),
+            max_jump_range: 2,
+        };
+
+        let docked_ship = loaded_data.ships.add(
+            LocalHexPosition::new(RIGHT, Vec2::NEG_Y),
+            0.0,
+            String::from("Docked ship"),
+            trade_behavior,
+        );
+        docked_ship.docked_at = Some(station.into());
+        docked_ship.inventory.items = vec![(DEBUG_ITEM_ID_A, 10)];
+        docked_ship.task_queue = vec![
+            TaskSaveData::ExchangeWares {
+                target: station.into(),
+                data: ExchangeWareSaveData::Sell(DEBUG_ITEM_ID_A, 5, 10),
+                finishes_at: SimulationTimestamp::from(500),
+                deadline: SimulationTimestamp::from(9000),
+            },
+            TaskSaveData::Undock {
+                start_position: None,
+            },
+        ];
+
+        let waiting_ship = loaded_data.ships.add(
+            LocalHexPosition::new(RIGHT, Vec2::ZERO),
+            0.0,
+            String::from("Waiting ship"),
+            trade_behavior,
+        );
+        let waiting_ship_id = waiting_ship.id;
+        waiting_ship.task_queue = vec![
+            TaskSaveData::AwaitingSignal,
+            TaskSaveData::DockAtEntity {
+                target: station.into(),
+            },
+        ];
+
+        let traversing_ship = loaded_data.ships.add(
+            LocalHexPosition::new(CENTER, Vec2::X),
+            0.0,
+            String::from("Traversing ship"),
+            trade_behavior,
+        );
+        traversing_ship.task_queue = vec![TaskSaveData::UseGate {
+            enter_gate,
+            exit_sector: RIGHT,
+            progress: 0.25,
+            traversal_state: GateTraversalStateSaveData::BlendingIntoMotion {
+                origin: Vec2::new(1.0, 2.0),
+            },
+        }];
+
+        loaded_data.stations.data[0].interaction_queue = InteractionQueueSaveData {
+            currently_interacting: 4,
+            waiting: vec![waiting_ship_id],
+        };
+
+        let saved_data = load_then_save(&loaded_data);
+
+        assert_eq!(
+            loaded_data, saved_data,
+            "Task and interaction state wasn't equal after loading and saving."
+        );
+    }
 }
@@ -0,0 +1,517 @@
+//! Scenarios describe a freshly started universe in a human-readable format,
+//! so test maps can be created and shared without touching any code.
+//!
+//! They are written in RON and turned into [UniverseSaveData] through the builder methods, so the
+//! regular loading process takes care of spawning everything.
+
+use crate::constants;
+use crate::game_data::{GameData, ItemId, ProductionModuleId, RecipeId, ShipyardModuleId};
+use crate::map_layout::MapLayout;
+use crate::persistence::data::latest::*;
+use crate::persistence::local_hex_position::LocalHexPosition;
+use crate::session_data::{FactionId, SessionData};
+use crate::simulation::prelude::{Milliseconds, SimulationTimestamp};
+use crate::simulation::ship_ai::AutoMineState;
+use bevy::math::Vec2;
+use bevy::utils::HashSet;
+use hexx::Hex;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// The scenario which is loaded when neither a save slot nor a scenario was requested.
+pub const DEFAULT_SCENARIO_PATH: &str = "assets/scenarios/default.ron";
+
+#[derive(Deserialize)]
+pub struct Scenario {
+    pub sectors: Vec<ScenarioSector>,
+    #[serde(default)]
+    pub gates: Vec<ScenarioGate>,
+    #[serde(default)]
+    pub stations: Vec<ScenarioStation>,
+    #[serde(default)]
+    pub fleets: Vec<ScenarioFleet>,
+    #[serde(default)]
+    pub factions: Vec<ScenarioFaction>,
+}
+
+#[derive(Deserialize)]
+pub struct ScenarioSector {
+    pub coordinate: Hex,
+    #[serde(default)]
+    pub star: bool,
+    #[serde(default)]
+    pub planets: Vec<ScenarioPlanet>,
+    #[serde(default)]
+    pub asteroids: Option<ScenarioAsteroidField>,
+}
+
+#[derive(Deserialize)]
+pub struct ScenarioPlanet {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub kind: Option<PlanetKindSaveData>,
+    pub orbit_radius: f32,
+    #[serde(default)]
+    pub rotational_fraction: f32,
+}
+
+/// Asteroids are scattered randomly, but the same sector will always end up with the same layout.
+#[derive(Deserialize)]
+pub struct ScenarioAsteroidField {
+    pub amount: usize,
+    pub average_velocity: Vec2,
+}
+
+#[derive(Deserialize)]
+pub struct ScenarioGate {
+    pub from: LocalHexPosition,
+    pub to: LocalHexPosition,
+}
+
+#[derive(Deserialize)]
+pub struct ScenarioStation {
+    pub name: String,
+    pub position: LocalHexPosition,
+    #[serde(default)]
+    pub production: Vec<ScenarioProductionModule>,
+    #[serde(default)]
+    pub shipyard: Vec<ScenarioShipyardModule>,
+    #[serde(default)]
+    pub buys: Vec<ItemId>,
+    #[serde(default)]
+    pub sells: Vec<ItemId>,
+    #[serde(default)]
+    pub owner: Option<FactionId>,
+}
+
+#[derive(Deserialize)]
+pub struct ScenarioProductionModule {
+    pub module: ProductionModuleId,
+    pub recipe: RecipeId,
+    pub amount: u32,
+}
+
+#[derive(Deserialize)]
+pub struct ScenarioShipyardModule {
+    pub module: ShipyardModuleId,
+    pub amount: u32,
+}
+
+/// A group of identical ships. They are spawned in a circle and named after the fleet, followed by their index.
+#[derive(Deserialize)]
+pub struct ScenarioFleet {
+    pub name: String,
+    pub amount: u32,
+    pub position: LocalHexPosition,
+    pub behavior: ScenarioShipBehavior,
+    /// Only used by trading ships, defaults to [constants::DEFAULT_MAX_TRADE_JUMP_RANGE].
+    #[serde(default)]
+    pub max_jump_range: Option<u8>,
+    /// Owned ships trade on their faction's account, independent ones start with [constants::SHIP_STARTING_CREDITS].
+    #[serde(default)]
+    pub owner: Option<FactionId>,
+}
+
+#[derive(Deserialize)]
+pub struct ScenarioFaction {
+    pub id: FactionId,
+    pub name: String,
+    pub credits: i64,
+    /// Defaults to [constants::DEFAULT_REQUIRED_FAVOR].
+    #[serde(default)]
+    pub required_favor: Option<i64>,
+}
+
+#[derive(Deserialize, Copy, Clone)]
+pub enum ScenarioShipBehavior {
+    AutoTrade,
+    AutoMine,
+    AutoHarvest,
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, ron::error::SpannedError),
+    DuplicateSector(Hex),
+    DuplicateFaction(FactionId),
+    UnknownSector {
+        context: String,
+        sector: Hex,
+    },
+    UnknownGameData {
+        context: String,
+        kind: &'static str,
+        id: u32,
+    },
+    UnknownFaction {
+        context: String,
+        faction: FactionId,
+    },
+}
+
+impl Display for ScenarioError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScenarioError::Io(path, e) => write!(f, "Unable to read {}: {e}", path.display()),
+            ScenarioError::Parse(path, e) => write!(f, "{} is malformed: {e}", path.display()),
+            ScenarioError::DuplicateSector(hex) => {
+                write!(f, "Sector [{},{}] is defined more than once", hex.x, hex.y)
+            }
+            ScenarioError::DuplicateFaction(faction) => {
+                write!(f, "Faction {faction} is defined more than once")
+            }
+            ScenarioError::UnknownSector { context, sector } => write!(
+                f,
+                "{context} refers to sector [{},{}], which isn't defined",
+                sector.x, sector.y
+            ),
+            ScenarioError::UnknownGameData { context, kind, id } => {
+                write!(f, "{context} refers to {kind} {id}, which isn't defined")
+            }
+            ScenarioError::UnknownFaction { context, faction } => {
+                write!(
+                    f,
+                    "{context} is owned by faction {faction}, which isn't defined"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl Scenario {
+    pub fn read_from_file(path: &Path) -> Result<Self, ScenarioError> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| ScenarioError::Io(path.to_path_buf(), e))?;
+        ron::from_str(&content).map_err(|e| ScenarioError::Parse(path.to_path_buf(), e))
+    }
+
+    /// Makes sure that everything inside this scenario is placed in a sector which actually exists,
+    /// owned by a faction which is defined and only uses items, recipes and modules which are defined in `game_data`.
+    pub fn validate(&self, game_data: &GameData) -> Result<(), ScenarioError> {
+        let mut sectors = HashSet::new();
+        for sector in &self.sectors {
+            if !sectors.insert(sector.coordinate) {
+                return Err(ScenarioError::DuplicateSector(sector.coordinate));
+            }
+        }
+
+        let mut factions = HashSet::new();
+        for faction in &self.factions {
+            if !factions.insert(faction.id) {
+                return Err(ScenarioError::DuplicateFaction(faction.id));
+            }
+        }
+
+        let verify_owner = |context: &dyn Fn() -> String, owner: Option<FactionId>| match owner {
+            Some(faction) if !factions.contains(&faction) => Err(ScenarioError::UnknownFaction {
+                context: context(),
+                faction,
+            }),
+            _ => Ok(()),
+        };
+
+        let verify = |context: &dyn Fn() -> String, position: &LocalHexPosition| {
+            if sectors.contains(&position.sector) {
+                Ok(())
+            } else {
+                Err(ScenarioError::UnknownSector {
+                    context: context(),
+                    sector: position.sector,
+                })
+            }
+        };
+
+        for (index, gate) in self.gates.iter().enumerate() {
+            let context = || format!("Gate pair #{index}");
+            verify(&context, &gate.from)?;
+            verify(&context, &gate.to)?;
+        }
+        for station in &self.stations {
+            let context = || format!("Station {}", station.name);
+            verify(&context, &station.position)?;
+            verify_owner(&context, station.owner)?;
+            station.validate_game_data(game_data)?;
+        }
+        for fleet in &self.fleets {
+            let context = || format!("Fleet {}", fleet.name);
+            verify(&context, &fleet.position)?;
+            verify_owner(&context, fleet.owner)?;
+        }
+
+        Ok(())
+    }
+
+    /// Validates this scenario and converts it into the data for a universe which has just been created.
+    pub fn into_universe_save_data(
+        self,
+        game_data: &GameData,
+    ) -> Result<UniverseSaveData, ScenarioError> {
+        self.validate(game_data)?;
+
+        let map_layout = MapLayout::default();
+        let mut result = UniverseSaveData {
+            session: SessionSaveData::from(&SessionData::mock_data()),
+            ..Default::default()
+        };
+        result.session.factions = self
+            .factions
+            .into_iter()
+            .map(|faction| FactionSaveData {
+                id: faction.id,
+                name: faction.name,
+                credits: faction.credits,
+                required_favor: faction
+                    .required_favor
+                    .unwrap_or(constants::DEFAULT_REQUIRED_FAVOR),
+            })
+            .collect();
+
+        for sector in self.sectors {
+            sector.add_to(&mut result.sectors, &map_layout);
+        }
+
+        for gate in self.gates {
+            result.gate_pairs.add(gate.from, gate.to);
+        }
+
+        for station in self.stations {
+            station.add_to(&mut result.stations);
+        }
+
+        for fleet in self.fleets {
+            fleet.add_to(&mut result.ships);
+        }
+
+        result.persistent_id_counters = PersistentIdCountersSaveData::from_current_counters();
+        Ok(result)
+    }
+}
+
+impl ScenarioSector {
+    fn add_to(self, sectors: &mut SaveDataCollection<SectorSaveData>, map_layout: &MapLayout) {
+        let sector = sectors.add(self.coordinate);
+        if self.star {
+            sector.with_star(SectorStarSaveData::new());
+        }
+
+        for planet in self.planets {
+            let mut data = SectorPlanetSaveData::new(
+                ConstantOrbitSaveData::new(planet.orbit_radius)
+                    .with_current_rotational_fraction(planet.rotational_fraction),
+            );
+            if let Some(name) = planet.name {
+                data = data.with_name(name);
+            }
+            if let Some(kind) = planet.kind {
+                data = data.with_kind(kind);
+            }
+
+            sector.with_planet(data);
+        }
+
+        if let Some(asteroids) = self.asteroids {
+            sector.with_asteroids(
+                SectorAsteroidSaveData::new()
+                    .with_average_velocity(asteroids.average_velocity)
+                    .add_random_live_asteroids(self.coordinate, asteroids.amount, map_layout),
+            );
+        }
+    }
+}
+
+impl ScenarioStation {
+    fn validate_game_data(&self, game_data: &GameData) -> Result<(), ScenarioError> {
+        let verify = |kind: &'static str, id: u32, exists: bool| {
+            if exists {
+                Ok(())
+            } else {
+                Err(ScenarioError::UnknownGameData {
+                    context: format!("Station {}", self.name),
+                    kind,
+                    id,
+                })
+            }
+        };
+
+        for module in &self.production {
+            verify(
+                "production module",
+                module.module,
+                game_data.production_modules.contains_key(&module.module),
+            )?;
+            verify(
+                "recipe",
+                module.recipe,
+                game_data.item_recipes.contains_key(&module.recipe),
+            )?;
+        }
+        for module in &self.shipyard {
+            verify(
+                "shipyard module",
+                module.module,
+                game_data.shipyard_modules.contains_key(&module.module),
+            )?;
+        }
+        for item_id in self.buys.iter().chain(self.sells.iter()) {
+            verify("item", *item_id, game_data.items.contains_key(item_id))?;
+        }
+
+        Ok(())
+    }
+
+    fn add_to(self, stations: &mut SaveDataCollection<StationSaveData>) {
+        let station = stations.add(self.position, self.name);
+        for module in self.production {
+            station.with_production(module.amount, module.module, module.recipe);
+        }
+        for module in self.shipyard {
+            station.with_shipyard(module.amount, module.module);
+        }
+        if !self.buys.is_empty() {
+            station.with_buys(self.buys);
+        }
+        if !self.sells.is_empty() {
+            station.with_sells(self.sells);
+        }
+        if let Some(owner) = self.owner {
+            station.with_owner(owner);
+        }
+    }
+}
+
+impl ScenarioFleet {
+    fn add_to(self, ships: &mut SaveDataCollection<ShipSaveData>) {
+        let rotation_factor = (std::f32::consts::PI * 2.0) / self.amount as f32;
+        for i in 0..self.amount {
+            // Spread out the first idle update so not all ships start thinking within the same tick.
+            let next_idle_update = SimulationTimestamp::from(i as Milliseconds % 1000);
+            let ship = ships.add(
+                LocalHexPosition::new(self.position.sector, self.position.position),
+                rotation_factor * (i as f32),
+                format!("{} {i}", self.name),
+                self.behavior.parse(next_idle_update, self.max_jump_range),
+            );
+            if let Some(owner) = self.owner {
+                ship.with_owner(owner);
+            }
+        }
+    }
+}
+
+impl ScenarioShipBehavior {
+    fn parse(
+        self,
+        next_idle_update: SimulationTimestamp,
+        max_jump_range: Option<u8>,
+    ) -> ShipBehaviorSaveData {
+        match self {
+            ScenarioShipBehavior::AutoTrade => ShipBehaviorSaveData::AutoTrade {
+                next_idle_update,
+                max_jump_range: max_jump_range.unwrap_or(constants::DEFAULT_MAX_TRADE_JUMP_RANGE),
+            },
+            ScenarioShipBehavior::AutoMine => ShipBehaviorSaveData::AutoMine {
+                next_idle_update,
+                state: AutoMineState::Mining,
+            },
+            ScenarioShipBehavior::AutoHarvest => ShipBehaviorSaveData::AutoHarvest {
+                next_idle_update,
+                state: AutoMineState::Mining,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_bundled_scenario(file_name: &str) -> Scenario {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("assets/scenarios")
+            .join(file_name);
+        Scenario::read_from_file(&path).unwrap()
+    }
+
+    #[test]
+    fn default_scenario_is_valid() {
+        let data = read_bundled_scenario("default.ron")
+            .into_universe_save_data(&GameData::mock_data())
+            .unwrap();
+
+        assert_eq!(data.sectors.data.len(), 5);
+        assert_eq!(data.gate_pairs.data.len(), 4);
+        assert_eq!(data.stations.data.len(), 4);
+        assert_eq!(data.ships.data.len(), 60);
+    }
+
+    #[test]
+    fn references_to_unknown_sectors_are_rejected() {
+        let scenario: Scenario = ron::from_str(
+            r#"(
+                sectors: [(coordinate: (x: 0, y: 0))],
+                stations: [(name: "Lost", position: (sector: (x: 1, y: 0), position: (0.0, 0.0)))],
+            )"#,
+        )
+        .unwrap();
+
+        let Err(ScenarioError::UnknownSector { sector, .. }) =
+            scenario.into_universe_save_data(&GameData::mock_data())
+        else {
+            panic!("Stations in unknown sectors should not be accepted!");
+        };
+        assert_eq!(sector, Hex::new(1, 0));
+    }
+
+    #[test]
+    fn references_to_unknown_game_data_are_rejected() {
+        let scenario: Scenario = ron::from_str(
+            r#"(
+                sectors: [(coordinate: (x: 0, y: 0))],
+                stations: [(
+                    name: "Confused",
+                    position: (sector: (x: 0, y: 0), position: (0.0, 0.0)),
+                    production: [(module: 1, recipe: 404, amount: 1)],
+                )],
+            )"#,
+        )
+        .unwrap();
+
+        let Err(ScenarioError::UnknownGameData { kind, id, .. }) =
+            scenario.into_universe_save_data(&GameData::mock_data())
+        else {
+            panic!("Stations with unknown recipes should not be accepted!");
+        };
+        assert_eq!(kind, "recipe");
+        assert_eq!(id, 404);
+    }
+
+    #[test]
+    fn fleets_owned_by_unknown_factions_are_rejected() {
+        let scenario: Scenario = ron::from_str(
+            r#"(
+                sectors: [(coordinate: (x: 0, y: 0))],
+                factions: [(id: 1, name: "Known", credits: 1000)],
+                fleets: [(
+                    name: "Stray",
+                    amount: 1,
+                    position: (sector: (x: 0, y: 0), position: (0.0, 0.0)),
+                    behavior: AutoTrade,
+                    owner: Some(2),
+                )],
+            )"#,
+        )
+        .unwrap();
+
+        let Err(ScenarioError::UnknownFaction { faction, .. }) =
+            scenario.into_universe_save_data(&GameData::mock_data())
+        else {
+            panic!("Fleets owned by unknown factions should not be accepted!");
+        };
+        assert_eq!(faction, 2);
+    }
+}
@@ -0,0 +1,404 @@
+//! Procedurally creates universes from a seed, so every seed can be reproduced later on.
+//!
+//! Sectors are grouped into regions around randomly picked center sectors. Gates are placed freely
+//! within each region, but there are only as many gates between regions as are needed to reach every
+//! sector, creating the hubs and choke points described in `docs/design/map.md`.
+//!
+//! Optionally, every region receives a small production chain and ships are spread evenly across all sectors,
+//! which is mostly useful for stress tests and benchmarks.
+
+use crate::constants;
+use crate::game_data::{
+    DEBUG_ITEM_ID_A, DEBUG_ITEM_ID_B, DEBUG_ITEM_ID_C, PRODUCTION_MODULE_A_ID,
+    PRODUCTION_MODULE_B_ID, PRODUCTION_MODULE_C_ID, RECIPE_A_ID, RECIPE_B_ID, RECIPE_C_ID,
+    SHIPYARD_MODULE_ID,
+};
+use crate::map_layout::MapLayout;
+use crate::persistence::data::latest::*;
+use crate::persistence::local_hex_position::LocalHexPosition;
+use crate::session_data::SessionData;
+use crate::simulation::prelude::{Milliseconds, SimulationTimestamp};
+use crate::simulation::ship_ai::AutoMineState;
+use crate::utils::EarthMass;
+use bevy::math::Vec2;
+use hexx::Hex;
+use rand::prelude::{SliceRandom, StdRng};
+use rand::{Rng, SeedableRng};
+use std::ops::Range;
+
+const SECTORS_PER_REGION: usize = 7;
+const STAR_CHANCE: f64 = 0.3;
+const ASTEROID_CHANCE: f64 = 0.35;
+const GAS_GIANT_CHANCE: f64 = 0.3;
+const PLANET_COUNT_RANGE: Range<usize> = 1..5;
+const PLANET_ORBIT_DISTANCE_RANGE: Range<f32> = 50.0..120.0;
+const ASTEROID_COUNT_RANGE: Range<usize> = 100..400;
+const ASTEROID_VELOCITY_RANGE: Range<f32> = 0.5..2.0;
+/// Chance for an additional gate between two neighboring sectors within the same region.
+const EXTRA_GATE_CHANCE: f64 = 0.3;
+/// How far gates are placed from the sector center, as a fraction of [constants::SECTOR_SIZE].
+const GATE_DISTANCE_FROM_CENTER: f32 = 0.6;
+/// How far stations are placed from the sector center, as a fraction of [constants::SECTOR_SIZE].
+const STATION_DISTANCE_FROM_CENTER: f32 = 0.3;
+
+/// How many ships with each behavior should be spread across a generated universe.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct ShipCounts {
+    pub trading: u32,
+    pub mining: u32,
+    pub harvesting: u32,
+}
+
+impl ShipCounts {
+    /// Splits `total` into roughly 60% trading, 30% mining and 10% harvesting ships.
+    pub fn split(total: u32) -> Self {
+        let mining = total * 3 / 10;
+        let harvesting = total / 10;
+        Self {
+            trading: total - mining - harvesting,
+            mining,
+            harvesting,
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        self.trading + self.mining + self.harvesting
+    }
+}
+
+pub struct UniverseGenerator {
+    seed: u64,
+    rings: u32,
+    ship_counts: ShipCounts,
+}
+
+impl UniverseGenerator {
+    /// Creates a generator for a hexagonal universe with the given amount of rings around its center sector.
+    pub fn new(seed: u64, rings: u32) -> Self {
+        Self {
+            seed,
+            rings,
+            ship_counts: ShipCounts::default(),
+        }
+    }
+
+    /// Adds stations to every region and spreads `amount` ships across the universe.
+    pub fn with_ships(self, amount: u32) -> Self {
+        self.with_ship_counts(ShipCounts::split(amount))
+    }
+
+    /// Like [Self::with_ships], but with an explicit amount of ships for each behavior.
+    pub fn with_ship_counts(mut self, ship_counts: ShipCounts) -> Self {
+        self.ship_counts = ship_counts;
+        self
+    }
+
+    pub fn generate(&self) -> UniverseSaveData {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let map_layout = MapLayout::default();
+
+        let coordinates: Vec<Hex> = Hex::ZERO.range(self.rings).collect();
+        let mut result = UniverseSaveData {
+            session: SessionSaveData::from(&SessionData::mock_data()),
+            ..Default::default()
+        };
+
+        for coordinate in &coordinates {
+            add_sector(&mut result.sectors, *coordinate, &mut rng, &map_layout);
+        }
+
+        let region_count = (coordinates.len() / SECTORS_PER_REGION).max(1);
+        let region_centers: Vec<Hex> = coordinates
+            .choose_multiple(&mut rng, region_count)
+            .copied()
+            .collect();
+
+        for (from, to) in pick_gate_connections(&coordinates, &region_centers, &mut rng) {
+            let direction = (map_layout.hex_layout.hex_to_world_pos(to)
+                - map_layout.hex_layout.hex_to_world_pos(from))
+            .normalize()
+                * constants::SECTOR_SIZE
+                * GATE_DISTANCE_FROM_CENTER;
+
+            result.gate_pairs.add(
+                LocalHexPosition::new(from, direction),
+                LocalHexPosition::new(to, -direction),
+            );
+        }
+
+        if self.ship_counts.total() > 0 {
+            for center in &region_centers {
+                add_production_chain(&mut result.stations, *center);
+            }
+            add_ships(&mut result.ships, &coordinates, self.ship_counts);
+        }
+
+        result.persistent_id_counters = PersistentIdCountersSaveData::from_current_counters();
+        result
+    }
+}
+
+fn add_sector(
+    sectors: &mut SaveDataCollection<SectorSaveData>,
+    coordinate: Hex,
+    rng: &mut StdRng,
+    map_layout: &MapLayout,
+) {
+    let sector = sectors.add(coordinate);
+
+    if rng.gen_bool(STAR_CHANCE) {
+        sector.with_star(SectorStarSaveData::new());
+
+        let planet_count = rng.gen_range(PLANET_COUNT_RANGE);
+        let mut orbit_radius = 0.0;
+        for i in 0..planet_count {
+            orbit_radius += rng.gen_range(PLANET_ORBIT_DISTANCE_RANGE);
+            let mut planet = SectorPlanetSaveData::new(
+                ConstantOrbitSaveData::new(orbit_radius)
+                    .with_current_rotational_fraction(rng.gen_range(0.0..1.0)),
+            );
+
+            // Gas giants only form in the outer parts of a solar system.
+            if i == planet_count - 1 && planet_count > 1 && rng.gen_bool(GAS_GIANT_CHANCE) {
+                planet = planet
+                    .with_kind(PlanetKindSaveData::GasGiant)
+                    .with_mass(EarthMass::from_earth_mass(300, 0));
+            }
+
+            sector.with_planet(planet);
+        }
+    } else if rng.gen_bool(ASTEROID_CHANCE) {
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let velocity = Vec2::from_angle(angle) * rng.gen_range(ASTEROID_VELOCITY_RANGE);
+        sector.with_asteroids(
+            SectorAsteroidSaveData::new()
+                .with_average_velocity(velocity)
+                .add_random_live_asteroids(
+                    coordinate,
+                    rng.gen_range(ASTEROID_COUNT_RANGE),
+                    map_layout,
+                ),
+        );
+    }
+}
+
+/// Returns the pairs of neighboring sectors which should be connected with a gate.
+///
+/// Builds a spanning tree so every sector is reachable, preferring connections within regions.
+/// Regions thus end up being connected through only as few gates as possible.
+fn pick_gate_connections(
+    coordinates: &[Hex],
+    region_centers: &[Hex],
+    rng: &mut StdRng,
+) -> Vec<(Hex, Hex)> {
+    let region_of = |hex: Hex| {
+        region_centers
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, center)| center.unsigned_distance_to(hex))
+            .map(|(index, _)| index)
+            .unwrap()
+    };
+
+    let mut inner_edges = Vec::new();
+    let mut outer_edges = Vec::new();
+    for (index, from) in coordinates.iter().enumerate() {
+        for to in from.all_neighbors() {
+            // Only look at each edge once, and ignore sectors outside the map
+            let Some(to_index) = coordinates.iter().position(|x| x == &to) else {
+                continue;
+            };
+            if to_index <= index {
+                continue;
+            }
+
+            if region_of(*from) == region_of(to) {
+                inner_edges.push((index, to_index));
+            } else {
+                outer_edges.push((index, to_index));
+            }
+        }
+    }
+
+    inner_edges.shuffle(rng);
+    outer_edges.shuffle(rng);
+
+    let mut parents: Vec<usize> = (0..coordinates.len()).collect();
+    let mut result = Vec::new();
+    let mut unused_inner_edges = Vec::new();
+    for (is_inner, (from, to)) in inner_edges
+        .into_iter()
+        .map(|x| (true, x))
+        .chain(outer_edges.into_iter().map(|x| (false, x)))
+    {
+        let from_root = find_root(&mut parents, from);
+        let to_root = find_root(&mut parents, to);
+        if from_root != to_root {
+            parents[from_root] = to_root;
+            result.push((coordinates[from], coordinates[to]));
+        } else if is_inner {
+            unused_inner_edges.push((from, to));
+        }
+    }
+
+    for (from, to) in unused_inner_edges {
+        if rng.gen_bool(EXTRA_GATE_CHANCE) {
+            result.push((coordinates[from], coordinates[to]));
+        }
+    }
+
+    result
+}
+
+/// Adds the same production chain as in the default scenario, so traders always have something to do.
+fn add_production_chain(stations: &mut SaveDataCollection<StationSaveData>, sector: Hex) {
+    let position = |angle: f32| {
+        LocalHexPosition::new(
+            sector,
+            Vec2::from_angle(angle) * constants::SECTOR_SIZE * STATION_DISTANCE_FROM_CENTER,
+        )
+    };
+
+    stations
+        .add(position(0.0), "Station A".into())
+        .with_production(1, PRODUCTION_MODULE_A_ID, RECIPE_A_ID)
+        .with_buys(vec![DEBUG_ITEM_ID_C])
+        .with_sells(vec![DEBUG_ITEM_ID_A]);
+    stations
+        .add(position(std::f32::consts::FRAC_PI_2), "Station B".into())
+        .with_production(5, PRODUCTION_MODULE_B_ID, RECIPE_B_ID)
+        .with_buys(vec![DEBUG_ITEM_ID_A])
+        .with_sells(vec![DEBUG_ITEM_ID_B]);
+    stations
+        .add(position(std::f32::consts::PI), "Station C".into())
+        .with_production(3, PRODUCTION_MODULE_C_ID, RECIPE_C_ID)
+        .with_buys(vec![DEBUG_ITEM_ID_B])
+        .with_sells(vec![DEBUG_ITEM_ID_C]);
+    stations
+        .add(position(-std::f32::consts::FRAC_PI_2), "Shipyard".into())
+        .with_shipyard(2, SHIPYARD_MODULE_ID)
+        .with_buys(vec![DEBUG_ITEM_ID_A, DEBUG_ITEM_ID_B, DEBUG_ITEM_ID_C]);
+}
+
+/// Spreads ships evenly across all sectors, starting with all trading ships, followed by mining and harvesting ones.
+fn add_ships(
+    ships: &mut SaveDataCollection<ShipSaveData>,
+    coordinates: &[Hex],
+    counts: ShipCounts,
+) {
+    for i in 0..counts.total() {
+        let sector = coordinates[i as usize % coordinates.len()];
+        // Spread out the first idle update so not all ships start thinking within the same tick.
+        let next_idle_update = SimulationTimestamp::from(i as Milliseconds % 1000);
+        let (name, behavior) = if i < counts.trading {
+            (
+                "Trade Ship",
+                ShipBehaviorSaveData::AutoTrade {
+                    next_idle_update,
+                    max_jump_range: constants::DEFAULT_MAX_TRADE_JUMP_RANGE,
+                },
+            )
+        } else if i < counts.trading + counts.mining {
+            (
+                "Mining Ship",
+                ShipBehaviorSaveData::AutoMine {
+                    next_idle_update,
+                    state: AutoMineState::Mining,
+                },
+            )
+        } else {
+            (
+                "Harvesting Ship",
+                ShipBehaviorSaveData::AutoHarvest {
+                    next_idle_update,
+                    state: AutoMineState::Mining,
+                },
+            )
+        };
+
+        ships.add(
+            LocalHexPosition::new(sector, Vec2::ZERO),
+            (i % 360) as f32,
+            format!("{name} {i}"),
+            behavior,
+        );
+    }
+}
+
+fn find_root(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::utils::HashSet;
+
+    /// IDs are handed out globally, so they'll differ between two generated universes even if everything else matches.
+    fn describe_layout(data: &UniverseSaveData) -> Vec<String> {
+        let sectors = data.sectors.data.iter().map(|x| {
+            let features = &x.features;
+            format!(
+                "{:?} star: {} planets: {:?} asteroids: {:?}",
+                x.coordinate,
+                features.star.is_some(),
+                features.planets.as_ref().map(|planets| planets
+                    .iter()
+                    .map(|x| (x.orbit.radius, x.kind.clone()))
+                    .collect::<Vec<_>>()),
+                features
+                    .asteroids
+                    .as_ref()
+                    .map(|x| (x.average_velocity, x.live_asteroids.len())),
+            )
+        });
+        let gates = data
+            .gate_pairs
+            .data
+            .iter()
+            .map(|x| format!("{:?} -> {:?}", x.from_position, x.to_position));
+
+        sectors.chain(gates).collect()
+    }
+
+    #[test]
+    fn same_seed_produces_same_universe() {
+        let a = UniverseGenerator::new(42, 3).generate();
+        let b = UniverseGenerator::new(42, 3).generate();
+        let c = UniverseGenerator::new(43, 3).generate();
+
+        assert_eq!(describe_layout(&a), describe_layout(&b));
+        assert_ne!(describe_layout(&a), describe_layout(&c));
+    }
+
+    #[test]
+    fn every_sector_is_reachable_through_gates() {
+        let data = UniverseGenerator::new(1337, 4).generate();
+        assert_eq!(data.sectors.data.len(), 61);
+
+        let mut reached = HashSet::from([Hex::ZERO]);
+        let mut open = vec![Hex::ZERO];
+        while let Some(current) = open.pop() {
+            for gate in &data.gate_pairs.data {
+                let next = if gate.from_position.sector == current {
+                    gate.to_position.sector
+                } else if gate.to_position.sector == current {
+                    gate.from_position.sector
+                } else {
+                    continue;
+                };
+
+                if reached.insert(next) {
+                    open.push(next);
+                }
+            }
+        }
+
+        assert_eq!(reached.len(), data.sectors.data.len());
+    }
+}
@@ -1,7 +1,7 @@
 use bevy::prelude::Query;
 
 use crate::components::{Gate, InSector, Sector};
-use crate::persistence::data::v1::*;
+use crate::persistence::data::latest::*;
 use crate::persistence::local_hex_position::LocalHexPosition;
 use crate::persistence::ComponentWithPersistentId;
 use crate::simulation::transform::simulation_transform::SimulationTransform;
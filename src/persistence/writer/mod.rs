@@ -4,6 +4,7 @@ pub mod gates;
 pub mod inventory;
 pub mod save_data_collection;
 pub mod sectors;
+pub mod session;
 pub mod ships;
 pub mod stations;
 pub mod tasks;
@@ -1,4 +1,4 @@
-use crate::persistence::data::v1::SaveDataCollection;
+use crate::persistence::data::latest::SaveDataCollection;
 
 impl<T, I> From<I> for SaveDataCollection<T>
 where
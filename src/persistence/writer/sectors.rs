@@ -1,12 +1,14 @@
 use crate::components::{
-    Asteroid, RespawningAsteroidData, Sector, SectorAsteroidComponent, SectorStarComponent, Star,
+    Asteroid, ConstantOrbit, GasGiant, Planet, RespawningAsteroidData, Sector,
+    SectorAsteroidComponent, SectorPlanets, SectorStarComponent, Star,
 };
-use crate::persistence::data::v1::*;
+use crate::persistence::data::latest::*;
 use crate::persistence::ComponentWithPersistentId;
 use crate::simulation::physics::ConstantVelocity;
 use crate::simulation::transform::simulation_transform::SimulationTransform;
+use bevy::core::Name;
 use bevy::ecs::query::QueryData;
-use bevy::prelude::Query;
+use bevy::prelude::{Has, Query};
 
 impl AsteroidSaveData {
     pub fn from(
@@ -64,11 +66,33 @@ impl SectorAsteroidSaveData {
     }
 }
 
+impl SectorPlanetSaveData {
+    pub fn from(
+        (planet, name, orbit, is_gas_giant): (&Planet, &Name, &ConstantOrbit, bool),
+    ) -> Self {
+        Self {
+            id: planet.id,
+            kind: if is_gas_giant {
+                PlanetKindSaveData::GasGiant
+            } else {
+                PlanetKindSaveData::Terrestrial
+            },
+            name: name.to_string(),
+            mass: planet.mass,
+            orbit: ConstantOrbitSaveData {
+                current_rotational_fraction: orbit.rotational_fraction,
+                radius: orbit.radius,
+            },
+        }
+    }
+}
+
 impl SectorFeatureSaveData {
     pub fn from(
         data: SectorSaveDataQueryItem,
         asteroid_query: &Query<(&Asteroid, &SimulationTransform, &ConstantVelocity)>,
         star_query: &Query<&Star>,
+        planet_query: &Query<(&Planet, &Name, &ConstantOrbit, Has<GasGiant>)>,
     ) -> Self {
         SectorFeatureSaveData {
             star: data.star.map(|x| SectorStarSaveData {
@@ -77,7 +101,16 @@ impl SectorFeatureSaveData {
             asteroids: data
                 .asteroids
                 .map(|x| SectorAsteroidSaveData::from(x, asteroid_query)),
-            planets: todo!(),
+            planets: data.planets.map(|x| {
+                let mut planets: Vec<SectorPlanetSaveData> = x
+                    .planets
+                    .iter()
+                    .map(|x| planet_query.get(x.into()).unwrap())
+                    .map(SectorPlanetSaveData::from)
+                    .collect();
+                planets.sort_by_key(|x| x.id);
+                planets
+            }),
         }
     }
 }
@@ -87,6 +120,7 @@ pub struct SectorSaveDataQuery {
     sector: &'static Sector,
     star: Option<&'static SectorStarComponent>,
     asteroids: Option<&'static SectorAsteroidComponent>,
+    planets: Option<&'static SectorPlanets>,
 }
 
 impl SectorSaveData {
@@ -94,10 +128,11 @@ impl SectorSaveData {
         data: SectorSaveDataQueryItem,
         asteroid_query: &Query<(&Asteroid, &SimulationTransform, &ConstantVelocity)>,
         star_query: &Query<&Star>,
+        planet_query: &Query<(&Planet, &Name, &ConstantOrbit, Has<GasGiant>)>,
     ) -> Self {
         Self {
             coordinate: data.sector.coordinate,
-            features: SectorFeatureSaveData::from(data, asteroid_query, star_query),
+            features: SectorFeatureSaveData::from(data, asteroid_query, star_query, planet_query),
         }
     }
 }
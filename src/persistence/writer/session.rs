@@ -0,0 +1,71 @@
+use crate::persistence::data::latest::*;
+use crate::session_data::{Faction, SessionData, ShipConfiguration};
+use crate::simulation::prelude::SimulationTime;
+
+impl From<&SessionData> for SessionSaveData {
+    fn from(value: &SessionData) -> Self {
+        let mut ship_configurations: Vec<ShipConfigurationSaveData> = value
+            .ship_configurations
+            .values()
+            .map(ShipConfigurationSaveData::from)
+            .collect();
+
+        let mut factions: Vec<FactionSaveData> =
+            value.factions.values().map(FactionSaveData::from).collect();
+
+        // HashMap iteration order is random, but save files shouldn't be
+        ship_configurations.sort_by_key(|x| x.id);
+        factions.sort_by_key(|x| x.id);
+
+        Self {
+            ship_configurations,
+            factions,
+            relations: value
+                .relations
+                .sorted()
+                .into_iter()
+                .map(|(faction, towards, relation)| RelationSaveData {
+                    faction,
+                    towards,
+                    favor: relation.favor,
+                    perception: relation.perception,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&Faction> for FactionSaveData {
+    fn from(value: &Faction) -> Self {
+        Self {
+            id: value.id,
+            name: value.name.clone(),
+            credits: value.wallet.credits(),
+            required_favor: value.required_favor,
+        }
+    }
+}
+
+impl From<&ShipConfiguration> for ShipConfigurationSaveData {
+    fn from(value: &ShipConfiguration) -> Self {
+        Self {
+            id: value.id,
+            name: value.name.clone(),
+            duration: value.duration,
+            materials: value
+                .materials
+                .iter()
+                .map(|x| (x.item_id, x.amount))
+                .collect(),
+        }
+    }
+}
+
+impl From<&SimulationTime> for SimulationTimeSaveData {
+    fn from(value: &SimulationTime) -> Self {
+        Self {
+            total: value.total(),
+            tick: value.tick(),
+        }
+    }
+}
@@ -1,29 +1,61 @@
-use crate::components::{InSector, Inventory, Sector, Ship};
-use crate::persistence::data::v1::*;
+use crate::components::{Gate, InSector, Inventory, IsDocked, Owner, Sector, Ship, Wallet};
+use crate::persistence::data::latest::*;
 use crate::persistence::local_hex_position::LocalHexPosition;
+use crate::persistence::writer::tasks::ActiveTaskQueryItem;
 use crate::persistence::{AllEntityIdMaps, ComponentWithPersistentId};
 use crate::simulation::physics::ShipVelocity;
-use crate::simulation::ship_ai::{AutoMineBehavior, AutoTradeBehavior, TaskQueue};
+use crate::simulation::ship_ai::{
+    AutoHarvestBehavior, AutoMineBehavior, AutoTradeBehavior, TaskQueue,
+};
 use crate::simulation::transform::simulation_transform::SimulationTransform;
 use bevy::core::Name;
 use bevy::prelude::Query;
 
 impl ShipSaveData {
+    #[allow(clippy::type_complexity)]
     pub fn from(
-        (ship, name, in_sector, transform, task_queue, velocity, inventory, auto_trade, auto_mine): (
+        (
+            ship,
+            name,
+            in_sector,
+            transform,
+            task_queue,
+            velocity,
+            inventory,
+            auto_trade,
+            auto_mine,
+            auto_harvest,
+            active_task,
+            is_docked,
+            owner,
+            wallet,
+        ): (
             &Ship,
             &Name,
-            &InSector,
+            Option<&InSector>,
             &SimulationTransform,
             &TaskQueue,
             &ShipVelocity,
             &Inventory,
             Option<&AutoTradeBehavior>,
             Option<&AutoMineBehavior>,
+            Option<&AutoHarvestBehavior>,
+            ActiveTaskQueryItem,
+            Option<&IsDocked>,
+            Option<&Owner>,
+            Option<&Wallet>,
         ),
         sectors: &Query<&Sector>,
+        gates: &Query<(&Gate, &InSector, &SimulationTransform)>,
         all_entity_id_maps: &AllEntityIdMaps,
     ) -> Self {
+        let in_sector = in_sector.unwrap_or_else(|| {
+            let gate = active_task
+                .use_gate
+                .expect("Ships without sector should be traversing a gate!");
+            gates.get(gate.enter_gate.into()).unwrap().1
+        });
+
         Self {
             id: ship.id(),
             name: name.to_string(),
@@ -31,13 +63,23 @@ impl ShipSaveData {
             forward_velocity: velocity.forward,
             rotation_degrees: transform.rotation.as_degrees(),
             angular_velocity: velocity.angular,
-            behavior: ShipBehaviorSaveData::from(auto_trade, auto_mine),
+            behavior: ShipBehaviorSaveData::from(auto_trade, auto_mine, auto_harvest),
             task_queue: task_queue
                 .queue
                 .iter()
-                .map(|x| TaskSaveData::from(x, all_entity_id_maps))
+                .enumerate()
+                .map(|(index, task)| {
+                    if index == 0 {
+                        TaskSaveData::from_active(task, &active_task, all_entity_id_maps)
+                    } else {
+                        TaskSaveData::from(task, all_entity_id_maps)
+                    }
+                })
                 .collect(),
             inventory: InventorySaveData::from(inventory),
+            docked_at: is_docked.map(|x| all_entity_id_maps.get_typed_id_unchecked(&x.at)),
+            owner: owner.map(|x| x.faction),
+            credits: wallet.map_or(0, Wallet::credits),
         }
     }
 }
@@ -46,15 +88,24 @@ impl ShipBehaviorSaveData {
     pub fn from(
         auto_trade: Option<&AutoTradeBehavior>,
         auto_mine: Option<&AutoMineBehavior>,
+        auto_harvest: Option<&AutoHarvestBehavior>,
     ) -> Self {
         if let Some(auto_trade) = auto_trade {
             return ShipBehaviorSaveData::AutoTrade {
                 next_idle_update: auto_trade.next_idle_update,
+                max_jump_range: auto_trade.max_jump_range,
             };
         }
         if let Some(auto_mine) = auto_mine {
-            return ShipBehaviorSaveData::AutoTrade {
+            return ShipBehaviorSaveData::AutoMine {
                 next_idle_update: auto_mine.next_idle_update,
+                state: auto_mine.state,
+            };
+        }
+        if let Some(auto_harvest) = auto_harvest {
+            return ShipBehaviorSaveData::AutoHarvest {
+                next_idle_update: auto_harvest.next_idle_update,
+                state: auto_harvest.state,
             };
         }
 
@@ -1,11 +1,11 @@
 use crate::components::{
-    BuyOrderData, BuyOrders, InSector, Inventory, Sector, SellOrderData, SellOrders, Station,
-    TradeOrder,
+    BuyOrderData, BuyOrders, InSector, InteractionQueue, Inventory, Owner, Sector, SellOrderData,
+    SellOrders, Station, TradeOrder, Wallet,
 };
 use crate::game_data::{ItemId, ProductionModuleId, ShipyardModuleId};
-use crate::persistence::data::v1::*;
+use crate::persistence::data::latest::*;
 use crate::persistence::local_hex_position::LocalHexPosition;
-use crate::persistence::ComponentWithPersistentId;
+use crate::persistence::{AllEntityIdMaps, ComponentWithPersistentId};
 use crate::simulation::production::{
     OngoingShipConstructionOrder, ProductionComponent, ProductionModule, ShipyardComponent,
     ShipyardModule,
@@ -132,6 +132,9 @@ impl StationSaveData {
             shipyard,
             buy_orders,
             sell_orders,
+            interaction_queue,
+            owner,
+            wallet,
         ): (
             &Station,
             &Name,
@@ -142,8 +145,12 @@ impl StationSaveData {
             Option<&ShipyardComponent>,
             Option<&BuyOrders>,
             Option<&SellOrders>,
+            &InteractionQueue,
+            Option<&Owner>,
+            &Wallet,
         ),
         sectors: &Query<&Sector>,
+        all_entity_id_maps: &AllEntityIdMaps,
     ) -> Self {
         Self {
             id: station.id(),
@@ -154,6 +161,25 @@ impl StationSaveData {
             sell_orders: sell_orders.map(SerializedSellOrder::from),
             production_modules: production.map(ProductionSaveData::from),
             shipyard_modules: shipyard.map(ShipyardSaveData::from),
+            interaction_queue: InteractionQueueSaveData::from(
+                interaction_queue,
+                all_entity_id_maps,
+            ),
+            owner: owner.map(|x| x.faction),
+            credits: wallet.credits(),
+        }
+    }
+}
+
+impl InteractionQueueSaveData {
+    pub fn from(queue: &InteractionQueue, all_entity_id_maps: &AllEntityIdMaps) -> Self {
+        Self {
+            currently_interacting: queue.currently_interacting(),
+            waiting: queue
+                .waiting_queue()
+                .iter()
+                .map(|x| all_entity_id_maps.ships.entity_to_id()[x])
+                .collect(),
         }
     }
 }
@@ -1,14 +1,35 @@
-use crate::persistence::data::v1::*;
+use crate::persistence::data::latest::*;
 use crate::persistence::AllEntityIdMaps;
-use crate::simulation::ship_ai::TaskInsideQueue;
+use crate::simulation::prelude::SimulationTimestamp;
+use crate::simulation::ship_ai::{
+    ExchangeWares, GateTraversalState, HarvestGas, MineAsteroid, TaskInsideQueue, Undock, UseGate,
+};
 use crate::utils::ExchangeWareData;
+use bevy::ecs::query::QueryData;
+
+/// The task components of a ship which store progress.
+/// Only the component of the first task inside its [`crate::simulation::ship_ai::TaskQueue`] will exist.
+#[derive(QueryData)]
+pub struct ActiveTaskQuery {
+    pub exchange_wares: Option<&'static ExchangeWares>,
+    pub use_gate: Option<&'static UseGate>,
+    pub mine_asteroid: Option<&'static MineAsteroid>,
+    pub harvest_gas: Option<&'static HarvestGas>,
+    pub undock: Option<&'static Undock>,
+}
 
 impl TaskSaveData {
     pub fn from(task: &TaskInsideQueue, all_entity_id_maps: &AllEntityIdMaps) -> Self {
         match task {
-            TaskInsideQueue::ExchangeWares { target, data } => Self::ExchangeWares {
+            TaskInsideQueue::ExchangeWares {
+                target,
+                data,
+                deadline,
+            } => Self::ExchangeWares {
                 target: all_entity_id_maps.get_typed_id_unchecked(target),
                 data: data.into(),
+                finishes_at: SimulationTimestamp::MAX,
+                deadline: *deadline,
             },
             TaskInsideQueue::MoveToEntity {
                 target,
@@ -25,26 +46,86 @@ impl TaskSaveData {
             } => Self::UseGate {
                 enter_gate: all_entity_id_maps.gates.entity_to_id()[enter_gate],
                 exit_sector: all_entity_id_maps.sectors.entity_to_id()[exit_sector],
+                progress: 0.0,
+                traversal_state: GateTraversalStateSaveData::JustCreated,
             },
             TaskInsideQueue::MineAsteroid { target, reserved } => Self::MineAsteroid {
                 target: all_entity_id_maps.asteroids.entity_to_id()[target],
                 reserved: *reserved,
+                progress: None,
             },
             TaskInsideQueue::HarvestGas { target } => Self::HarvestGas {
                 target: all_entity_id_maps.planets.entity_to_id()[target],
+                next_update: None,
+            },
+            TaskInsideQueue::DockAtEntity { target } => Self::DockAtEntity {
+                target: all_entity_id_maps.get_typed_id_unchecked(target),
             },
-            TaskInsideQueue::DockAtEntity { .. } => {
-                todo!()
+            TaskInsideQueue::AwaitingSignal => Self::AwaitingSignal,
+            TaskInsideQueue::RequestAccess { target } => Self::RequestAccess {
+                target: all_entity_id_maps.get_typed_id_unchecked(target),
+            },
+            TaskInsideQueue::Undock => Self::Undock {
+                start_position: None,
+            },
+        }
+    }
+
+    /// Same as [Self::from], but also includes the progress stored inside the task's component.
+    pub fn from_active(
+        task: &TaskInsideQueue,
+        components: &ActiveTaskQueryItem,
+        all_entity_id_maps: &AllEntityIdMaps,
+    ) -> Self {
+        let mut result = Self::from(task, all_entity_id_maps);
+        match &mut result {
+            Self::ExchangeWares { finishes_at, .. } => {
+                if let Some(task) = components.exchange_wares {
+                    *finishes_at = task.finishes_at;
+                }
+            }
+            Self::UseGate {
+                progress,
+                traversal_state,
+                ..
+            } => {
+                if let Some(task) = components.use_gate {
+                    *progress = task.progress;
+                    *traversal_state = GateTraversalStateSaveData::from(&task.traversal_state);
+                }
+            }
+            Self::MineAsteroid { progress, .. } => {
+                *progress = components
+                    .mine_asteroid
+                    .map(|task| MineAsteroidProgressSaveData {
+                        next_update: task.next_update,
+                        reserved_ore_amount: task.reserved_ore_amount,
+                    });
             }
-            TaskInsideQueue::AwaitingSignal => {
-                todo!()
+            Self::HarvestGas { next_update, .. } => {
+                *next_update = components.harvest_gas.map(|task| task.next_update);
             }
-            TaskInsideQueue::RequestAccess { .. } => {
-                todo!()
+            Self::Undock { start_position } => {
+                *start_position = components.undock.and_then(|task| task.start_position);
             }
-            TaskInsideQueue::Undock => {
-                todo!()
+            Self::AwaitingSignal
+            | Self::RequestAccess { .. }
+            | Self::DockAtEntity { .. }
+            | Self::MoveToEntity { .. } => {}
+        }
+
+        result
+    }
+}
+
+impl From<&GateTraversalState> for GateTraversalStateSaveData {
+    fn from(value: &GateTraversalState) -> Self {
+        match value {
+            GateTraversalState::JustCreated => Self::JustCreated,
+            GateTraversalState::BlendingIntoMotion { origin } => {
+                Self::BlendingIntoMotion { origin: *origin }
             }
+            GateTraversalState::TraversingLine => Self::TraversingLine,
         }
     }
 }
@@ -52,8 +133,8 @@ impl TaskSaveData {
 impl From<&ExchangeWareData> for ExchangeWareSaveData {
     fn from(value: &ExchangeWareData) -> Self {
         match value {
-            ExchangeWareData::Buy(item, amount) => Self::Buy(*item, *amount),
-            ExchangeWareData::Sell(item, amount) => Self::Sell(*item, *amount),
+            ExchangeWareData::Buy(item, amount, price) => Self::Buy(*item, *amount, *price),
+            ExchangeWareData::Sell(item, amount, price) => Self::Sell(*item, *amount, *price),
         }
     }
 }
@@ -0,0 +1,15 @@
+use crate::components::Wallet;
+
+pub type FactionId = u32;
+
+/// A group of ships and stations which are marked with the same [crate::components::Owner].
+///
+/// Owned ships don't carry any credits themselves, everything they buy or sell is paid for through this wallet.
+/// Stations always keep a wallet of their own.
+pub struct Faction {
+    pub id: FactionId,
+    pub name: String,
+    pub wallet: Wallet,
+    /// The favor other factions need with this one before its stations are willing to trade with them.
+    pub required_favor: i64,
+}
@@ -0,0 +1,123 @@
+use crate::session_data::FactionId;
+use bevy::utils::HashMap;
+
+/// What one faction thinks about another one, see `docs/design/currency_favor.md` for the idea behind it.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Relation {
+    /// Earned by selling goods to the faction, spent by buying goods from it.
+    pub favor: i64,
+    /// The highest favor which has ever been reached, unless something bad happened. This is the public standing.
+    pub perception: i64,
+}
+
+impl Relation {
+    fn earn(&mut self, amount: i64) {
+        self.favor += amount;
+        self.perception = self.perception.max(self.favor);
+    }
+
+    fn spend(&mut self, amount: i64) {
+        self.favor -= amount;
+    }
+
+    /// Perception suffers more the more favor has been spent since it was reached:
+    /// Buying a lot and then misbehaving looks a lot worse than misbehaving while the faction still owes you.
+    fn punish(&mut self, amount: i64) {
+        let spent_favor = self.perception - self.favor;
+        self.favor -= amount;
+        self.perception -= amount + spent_favor / 2;
+    }
+}
+
+/// The ledger of [Relation]s between all factions which ever had anything to do with each other.
+/// Relations aren't symmetric, selling goods to a faction doesn't change what the seller thinks about it.
+#[derive(Default)]
+pub struct FactionRelations {
+    relations: HashMap<(FactionId, FactionId), Relation>,
+}
+
+impl FactionRelations {
+    /// What `faction` thinks about `towards`.
+    pub fn get(&self, faction: FactionId, towards: FactionId) -> Relation {
+        self.relations
+            .get(&(faction, towards))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn set(&mut self, faction: FactionId, towards: FactionId, relation: Relation) {
+        self.relations.insert((faction, towards), relation);
+    }
+
+    /// `towards` sold goods worth `amount` to `faction`.
+    pub fn earn(&mut self, faction: FactionId, towards: FactionId, amount: i64) {
+        self.entry(faction, towards).earn(amount);
+    }
+
+    /// `towards` bought goods worth `amount` from `faction`.
+    pub fn spend(&mut self, faction: FactionId, towards: FactionId, amount: i64) {
+        self.entry(faction, towards).spend(amount);
+    }
+
+    /// `towards` did something bad to `faction`, like missing a delivery.
+    pub fn punish(&mut self, faction: FactionId, towards: FactionId, amount: i64) {
+        self.entry(faction, towards).punish(amount);
+    }
+
+    /// All relations, sorted by faction and the one it thinks about.
+    pub fn sorted(&self) -> Vec<(FactionId, FactionId, Relation)> {
+        let mut result: Vec<_> = self
+            .relations
+            .iter()
+            .map(|((faction, towards), relation)| (*faction, *towards, *relation))
+            .collect();
+        result.sort_by_key(|(faction, towards, _)| (*faction, *towards));
+        result
+    }
+
+    fn entry(&mut self, faction: FactionId, towards: FactionId) -> &mut Relation {
+        self.relations.entry((faction, towards)).or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SELLER: FactionId = 1;
+    const TRADER: FactionId = 2;
+
+    #[test]
+    fn perception_remembers_the_highest_favor() {
+        let mut relations = FactionRelations::default();
+        relations.earn(SELLER, TRADER, 100);
+        relations.spend(SELLER, TRADER, 80);
+
+        assert_eq!(
+            relations.get(SELLER, TRADER),
+            Relation {
+                favor: 20,
+                perception: 100
+            }
+        );
+        assert_eq!(relations.get(TRADER, SELLER), Relation::default());
+    }
+
+    #[test]
+    fn punishments_hurt_perception_more_after_spending_favor() {
+        let mut relations = FactionRelations::default();
+        relations.earn(SELLER, TRADER, 100);
+        relations.punish(SELLER, TRADER, 10);
+        let without_spending = relations.get(SELLER, TRADER);
+
+        relations.earn(TRADER, SELLER, 100);
+        relations.spend(TRADER, SELLER, 80);
+        relations.punish(TRADER, SELLER, 10);
+        let after_spending = relations.get(TRADER, SELLER);
+
+        assert_eq!(without_spending.perception, 90);
+        assert_eq!(after_spending.favor, 10);
+        assert_eq!(after_spending.perception, 50);
+    }
+}
@@ -1,15 +1,23 @@
 use bevy::prelude::Resource;
 use bevy::utils::HashMap;
 
+mod faction;
+mod faction_relations;
 mod ship_configuration;
 
+use crate::components::Owner;
 use crate::game_data::*;
+use crate::utils::TradeIntent;
+pub use faction::*;
+pub use faction_relations::*;
 pub use ship_configuration::*;
 
 /// Data that's dynamically created and indexed whilst playing and needs to be persisted in between sessions.
 #[derive(Resource, Default)]
 pub struct SessionData {
     pub ship_configurations: HashMap<ShipConfigId, ShipConfiguration>,
+    pub factions: HashMap<FactionId, Faction>,
+    pub relations: FactionRelations,
 }
 
 impl SessionData {
@@ -37,6 +45,32 @@ impl SessionData {
                     ],
                 },
             )]),
+            factions: HashMap::new(),
+            relations: FactionRelations::default(),
         }
     }
+
+    /// Whether a station owned by `partner` is willing to trade with a ship owned by `trader`, which intends to
+    /// buy or sell there. Buying spends favor and requires enough of it, while selling earns favor and is always
+    /// welcome, so factions can work their way back up.
+    /// Favor is only tracked between factions, so independent entities trade with anyone.
+    pub fn is_willing_to_trade(
+        &self,
+        partner: Option<&Owner>,
+        trader: Option<&Owner>,
+        intent: TradeIntent,
+    ) -> bool {
+        if intent == TradeIntent::Sell {
+            return true;
+        }
+        let (Some(partner), Some(trader)) = (partner, trader) else {
+            return true;
+        };
+        let Some(faction) = self.factions.get(&partner.faction) else {
+            return true;
+        };
+
+        partner == trader
+            || self.relations.get(partner.faction, trader.faction).favor >= faction.required_favor
+    }
 }
@@ -1,6 +1,7 @@
 use crate::components::{
     Asteroid, InSector, RespawningAsteroidData, Sector, SectorAsteroidComponent,
 };
+use crate::constants;
 use crate::map_layout::MapLayout;
 use crate::simulation::asteroids::fading::FadingAsteroidsOut;
 use crate::simulation::asteroids::respawning;
@@ -27,7 +28,12 @@ pub fn on_asteroid_was_fully_mined(
     )>,
     mut sectors_with_asteroids: Query<(&Sector, &mut SectorAsteroidComponent)>,
     map_layout: Res<MapLayout>,
+    simulation_time: Res<SimulationTime>,
 ) {
+    let despawn_at = simulation_time
+        .now()
+        .add_milliseconds(constants::ASTEROID_FADE_OUT_TIME);
+
     for event in events.read() {
         let Ok((asteroid_sector, asteroid, velocity, transform)) =
             asteroids.get(event.asteroid.into())
@@ -62,6 +68,7 @@ pub fn on_asteroid_was_fully_mined(
                 asteroid,
                 velocity,
                 local_respawn_position,
+                despawn_at,
             );
         }
     }
@@ -76,6 +83,7 @@ pub fn make_asteroids_disappear_when_they_leave_sector(
     simulation_time: Res<SimulationTime>,
 ) {
     let now = simulation_time.now();
+    let despawn_at = now.add_milliseconds(constants::ASTEROID_FADE_OUT_TIME);
 
     for (sector, mut asteroid_component) in sector_asteroids.iter_mut() {
         while let Some(next) = asteroid_component.asteroids.first() {
@@ -99,6 +107,7 @@ pub fn make_asteroids_disappear_when_they_leave_sector(
                 asteroid,
                 velocity,
                 local_respawn_position,
+                despawn_at,
             );
         }
     }
@@ -111,6 +120,7 @@ fn initiate_despawn_animation(
     asteroid: &Asteroid,
     velocity: &ConstantVelocity,
     local_respawn_position: Vec2,
+    despawn_at: SimulationTimestamp,
 ) {
     feature
         .asteroid_respawns
@@ -120,5 +130,7 @@ fn initiate_despawn_animation(
             local_respawn_position,
         )));
 
-    fading_asteroids.asteroids.insert(asteroid_entity.entity);
+    fading_asteroids
+        .asteroids
+        .insert(asteroid_entity.entity, despawn_at);
 }
@@ -1,44 +1,62 @@
 use crate::components::Asteroid;
+use crate::constants;
 use crate::persistence::AsteroidIdMap;
+use crate::simulation::prelude::{SimulationTime, SimulationTimestamp};
 use crate::utils::AsteroidEntity;
 use bevy::color::Alpha;
 use bevy::prelude::{Commands, Query, Res, ResMut, Resource, Sprite, Time, With};
-use bevy::utils::HashSet;
+use bevy::utils::{HashMap, HashSet};
 
 #[derive(Resource, Default)]
 pub struct FadingAsteroidsIn {
     pub asteroids: HashSet<AsteroidEntity>,
 }
 
+/// Asteroids which are about to disappear, alongside the [SimulationTimestamp] at which they'll be despawned.
 #[derive(Resource, Default)]
 pub struct FadingAsteroidsOut {
-    pub asteroids: HashSet<AsteroidEntity>,
+    pub asteroids: HashMap<AsteroidEntity, SimulationTimestamp>,
 }
 
-/// Fades asteroid alpha values to 0 before finally despawning them.
-pub fn fade_asteroids_out_and_despawn_entities(
-    time: Res<Time>,
+/// Despawns asteroids once their fade out time has passed.
+/// This is part of the simulation, so it must not depend on whether anything is rendered.
+pub fn despawn_faded_out_asteroids(
+    simulation_time: Res<SimulationTime>,
     mut commands: Commands,
     mut fading_asteroids: ResMut<FadingAsteroidsOut>,
-    mut asteroid_query: Query<&mut Sprite, With<Asteroid>>,
     mut asteroid_id_map: ResMut<AsteroidIdMap>,
 ) {
-    let mut removals = HashSet::new();
-
-    for entity in &fading_asteroids.asteroids {
-        let mut sprite = asteroid_query.get_mut(entity.into()).unwrap();
+    let now = simulation_time.now();
 
-        let new_alpha = sprite.color.alpha() - time.delta_seconds();
-        if new_alpha > 0.0 {
-            sprite.color.set_alpha(new_alpha);
-        } else {
-            removals.insert(*entity);
-            asteroid_id_map.remove_by_entity(entity);
-            commands.entity(entity.into()).despawn();
+    fading_asteroids.asteroids.retain(|entity, despawn_at| {
+        if now.has_not_passed(*despawn_at) {
+            return true;
         }
-    }
 
-    fading_asteroids.asteroids.retain(|x| !removals.contains(x));
+        asteroid_id_map.remove_by_entity(entity);
+        commands.entity(entity.into()).despawn();
+        false
+    });
+}
+
+/// Fades asteroid alpha values towards 0 until they are despawned.
+pub fn fade_asteroids_out(
+    simulation_time: Res<SimulationTime>,
+    fading_asteroids: Res<FadingAsteroidsOut>,
+    mut asteroid_query: Query<&mut Sprite, With<Asteroid>>,
+) {
+    let now = simulation_time.now();
+
+    for (entity, despawn_at) in &fading_asteroids.asteroids {
+        let Ok(mut sprite) = asteroid_query.get_mut(entity.into()) else {
+            // Without sprites there's nothing to fade out
+            continue;
+        };
+
+        let remaining_millis = now.remaining_time(*despawn_at).as_millis() as f32;
+        let new_alpha = remaining_millis / constants::ASTEROID_FADE_OUT_TIME as f32;
+        sprite.color.set_alpha(new_alpha.min(sprite.color.alpha()));
+    }
 }
 
 /// Fades asteroids alpha values to 1
@@ -50,7 +68,11 @@ pub fn fade_asteroids_in(
     let mut removals = HashSet::new();
 
     for entity in &fading_asteroids.asteroids {
-        let mut sprite = asteroid_query.get_mut(entity.into()).unwrap();
+        let Ok(mut sprite) = asteroid_query.get_mut(entity.into()) else {
+            // Without sprites there's nothing to fade in
+            removals.insert(*entity);
+            continue;
+        };
 
         let new_alpha = sprite.color.alpha() + time.delta_seconds();
         if new_alpha < 1.0 {
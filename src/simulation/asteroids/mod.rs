@@ -5,5 +5,6 @@ mod plugin;
 mod respawning;
 
 pub use {
+    despawning::make_asteroids_disappear_when_they_leave_sector,
     despawning::AsteroidWasFullyMinedEvent, plugin::AsteroidPlugin, respawning::respawn_asteroids,
 };
@@ -1,9 +1,9 @@
 use crate::simulation::asteroids::despawning::AsteroidWasFullyMinedEvent;
 use crate::simulation::asteroids::fading::{FadingAsteroidsIn, FadingAsteroidsOut};
 use crate::simulation::asteroids::{despawning, fading, respawning};
-use crate::states::SimulationState;
+use crate::states::{ApplicationState, SimulationState};
 use bevy::app::{App, Plugin};
-use bevy::prelude::{in_state, on_event, FixedUpdate, IntoSystemConfigs, Update};
+use bevy::prelude::{in_state, on_event, FixedUpdate, IntoSystemConfigs, OnEnter, ResMut, Update};
 
 /// ### General Idea
 /// Every Sector may have asteroids inside it, defined by its [SectorAsteroidData].
@@ -20,6 +20,7 @@ impl Plugin for AsteroidPlugin {
         app.init_resource::<FadingAsteroidsOut>()
             .init_resource::<FadingAsteroidsIn>()
             .add_event::<AsteroidWasFullyMinedEvent>()
+            .add_systems(OnEnter(ApplicationState::Loading), reset_fading_asteroids)
             .add_systems(
                 FixedUpdate,
                 (
@@ -27,17 +28,23 @@ impl Plugin for AsteroidPlugin {
                     despawning::on_asteroid_was_fully_mined
                         .run_if(on_event::<AsteroidWasFullyMinedEvent>()),
                     respawning::respawn_asteroids,
+                    fading::despawn_faded_out_asteroids,
                     //draw_asteroid_debug_gizmos,
                 )
                     .run_if(in_state(SimulationState::Running)),
             )
             .add_systems(
                 Update,
-                (
-                    fading::fade_asteroids_out_and_despawn_entities,
-                    fading::fade_asteroids_in,
-                )
+                (fading::fade_asteroids_out, fading::fade_asteroids_in)
                     .run_if(in_state(SimulationState::Running)),
             );
     }
 }
+
+fn reset_fading_asteroids(
+    mut fading_in: ResMut<FadingAsteroidsIn>,
+    mut fading_out: ResMut<FadingAsteroidsOut>,
+) {
+    fading_in.asteroids.clear();
+    fading_out.asteroids.clear();
+}
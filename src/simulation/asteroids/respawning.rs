@@ -12,7 +12,7 @@ use bevy::prelude::{Commands, Entity, Query, Res, ResMut};
 pub fn respawn_asteroids(
     mut commands: Commands,
     mut asteroid_id_map: ResMut<AsteroidIdMap>,
-    sprites: Res<SpriteHandles>,
+    sprites: Option<Res<SpriteHandles>>,
     mut fading_asteroids: ResMut<FadingAsteroidsIn>,
     mut sectors_with_asteroids: Query<(Entity, &Sector, &mut SectorAsteroidComponent)>,
     simulation_time: Res<SimulationTime>,
@@ -38,7 +38,8 @@ pub fn respawn_asteroids(
             let asteroid_entity = spawn_helpers::spawn_asteroid(
                 &mut commands,
                 &mut asteroid_id_map,
-                &sprites,
+                sprites.as_deref(),
+                next.id,
                 "Asteroid".to_string(),
                 next.local_respawn_position + sector.world_pos,
                 &mut asteroid_component,
@@ -0,0 +1,179 @@
+//! Running the same universe twice should always end up in the exact same state, so bugs can be reproduced
+//! and simulation changes can be verified against a known outcome.
+//!
+//! Any randomness within the simulation has to be drawn from [SimulationRng], and systems making decisions
+//! must not depend on query or HashMap iteration order. Bevy's multithreaded executor may still run
+//! conflicting systems in any order, which is why [DeterministicSimulationPlugin] runs everything on one thread.
+
+use crate::simulation::prelude::Milliseconds;
+use bevy::app::{
+    App, FixedFirst, FixedLast, FixedPostUpdate, FixedPreUpdate, FixedUpdate, Plugin, Update,
+};
+use bevy::ecs::schedule::{ExecutorKind, ScheduleLabel};
+use bevy::prelude::Resource;
+use rand::prelude::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// The seed used for [SimulationRng] unless a different one was requested.
+pub const DEFAULT_SIMULATION_SEED: u64 = 0;
+
+/// Ships which couldn't find anything to do retry within up to this fraction of their delay earlier or later,
+/// so they don't all end up thinking within the same tick.
+const DELAY_JITTER_FRACTION: f64 = 0.25;
+
+/// The only source of randomness which should be used inside the simulation.
+#[derive(Resource)]
+pub struct SimulationRng(StdRng);
+
+impl SimulationRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+
+    /// Randomly shortens or extends the given delay by up to [DELAY_JITTER_FRACTION].
+    pub fn jitter(&mut self, delay: Milliseconds) -> Milliseconds {
+        let max_jitter = (delay as f64 * DELAY_JITTER_FRACTION) as Milliseconds;
+        self.0.gen_range(delay - max_jitter..=delay + max_jitter)
+    }
+}
+
+impl Default for SimulationRng {
+    fn default() -> Self {
+        Self::from_seed(DEFAULT_SIMULATION_SEED)
+    }
+}
+
+/// Makes the simulation reproducible: [SimulationRng] is seeded with `seed` and all schedules
+/// touching the simulation are run single-threaded, trading performance for a fixed system order.
+pub struct DeterministicSimulationPlugin {
+    pub seed: u64,
+}
+
+impl Plugin for DeterministicSimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SimulationRng::from_seed(self.seed));
+
+        set_single_threaded(app, FixedFirst);
+        set_single_threaded(app, FixedPreUpdate);
+        set_single_threaded(app, FixedUpdate);
+        set_single_threaded(app, FixedPostUpdate);
+        set_single_threaded(app, FixedLast);
+        set_single_threaded(app, Update);
+    }
+}
+
+fn set_single_threaded(app: &mut App, label: impl ScheduleLabel) {
+    app.edit_schedule(label, |schedule| {
+        schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants;
+    use crate::game_data::{GameData, DEBUG_ITEM_ID_A};
+    use crate::headless::HeadlessPlugin;
+    use crate::persistence::local_hex_position::LocalHexPosition;
+    use crate::persistence::scenario::Scenario;
+    use crate::persistence::{
+        ShipBehaviorSaveData, UniverseSaveData, UniverseSaveDataLoadingPlugin,
+    };
+    use crate::session_data::SessionData;
+    use crate::simulation::plugin::SimulationPlugin;
+    use crate::simulation::prelude::SimulationTimestamp;
+    use crate::simulation::state_checksum::{
+        capture_state_checksum, find_divergence, StateChecksum,
+    };
+    use crate::states::{ApplicationState, StatePlugin};
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::{State, Vec2};
+    use hexx::Hex;
+    use std::path::Path;
+
+    /// Enough for the universe to load and for ships to pick and finish a couple of jobs.
+    const UPDATES: usize = 600;
+    const UPDATES_BETWEEN_CHECKSUMS: usize = 100;
+
+    fn run_simulation(data: UniverseSaveData) -> Vec<StateChecksum> {
+        let mut app = App::new();
+        app.add_plugins((
+            HeadlessPlugin,
+            StatePlugin,
+            UniverseSaveDataLoadingPlugin,
+            SimulationPlugin,
+            DeterministicSimulationPlugin { seed: 42 },
+        ))
+        .insert_resource(GameData::mock_data())
+        .insert_resource(SessionData::mock_data());
+        data.insert_as_resources(app.world_mut());
+        app.finish();
+        app.cleanup();
+
+        let mut result = Vec::new();
+        for update in 1..=UPDATES {
+            app.update();
+            if update % UPDATES_BETWEEN_CHECKSUMS == 0 {
+                result.push(app.world_mut().run_system_once(capture_state_checksum));
+            }
+        }
+
+        assert_eq!(
+            app.world().resource::<State<ApplicationState>>().get(),
+            &ApplicationState::InGame
+        );
+        result
+    }
+
+    #[test]
+    fn same_scenario_ends_up_in_same_state() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/scenarios/default.ron");
+        let data = Scenario::read_from_file(&path)
+            .and_then(|scenario| scenario.into_universe_save_data(&GameData::mock_data()))
+            .unwrap();
+
+        let a = run_simulation(data.clone());
+        let b = run_simulation(data);
+        if let Some(divergence) = find_divergence(&a, &b) {
+            panic!("{divergence}");
+        }
+    }
+
+    #[test]
+    fn ships_finishing_tasks_within_the_same_tick_end_up_in_same_state() {
+        // Identical ships starting at the same spot do everything in lockstep,
+        // so they keep finishing their tasks within the same tick and competing for the same stations.
+        let sector = Hex::ZERO;
+        let mut data = UniverseSaveData::default();
+        data.sectors.add(sector);
+        data.stations
+            .add(
+                LocalHexPosition::new(sector, Vec2::new(0.0, 100.0)),
+                String::from("Seller"),
+            )
+            .with_sells(vec![DEBUG_ITEM_ID_A]);
+        data.stations
+            .add(
+                LocalHexPosition::new(sector, Vec2::new(0.0, -100.0)),
+                String::from("Buyer"),
+            )
+            .with_buys(vec![DEBUG_ITEM_ID_A]);
+        for i in 0..50 {
+            data.ships.add(
+                LocalHexPosition::new(sector, Vec2::ZERO),
+                0.0,
+                format!("Trader {i}"),
+                ShipBehaviorSaveData::AutoTrade {
+                    next_idle_update: SimulationTimestamp::MIN,
+                    max_jump_range: constants::DEFAULT_MAX_TRADE_JUMP_RANGE,
+                },
+            );
+        }
+
+        let a = run_simulation(data.clone());
+        let b = run_simulation(data);
+        if let Some(divergence) = find_divergence(&a, &b) {
+            panic!("{divergence}");
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Keeps every buy and sell order sorted by price and grouped by sector and item,
+//! so trading ships only need to look at the stations within their range which are actually worth it.
+//!
+//! The index is synced with [BuyOrders] and [SellOrders] right before idle trading ships are handled.
+//! Systems which update orders and search for trades within the same run need to update the index themselves.
+
+use crate::components::{BuyOrders, InSector, SellOrders, Station, TradeOrder};
+use crate::game_data::ItemId;
+use crate::persistence::PersistentStationId;
+use crate::simulation::ship_ai::behaviors;
+use crate::states::{ApplicationState, SimulationState};
+use crate::utils::SectorEntity;
+use bevy::app::{App, Plugin};
+use bevy::prelude::{
+    in_state, Changed, Entity, FixedUpdate, IntoSystemConfigs, OnEnter, Or, Query,
+    RemovedComponents, ResMut, Resource,
+};
+use bevy::utils::HashMap;
+use std::cmp::Ordering;
+
+/// A single buy or sell order for one item.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct MarketEntry {
+    pub entity: Entity,
+    /// Used to sort orders with equal prices, since entities differ between sessions.
+    pub station: PersistentStationId,
+    pub sector: SectorEntity,
+    pub price: u32,
+    pub amount: u32,
+}
+
+/// All buy and sell orders with a non-zero amount.
+#[derive(Resource)]
+pub struct MarketIndex {
+    buy_orders: OrderBook,
+    sell_orders: OrderBook,
+}
+
+impl Default for MarketIndex {
+    fn default() -> Self {
+        Self {
+            buy_orders: OrderBook::new(true),
+            sell_orders: OrderBook::new(false),
+        }
+    }
+}
+
+impl MarketIndex {
+    pub fn update_buy_orders(
+        &mut self,
+        entity: Entity,
+        station: PersistentStationId,
+        sector: SectorEntity,
+        orders: &BuyOrders,
+    ) {
+        self.buy_orders.update(
+            entity,
+            station,
+            sector,
+            orders
+                .orders()
+                .iter()
+                .map(|(item_id, order)| (*item_id, order.price, order.amount)),
+        );
+    }
+
+    pub fn update_sell_orders(
+        &mut self,
+        entity: Entity,
+        station: PersistentStationId,
+        sector: SectorEntity,
+        orders: &SellOrders,
+    ) {
+        self.sell_orders.update(
+            entity,
+            station,
+            sector,
+            orders
+                .orders()
+                .iter()
+                .map(|(item_id, order)| (*item_id, order.price, order.amount)),
+        );
+    }
+
+    pub fn remove_buy_orders(&mut self, entity: Entity) {
+        self.buy_orders.remove(entity);
+    }
+
+    pub fn remove_sell_orders(&mut self, entity: Entity) {
+        self.sell_orders.remove(entity);
+    }
+
+    /// All sell orders within `sector`, cheapest first for every item. Items are in no particular order.
+    pub fn sell_orders_in(
+        &self,
+        sector: SectorEntity,
+    ) -> impl Iterator<Item = (ItemId, &MarketEntry)> {
+        self.sell_orders
+            .sectors
+            .get(&sector)
+            .into_iter()
+            .flat_map(|items| {
+                items.iter().flat_map(|(item_id, entries)| {
+                    entries.iter().map(move |entry| (*item_id, entry))
+                })
+            })
+    }
+
+    /// Buy orders for `item_id` within `sector` which pay more than `price`, highest price first.
+    /// Since the orders are sorted, this stops at the first one which doesn't.
+    pub fn buy_orders_above(
+        &self,
+        sector: SectorEntity,
+        item_id: ItemId,
+        price: u32,
+    ) -> impl Iterator<Item = &MarketEntry> {
+        self.buy_orders
+            .entries(sector, item_id)
+            .iter()
+            .take_while(move |entry| entry.price > price)
+    }
+}
+
+/// Orders of one kind, bucketed by sector and item.
+struct OrderBook {
+    highest_price_first: bool,
+    sectors: HashMap<SectorEntity, HashMap<ItemId, Vec<MarketEntry>>>,
+    /// The sector in which the orders of every listed entity have been indexed.
+    listed: HashMap<Entity, SectorEntity>,
+}
+
+impl OrderBook {
+    fn new(highest_price_first: bool) -> Self {
+        Self {
+            highest_price_first,
+            sectors: HashMap::new(),
+            listed: HashMap::new(),
+        }
+    }
+
+    fn entries(&self, sector: SectorEntity, item_id: ItemId) -> &[MarketEntry] {
+        self.sectors
+            .get(&sector)
+            .and_then(|items| items.get(&item_id))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn update(
+        &mut self,
+        entity: Entity,
+        station: PersistentStationId,
+        sector: SectorEntity,
+        orders: impl Iterator<Item = (ItemId, u32, u32)>,
+    ) {
+        self.remove(entity);
+        self.listed.insert(entity, sector);
+
+        let highest_price_first = self.highest_price_first;
+        let items = self.sectors.entry(sector).or_default();
+        for (item_id, price, amount) in orders {
+            if amount == 0 {
+                continue;
+            }
+
+            let entry = MarketEntry {
+                entity,
+                station,
+                sector,
+                price,
+                amount,
+            };
+            let entries = items.entry(item_id).or_default();
+            let index = entries.partition_point(|existing| {
+                compare(existing, &entry, highest_price_first) == Ordering::Less
+            });
+            entries.insert(index, entry);
+        }
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        let Some(sector) = self.listed.remove(&entity) else {
+            return;
+        };
+
+        if let Some(items) = self.sectors.get_mut(&sector) {
+            for entries in items.values_mut() {
+                entries.retain(|entry| entry.entity != entity);
+            }
+        }
+    }
+}
+
+/// Orders are sorted by price, and by station ID in case prices are equal, so iteration order is reproducible.
+fn compare(a: &MarketEntry, b: &MarketEntry, highest_price_first: bool) -> Ordering {
+    let by_price = if highest_price_first {
+        b.price.cmp(&a.price)
+    } else {
+        a.price.cmp(&b.price)
+    };
+
+    by_price.then(a.station.cmp(&b.station))
+}
+
+pub struct MarketIndexPlugin;
+impl Plugin for MarketIndexPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MarketIndex>()
+            .add_systems(OnEnter(ApplicationState::Loading), reset_market_index)
+            .add_systems(
+                FixedUpdate,
+                update_market_index
+                    .before(behaviors::auto_trade::handle_idle_ships)
+                    .run_if(in_state(SimulationState::Running)),
+            );
+    }
+}
+
+fn reset_market_index(mut market: ResMut<MarketIndex>) {
+    *market = MarketIndex::default();
+}
+
+pub fn update_market_index(
+    mut market: ResMut<MarketIndex>,
+    changed_buy_orders: Query<
+        (Entity, &Station, &BuyOrders, &InSector),
+        Or<(Changed<BuyOrders>, Changed<InSector>)>,
+    >,
+    changed_sell_orders: Query<
+        (Entity, &Station, &SellOrders, &InSector),
+        Or<(Changed<SellOrders>, Changed<InSector>)>,
+    >,
+    mut removed_buy_orders: RemovedComponents<BuyOrders>,
+    mut removed_sell_orders: RemovedComponents<SellOrders>,
+) {
+    for entity in removed_buy_orders.read() {
+        market.remove_buy_orders(entity);
+    }
+    for entity in removed_sell_orders.read() {
+        market.remove_sell_orders(entity);
+    }
+
+    for (entity, station, orders, sector) in &changed_buy_orders {
+        market.update_buy_orders(entity, station.id, sector.get(), orders);
+    }
+    for (entity, station, orders, sector) in &changed_sell_orders {
+        market.update_sell_orders(entity, station.id, sector.get(), orders);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(entity: u32, price: u32) -> (Entity, u32) {
+        (Entity::from_raw(entity), price)
+    }
+
+    fn listed(entries: impl Iterator<Item = MarketEntry>) -> Vec<(Entity, u32)> {
+        entries.map(|entry| (entry.entity, entry.price)).collect()
+    }
+
+    #[test]
+    fn books_are_sorted_by_price_and_updates_replace_previous_orders() {
+        let sector = SectorEntity::from(100);
+        // Entities are allocated in the opposite order of station IDs, which decide ties
+        let stations: Vec<PersistentStationId> =
+            (0..4).map(|_| PersistentStationId::next()).collect();
+        let station = |entity: u32| stations[4 - entity as usize];
+        let mut book = OrderBook::new(true);
+        let update = |book: &mut OrderBook, entity: u32, price: u32, amount: u32| {
+            book.update(
+                Entity::from_raw(entity),
+                station(entity),
+                sector,
+                [(0, price, amount)].into_iter(),
+            )
+        };
+        update(&mut book, 1, 10, 5);
+        update(&mut book, 2, 20, 5);
+        update(&mut book, 3, 10, 5);
+        // Without anything left to buy, there's no reason to list this one
+        update(&mut book, 4, 50, 0);
+
+        assert_eq!(
+            listed(book.entries(sector, 0).iter().copied()),
+            vec![entry(2, 20), entry(3, 10), entry(1, 10)]
+        );
+
+        update(&mut book, 2, 5, 5);
+        book.remove(Entity::from_raw(3));
+
+        assert_eq!(
+            listed(book.entries(sector, 0).iter().copied()),
+            vec![entry(1, 10), entry(2, 5)]
+        );
+    }
+}
@@ -1,4 +1,6 @@
 pub mod asteroids;
+pub mod determinism;
+pub mod market_index;
 mod moving_gate_connections;
 pub mod physics;
 pub mod plugin;
@@ -6,5 +8,9 @@ pub mod precomputed_orbit_directions;
 pub mod prelude;
 pub mod production;
 pub mod ship_ai;
+pub mod speed;
+pub mod state_checksum;
+#[cfg(test)]
+pub mod test_app;
 pub mod time;
 pub mod transform;
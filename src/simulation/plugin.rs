@@ -1,30 +1,29 @@
 use crate::constants;
+use crate::map_layout::MapLayout;
+use crate::simulation::determinism::SimulationRng;
 use crate::simulation::precomputed_orbit_directions::PrecomputedOrbitDirections;
 use crate::simulation::*;
-use crate::states::{ApplicationState, SimulationState};
-use bevy::prelude::{
-    in_state, App, ButtonInput, IntoSystemConfigs, KeyCode, NextState, Plugin, Res, ResMut, State,
-    Time, Update, Virtual,
-};
+use crate::states::SimulationState;
+use bevy::prelude::{in_state, App, IntoSystemConfigs, Plugin, Time, Update};
 use bevy::time::Fixed;
 
 pub struct SimulationPlugin;
 impl Plugin for SimulationPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<MapLayout>();
         app.init_resource::<PrecomputedOrbitDirections>();
+        app.init_resource::<SimulationRng>();
         app.insert_resource(Time::<Fixed>::from_hz(constants::TICKS_PER_SECOND));
         app.add_plugins((
             asteroids::AsteroidPlugin,
+            market_index::MarketIndexPlugin,
             physics::PhysicsPlugin,
             production::ProductionPlugin,
             ship_ai::ShipAiPlugin,
+            speed::SimulationSpeedPlugin,
             time::SimulationTimePlugin,
             transform::SimulationTransformPlugin,
         ));
-        app.add_systems(
-            Update,
-            toggle_pause.run_if(in_state(ApplicationState::InGame)),
-        );
         app.add_systems(
             Update, // TODO: Depending on our orbit velocity, this should be running in FixedUpdate or even less often and use SimulationTransform
             moving_gate_connections::update_gate_connections
@@ -32,23 +31,3 @@ impl Plugin for SimulationPlugin {
         );
     }
 }
-
-pub fn toggle_pause(
-    input: Res<ButtonInput<KeyCode>>,
-    current_state: Res<State<SimulationState>>,
-    mut next_state: ResMut<NextState<SimulationState>>,
-    mut time: ResMut<Time<Virtual>>,
-) {
-    if input.just_pressed(KeyCode::Space) {
-        next_state.set(match current_state.get() {
-            SimulationState::Running => {
-                time.pause();
-                SimulationState::Paused
-            }
-            SimulationState::Paused => {
-                time.unpause();
-                SimulationState::Running
-            }
-        });
-    }
-}
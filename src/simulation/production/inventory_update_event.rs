@@ -1,4 +1,4 @@
-use crate::components::{BuyOrders, Inventory, SellOrders};
+use crate::components::{BuyOrders, Inventory, SellOrders, Wallet};
 use crate::game_data::{GameData, ShipyardModuleId};
 use crate::session_data::{SessionData, ShipConfigId};
 use crate::simulation::prelude::SimulationTime;
@@ -9,7 +9,7 @@ use crate::simulation::production::shipyard_component::{
 };
 use crate::simulation::production::ProductionComponent;
 use crate::utils;
-use bevy::log::error;
+use bevy::log::{error, warn};
 use bevy::prelude::{Entity, Event, EventReader, EventWriter, Or, Query, Res, With};
 
 /// This event should be sent whenever an entity's inventory is being updated outside the production manager
@@ -42,13 +42,14 @@ pub fn handle_inventory_updates(
             &mut Inventory,
             Option<&mut BuyOrders>,
             Option<&mut SellOrders>,
+            Option<&Wallet>,
         ),
         Or<(With<ProductionComponent>, With<ShipyardComponent>)>,
     >,
 ) {
     let now = simulation_time.now();
     for event in event_reader.read() {
-        let Ok((production, shipyard, mut inventory, buy_orders, sell_orders)) =
+        let Ok((production, shipyard, mut inventory, buy_orders, sell_orders, wallet)) =
             query.get_mut(event.entity)
         else {
             continue;
@@ -61,7 +62,14 @@ pub fn handle_inventory_updates(
                     continue;
                 }
 
-                let recipe = game_data.item_recipes.get(&module.recipe).unwrap();
+                let Some(recipe) = game_data.item_recipes.get(&module.recipe) else {
+                    warn!(
+                        "Was unable to find recipe {} for production module {} at entity {}, skipping it.",
+                        module.recipe, id, event.entity
+                    );
+                    continue;
+                };
+
                 if inventory.has_enough_items_in_inventory(&recipe.input, module.amount)
                     && inventory.has_enough_storage_for_items(&recipe.output, module.amount)
                 {
@@ -70,6 +78,7 @@ pub fn handle_inventory_updates(
 
                     let finish_timestamp = now.add_milliseconds(recipe.duration);
                     module.current_run_finished_at = Some(finish_timestamp);
+                    module.current_run_yield = recipe.output.clone();
 
                     production_start_event_writer.send(ProductionStartedEvent::new(
                         event.entity,
@@ -171,6 +180,6 @@ pub fn handle_inventory_updates(
             }
         }
 
-        utils::update_orders(&inventory, buy_orders, sell_orders);
+        utils::update_orders(&inventory, wallet, buy_orders, sell_orders);
     }
 }
@@ -9,5 +9,6 @@ mod state;
 
 pub use {
     inventory_update_event::InventoryUpdateForProductionEvent, plugin::ProductionPlugin,
-    production_component::*, shipyard_component::*,
+    production_component::*, production_runner::check_if_production_is_finished_and_start_new_one,
+    shipyard_component::*, state::GlobalProductionState,
 };
@@ -2,9 +2,9 @@ use crate::simulation::production::state::GlobalProductionState;
 use crate::simulation::production::{
     inventory_update_event, production_runner, production_started_event,
 };
-use crate::states::SimulationState;
+use crate::states::{ApplicationState, SimulationState};
 use bevy::app::{App, Plugin};
-use bevy::prelude::{in_state, FixedUpdate, IntoSystemConfigs};
+use bevy::prelude::{in_state, FixedUpdate, IntoSystemConfigs, OnEnter, ResMut};
 
 /// Handles everything production related.
 pub struct ProductionPlugin;
@@ -13,6 +13,7 @@ impl Plugin for ProductionPlugin {
         app.add_event::<production_started_event::ProductionStartedEvent>()
             .add_event::<inventory_update_event::InventoryUpdateForProductionEvent>()
             .insert_resource(GlobalProductionState::default())
+            .add_systems(OnEnter(ApplicationState::Loading), reset_production_state)
             .add_systems(
                 FixedUpdate,
                 (
@@ -24,3 +25,7 @@ impl Plugin for ProductionPlugin {
             );
     }
 }
+
+fn reset_production_state(mut production_state: ResMut<GlobalProductionState>) {
+    *production_state = GlobalProductionState::default();
+}
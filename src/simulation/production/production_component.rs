@@ -1,4 +1,4 @@
-use crate::game_data::{ProductionModuleId, RecipeId};
+use crate::game_data::{ItemRecipeElement, ProductionModuleId, RecipeId};
 use crate::simulation::prelude::SimulationTimestamp;
 use bevy::prelude::Component;
 use bevy::utils::HashMap;
@@ -12,4 +12,7 @@ pub struct ProductionModule {
     pub amount: u32,
     pub recipe: RecipeId,
     pub current_run_finished_at: Option<SimulationTimestamp>,
+    /// The output of the current run, as defined by its recipe at the time the run was started.
+    /// Stored separately so that changes to [crate::game_data::GameData] only affect the next run.
+    pub current_run_yield: Vec<ItemRecipeElement>,
 }
@@ -1,6 +1,6 @@
 use crate::game_data::{ProductionModuleId, ShipyardModuleId};
 
-#[derive(Eq, PartialEq, Copy, Clone)]
+#[derive(Eq, PartialEq, Hash, Copy, Clone)]
 pub enum ProductionKind {
     Item(ProductionModuleId),
     Shipyard(ShipyardModuleId),
@@ -1,9 +1,10 @@
-use bevy::log::error;
+use bevy::log::{error, warn};
 use bevy::prelude::{Commands, EventWriter, Or, Query, Res, ResMut, Transform, With};
 
-use crate::components::{BuyOrders, InSector, Inventory, Sector, SellOrders};
-use crate::game_data::GameData;
-use crate::persistence::{PersistentShipId, ShipIdMap};
+use crate::components::{
+    BuyOrders, InSector, Inventory, Owner, Sector, SellOrders, ShipAccount, Wallet,
+};
+use crate::persistence::{PersistentIdCounters, ShipIdMap};
 use crate::session_data::SessionData;
 use crate::simulation::prelude::{SimulationTime, SimulationTimestamp};
 use crate::simulation::production::production_kind::ProductionKind;
@@ -12,17 +13,17 @@ use crate::simulation::production::state::GlobalProductionState;
 use crate::simulation::production::{InventoryUpdateForProductionEvent, ProductionComponent};
 use crate::simulation::ship_ai::BehaviorBuilder;
 use crate::utils::spawn_helpers;
-use crate::{utils, SpriteHandles};
+use crate::{constants, utils, SpriteHandles};
 
 #[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn check_if_production_is_finished_and_start_new_one(
     mut commands: Commands,
-    sprites: Res<SpriteHandles>,
+    sprites: Option<Res<SpriteHandles>>,
     mut sector_query: Query<&mut Sector>,
     mut ship_id_map: ResMut<ShipIdMap>,
+    mut persistent_id_counters: ResMut<PersistentIdCounters>,
     simulation_time: Res<SimulationTime>,
     mut global_production_state: ResMut<GlobalProductionState>,
-    game_data: Res<GameData>,
     session_data: Res<SessionData>,
     mut inventory_update_writer: EventWriter<InventoryUpdateForProductionEvent>,
     mut query: Query<
@@ -32,6 +33,8 @@ pub fn check_if_production_is_finished_and_start_new_one(
             &mut Inventory,
             Option<&mut BuyOrders>,
             Option<&mut SellOrders>,
+            Option<&mut Wallet>,
+            Option<&Owner>,
             &Transform,
             &InSector,
         ),
@@ -53,6 +56,8 @@ pub fn check_if_production_is_finished_and_start_new_one(
             mut inventory,
             buy_orders,
             sell_orders,
+            mut wallet,
+            owner,
             transform,
             in_sector,
         )) = query.get_mut(next.entity)
@@ -80,9 +85,9 @@ pub fn check_if_production_is_finished_and_start_new_one(
                     continue;
                 };
 
-                let recipe = game_data.item_recipes.get(&module.recipe).unwrap();
-                inventory.finish_production(recipe, module.amount);
+                inventory.finish_production(&module.current_run_yield, module.amount);
                 module.current_run_finished_at = None;
+                module.current_run_yield.clear();
             }
             ProductionKind::Shipyard(module_id) => {
                 let Some(mut shipyard) = shipyard else {
@@ -104,15 +109,34 @@ pub fn check_if_production_is_finished_and_start_new_one(
                     .unwrap();
                 let order = module.active.remove(position);
 
-                let definition = session_data
-                    .ship_configurations
-                    .get(&order.ship_config)
-                    .unwrap();
+                let Some(definition) = session_data.ship_configurations.get(&order.ship_config)
+                else {
+                    warn!(
+                        "Was unable to find ship configuration {} for finished construction order at entity {}, skipping it.",
+                        order.ship_config, next.entity
+                    );
+                    continue;
+                };
+
+                // Ships built by owned shipyards join their faction, independent ones get their starting credits
+                // from the shipyard, as far as it can afford them
+                let account = if let Some(owner) = owner {
+                    ShipAccount::Owner(*owner)
+                } else {
+                    let mut ship_wallet = Wallet::new(0);
+                    if let Some(shipyard_wallet) = wallet.as_deref_mut() {
+                        let credits = shipyard_wallet
+                            .available_credits()
+                            .clamp(0, constants::SHIP_STARTING_CREDITS);
+                        Wallet::transfer(shipyard_wallet, &mut ship_wallet, credits);
+                    }
+                    ShipAccount::Wallet(ship_wallet)
+                };
 
                 spawn_helpers::spawn_ship(
                     &mut commands,
-                    &sprites,
-                    PersistentShipId::next(),
+                    sprites.as_deref(),
+                    persistent_id_counters.next_ship(),
                     definition.name.clone(),
                     &mut sector_query,
                     in_sector.get(),
@@ -120,13 +144,15 @@ pub fn check_if_production_is_finished_and_start_new_one(
                     0.0,
                     &BehaviorBuilder::AutoTrade {
                         next_idle_update: SimulationTimestamp::MIN,
+                        max_jump_range: constants::DEFAULT_MAX_TRADE_JUMP_RANGE,
                     },
+                    account,
                     &mut ship_id_map,
                 );
             }
         }
 
         inventory_update_writer.send(InventoryUpdateForProductionEvent::new(next.entity));
-        utils::update_orders(&inventory, buy_orders, sell_orders);
+        utils::update_orders(&inventory, wallet.as_deref(), buy_orders, sell_orders);
     }
 }
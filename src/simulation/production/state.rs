@@ -32,6 +32,11 @@ impl GlobalProductionState {
     pub fn pop(&mut self) -> Option<SingleProductionState> {
         self.elements.pop()
     }
+
+    /// Iterates over all running productions in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = &SingleProductionState> {
+        self.elements.iter()
+    }
 }
 
 #[derive(Eq, PartialEq)]
@@ -1,13 +1,19 @@
-use crate::components::{BuyOrders, GasGiant, InSector, Inventory, Sector, SectorPlanets};
+use crate::components::{
+    BuyOrders, Engine, GasGiant, InSector, Inventory, Owner, Sector, SectorPlanets, Ship, Station,
+    Wallet,
+};
 use crate::pathfinding;
+use crate::persistence::ComponentWithPersistentId;
+use crate::session_data::SessionData;
+use crate::simulation::determinism::SimulationRng;
 use crate::simulation::prelude::{SimulationTime, SimulationTimestamp};
 use crate::simulation::ship_ai::behaviors::auto_mine;
 use crate::simulation::ship_ai::ship_is_idle_filter::ShipIsIdleFilter;
-use crate::simulation::ship_ai::{TaskInsideQueue, TaskQueue};
+use crate::simulation::ship_ai::{DeliveryDeadlines, TaskInsideQueue, TaskQueue};
 use crate::simulation::transform::simulation_transform::SimulationTransform;
-use crate::trade_plan::TradePlan;
-use crate::utils::{SectorEntity, TradeIntent, TypedEntity};
-use bevy::prelude::{Commands, Component, Entity, Query, Res};
+use crate::trade_plan::{DeliverySchedule, TradePlan};
+use crate::utils::{SectorEntity, TypedEntity};
+use bevy::prelude::{Commands, Component, Entity, Query, Res, ResMut};
 
 #[derive(Component)]
 pub struct AutoHarvestBehavior {
@@ -29,23 +35,39 @@ impl Default for AutoHarvestBehavior {
 pub fn handle_idle_ships(
     mut commands: Commands,
     simulation_time: Res<SimulationTime>,
+    mut rng: ResMut<SimulationRng>,
     mut ships: Query<
-        (Entity, &mut TaskQueue, &mut AutoHarvestBehavior, &InSector),
+        (
+            Entity,
+            &mut TaskQueue,
+            &mut AutoHarvestBehavior,
+            &InSector,
+            &Engine,
+            &Ship,
+        ),
         ShipIsIdleFilter,
     >,
-    buy_orders: Query<(Entity, &mut BuyOrders, &InSector)>,
+    mut buy_orders: Query<(Entity, &Station, &mut BuyOrders, &InSector)>,
     mut inventories: Query<&mut Inventory>,
     all_sectors_with_gas_giants: Query<&SectorPlanets>,
     all_sectors: Query<&Sector>,
     all_gas_giants: Query<&GasGiant>,
     all_transforms: Query<&SimulationTransform>,
+    mut all_wallets: Query<&mut Wallet>,
+    all_owners: Query<&Owner>,
+    mut session_data: ResMut<SessionData>,
+    mut delivery_deadlines: ResMut<DeliveryDeadlines>,
 ) {
     let now = simulation_time.now();
 
-    ships
+    let mut idle_ships: Vec<_> = ships
         .iter_mut()
-        .filter(|(_, _, behavior, _)| now.has_passed(behavior.next_idle_update))
-        .for_each(|(ship_entity, mut queue, mut behavior, in_sector)| {
+        .filter(|(_, _, behavior, ..)| now.has_passed(behavior.next_idle_update))
+        .collect();
+    idle_ships.sort_by_key(|(.., ship)| ship.id());
+
+    idle_ships.into_iter().for_each(
+        |(ship_entity, mut queue, mut behavior, in_sector, engine, _)| {
             let ship_inventory = inventories.get_mut(ship_entity).unwrap();
             let used_inventory_space = ship_inventory.used();
 
@@ -98,7 +120,7 @@ pub fn handle_idle_ships(
                     ) {
                         Some(value) => value,
                         None => {
-                            behavior.next_idle_update = now.add_milliseconds(2000);
+                            behavior.next_idle_update = now.add_milliseconds(rng.jitter(2000));
                             return;
                         }
                     };
@@ -117,28 +139,52 @@ pub fn handle_idle_ships(
                 }
                 auto_mine::AutoMineState::Trading => {
                     // TODO: This is quite literally 100% the same logic as auto_mine
+                    let ship_owner = all_owners.get(ship_entity).ok();
                     let Some(plan) = TradePlan::sell_anything_from_inventory(
                         ship_entity,
                         in_sector,
                         &ship_inventory,
                         &buy_orders,
+                        &|partner, intent| {
+                            session_data.is_willing_to_trade(
+                                all_owners.get(partner).ok(),
+                                ship_owner,
+                                intent,
+                            )
+                        },
                     ) else {
-                        behavior.next_idle_update = now.add_milliseconds(2000);
+                        behavior.next_idle_update = now.add_milliseconds(rng.jitter(2000));
                         return;
                     };
 
-                    let [mut this_inventory, mut buyer_inventory] = inventories
-                        .get_many_mut([ship_entity, plan.buyer.into()])
-                        .unwrap();
-
-                    this_inventory.create_order(plan.item_id, TradeIntent::Sell, plan.amount);
-                    buyer_inventory.create_order(plan.item_id, TradeIntent::Buy, plan.amount);
+                    plan.create_orders_for_sale(ship_entity, &mut inventories);
+                    auto_mine::reserve_credits_for_sale(
+                        &plan,
+                        ship_entity,
+                        &mut buy_orders,
+                        &inventories,
+                        &mut all_wallets,
+                        &all_owners,
+                        &mut session_data,
+                    );
 
-                    plan.create_tasks_for_sale(&all_sectors, &all_transforms, &mut queue);
+                    let mut schedule = DeliverySchedule::new(
+                        ship_entity,
+                        engine.max_speed,
+                        now,
+                        &mut delivery_deadlines,
+                    );
+                    plan.create_tasks_for_sale(
+                        &all_sectors,
+                        &all_transforms,
+                        &mut schedule,
+                        &mut queue,
+                    );
                     queue.apply(&mut commands, now, ship_entity);
                 }
             }
-        });
+        },
+    );
 }
 
 fn find_nearby_sector_with_gas_giants(
@@ -1,14 +1,18 @@
 use crate::components::{
-    Asteroid, BuyOrders, InSector, Inventory, Sector, SectorAsteroidComponent,
+    Asteroid, BuyOrders, Engine, InSector, Inventory, Owner, Sector, SectorAsteroidComponent, Ship,
+    Station, Wallet,
 };
-use crate::pathfinding;
+use crate::persistence::ComponentWithPersistentId;
+use crate::session_data::SessionData;
+use crate::simulation::determinism::SimulationRng;
 use crate::simulation::prelude::{SimulationTime, SimulationTimestamp};
 use crate::simulation::ship_ai::ship_is_idle_filter::ShipIsIdleFilter;
-use crate::simulation::ship_ai::{TaskInsideQueue, TaskQueue};
+use crate::simulation::ship_ai::{DeliveryDeadlines, TaskInsideQueue, TaskQueue};
 use crate::simulation::transform::simulation_transform::SimulationTransform;
-use crate::trade_plan::TradePlan;
-use crate::utils::{SectorEntity, TradeIntent};
-use bevy::prelude::{Commands, Component, Entity, Query, Res, Vec2};
+use crate::trade_plan::{DeliverySchedule, TradePlan};
+use crate::utils::SectorEntity;
+use crate::{constants, pathfinding, utils};
+use bevy::prelude::{Commands, Component, Entity, Query, Res, ResMut, Vec2};
 use serde::{Deserialize, Serialize};
 
 #[derive(Eq, PartialEq, Serialize, Deserialize, Copy, Clone)]
@@ -51,13 +55,28 @@ impl Default for AutoMineBehavior {
 pub fn handle_idle_ships(
     mut commands: Commands,
     simulation_time: Res<SimulationTime>,
-    mut ships: Query<(Entity, &mut TaskQueue, &mut AutoMineBehavior, &InSector), ShipIsIdleFilter>,
-    buy_orders: Query<(Entity, &mut BuyOrders, &InSector)>,
+    mut rng: ResMut<SimulationRng>,
+    mut ships: Query<
+        (
+            Entity,
+            &mut TaskQueue,
+            &mut AutoMineBehavior,
+            &InSector,
+            &Engine,
+            &Ship,
+        ),
+        ShipIsIdleFilter,
+    >,
+    mut buy_orders: Query<(Entity, &Station, &mut BuyOrders, &InSector)>,
     mut inventories: Query<&mut Inventory>,
     all_sectors_with_asteroids: Query<&SectorAsteroidComponent>,
     all_sectors: Query<&Sector>,
     mut all_asteroids: Query<&mut Asteroid>,
     all_transforms: Query<&SimulationTransform>,
+    mut all_wallets: Query<&mut Wallet>,
+    all_owners: Query<&Owner>,
+    mut session_data: ResMut<SessionData>,
+    mut delivery_deadlines: ResMut<DeliveryDeadlines>,
 ) {
     let now = simulation_time.now();
 
@@ -65,10 +84,14 @@ pub fn handle_idle_ships(
     let max_asteroid_age = now.add_milliseconds(15000);
 
     // TODO: Benchmark this .filter vs a priority queue
-    ships
+    let mut idle_ships: Vec<_> = ships
         .iter_mut()
-        .filter(|(_, _, behavior, _)| now.has_passed(behavior.next_idle_update))
-        .for_each(|(ship_entity, mut queue, mut behavior, in_sector)| {
+        .filter(|(_, _, behavior, ..)| now.has_passed(behavior.next_idle_update))
+        .collect();
+    idle_ships.sort_by_key(|(.., ship)| ship.id());
+
+    idle_ships.into_iter().for_each(
+        |(ship_entity, mut queue, mut behavior, in_sector, engine, _)| {
             let ship_inventory = inventories.get_mut(ship_entity).unwrap();
             let used_inventory_space = ship_inventory.used();
 
@@ -129,7 +152,7 @@ pub fn handle_idle_ships(
                     ) {
                         Some(value) => value,
                         None => {
-                            behavior.next_idle_update = now.add_milliseconds(2000);
+                            behavior.next_idle_update = now.add_milliseconds(rng.jitter(2000));
                             return;
                         }
                     };
@@ -147,28 +170,73 @@ pub fn handle_idle_ships(
                     queue.apply(&mut commands, now, ship_entity);
                 }
                 AutoMineState::Trading => {
+                    let ship_owner = all_owners.get(ship_entity).ok();
                     let Some(plan) = TradePlan::sell_anything_from_inventory(
                         ship_entity,
                         in_sector,
                         &ship_inventory,
                         &buy_orders,
+                        &|partner, intent| {
+                            session_data.is_willing_to_trade(
+                                all_owners.get(partner).ok(),
+                                ship_owner,
+                                intent,
+                            )
+                        },
                     ) else {
-                        behavior.next_idle_update = now.add_milliseconds(2000);
+                        behavior.next_idle_update = now.add_milliseconds(rng.jitter(2000));
                         return;
                     };
 
-                    let [mut this_inventory, mut buyer_inventory] = inventories
-                        .get_many_mut([ship_entity, plan.buyer.into()])
-                        .unwrap();
-
-                    this_inventory.create_order(plan.item_id, TradeIntent::Sell, plan.amount);
-                    buyer_inventory.create_order(plan.item_id, TradeIntent::Buy, plan.amount);
+                    plan.create_orders_for_sale(ship_entity, &mut inventories);
+                    reserve_credits_for_sale(
+                        &plan,
+                        ship_entity,
+                        &mut buy_orders,
+                        &inventories,
+                        &mut all_wallets,
+                        &all_owners,
+                        &mut session_data,
+                    );
 
-                    plan.create_tasks_for_sale(&all_sectors, &all_transforms, &mut queue);
+                    let mut schedule = DeliverySchedule::new(
+                        ship_entity,
+                        engine.max_speed,
+                        now,
+                        &mut delivery_deadlines,
+                    );
+                    plan.create_tasks_for_sale(
+                        &all_sectors,
+                        &all_transforms,
+                        &mut schedule,
+                        &mut queue,
+                    );
                     queue.apply(&mut commands, now, ship_entity);
                 }
             }
-        });
+        },
+    );
+}
+
+/// Buyers reserve the credits for what they are about to receive, which leaves less for their other buy orders.
+pub fn reserve_credits_for_sale(
+    plan: &TradePlan,
+    ship_entity: Entity,
+    buy_orders: &mut Query<(Entity, &Station, &mut BuyOrders, &InSector)>,
+    inventories: &Query<&mut Inventory>,
+    all_wallets: &mut Query<&mut Wallet>,
+    all_owners: &Query<&Owner>,
+    session_data: &mut SessionData,
+) {
+    plan.reserve_credits(ship_entity, all_wallets, all_owners, session_data);
+    for stop in &plan.stops {
+        let buyer: Entity = stop.buyer.into();
+        if let (Ok((.., orders, _)), Ok(inventory)) =
+            (buy_orders.get_mut(buyer), inventories.get(buyer))
+        {
+            utils::update_orders(inventory, all_wallets.get(buyer).ok(), Some(orders), None);
+        }
+    }
 }
 
 fn find_nearby_sector_with_asteroids(
@@ -216,3 +284,59 @@ where
         .translation
         .distance_squared(ship_pos) as u32
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants;
+    use crate::game_data::DEBUG_ITEM_ID_ORE;
+    use crate::map_layout::MapLayout;
+    use crate::persistence::local_hex_position::LocalHexPosition;
+    use crate::persistence::{SectorAsteroidSaveData, ShipBehaviorSaveData, UniverseSaveData};
+    use crate::simulation::test_app::SimulationTestApp;
+    use hexx::Hex;
+
+    const CENTER: Hex = Hex::new(0, 0);
+
+    /// Generous, so changes to ship speeds or mining rates don't break this immediately.
+    const MAX_TICKS: u32 = 1200;
+    const MAX_TICKS_PER_TASK: u32 = 300;
+
+    #[test]
+    fn mined_ore_is_sold_to_stations() {
+        let mut data = UniverseSaveData::default();
+        data.sectors.add(CENTER).with_asteroids(
+            SectorAsteroidSaveData::new().add_random_live_asteroids(
+                CENTER,
+                10,
+                &MapLayout::default(),
+            ),
+        );
+        let buyer = data
+            .stations
+            .add(
+                LocalHexPosition::new(CENTER, Vec2::ZERO),
+                String::from("Buyer"),
+            )
+            .with_buys(vec![DEBUG_ITEM_ID_ORE])
+            .id;
+        data.ships.add(
+            LocalHexPosition::new(CENTER, Vec2::new(100.0, 0.0)),
+            0.0,
+            String::from("Miner"),
+            ShipBehaviorSaveData::AutoMine {
+                next_idle_update: SimulationTimestamp::MIN,
+                state: AutoMineState::Mining,
+            },
+        );
+
+        let mut app = SimulationTestApp::new(data);
+        app.assert_inventory_reaches(
+            buyer,
+            DEBUG_ITEM_ID_ORE,
+            constants::SHIP_INVENTORY_SIZE,
+            MAX_TICKS,
+        );
+        app.assert_no_ship_stuck_longer_than(MAX_TICKS_PER_TASK);
+    }
+}
@@ -1,22 +1,33 @@
-use bevy::prelude::{Commands, Component, Entity, Query, Res};
+use bevy::prelude::{Commands, Component, Entity, Query, Res, ResMut};
 
-use crate::components::{BuyOrders, InSector, Inventory, Sector, SellOrders, TradeOrder};
+use crate::components::{
+    BuyOrders, Engine, InSector, Inventory, Owner, Sector, SellOrders, Ship, Station, TradeOrder,
+    Wallet,
+};
+use crate::constants;
+use crate::persistence::ComponentWithPersistentId;
+use crate::session_data::SessionData;
+use crate::simulation::determinism::SimulationRng;
+use crate::simulation::market_index::MarketIndex;
 use crate::simulation::prelude::{SimulationTime, SimulationTimestamp};
 use crate::simulation::ship_ai::ship_is_idle_filter::ShipIsIdleFilter;
-use crate::simulation::ship_ai::TaskQueue;
+use crate::simulation::ship_ai::{DeliveryDeadlines, TaskQueue};
 use crate::simulation::transform::simulation_transform::SimulationTransform;
-use crate::trade_plan::TradePlan;
+use crate::trade_plan::{DeliverySchedule, TradePlan, TradingShip};
 use crate::utils::{TradeIntent, TypedEntity};
 
 #[derive(Component)]
 pub struct AutoTradeBehavior {
     pub next_idle_update: SimulationTimestamp,
+    /// How many gates this ship may jump through to reach a seller, and from there to its final buyer.
+    pub max_jump_range: u8,
 }
 
 impl Default for AutoTradeBehavior {
     fn default() -> Self {
         Self {
             next_idle_update: SimulationTimestamp::MIN,
+            max_jump_range: constants::DEFAULT_MAX_TRADE_JUMP_RANGE,
         }
     }
 }
@@ -25,78 +36,398 @@ impl Default for AutoTradeBehavior {
 pub fn handle_idle_ships(
     mut commands: Commands,
     simulation_time: Res<SimulationTime>,
-    mut ships: Query<(Entity, &mut TaskQueue, &mut AutoTradeBehavior, &InSector), ShipIsIdleFilter>,
-    mut buy_orders: Query<(Entity, &mut BuyOrders, &InSector)>,
-    mut sell_orders: Query<(Entity, &mut SellOrders, &InSector)>,
+    mut rng: ResMut<SimulationRng>,
+    mut market: ResMut<MarketIndex>,
+    mut ships: Query<
+        (
+            Entity,
+            &mut TaskQueue,
+            &mut AutoTradeBehavior,
+            &InSector,
+            &Engine,
+            &Ship,
+        ),
+        ShipIsIdleFilter,
+    >,
+    mut buy_orders: Query<(Entity, &Station, &mut BuyOrders, &InSector)>,
+    mut sell_orders: Query<(Entity, &Station, &mut SellOrders, &InSector)>,
     mut inventories: Query<&mut Inventory>,
+    mut all_wallets: Query<&mut Wallet>,
+    all_owners: Query<&Owner>,
+    mut session_data: ResMut<SessionData>,
+    mut delivery_deadlines: ResMut<DeliveryDeadlines>,
     all_sectors: Query<&Sector>,
     all_transforms: Query<&SimulationTransform>,
 ) {
     let now = simulation_time.now();
 
-    ships
+    let mut idle_ships: Vec<_> = ships
         .iter_mut()
-        .filter(|(_, _, behavior, _)| now.has_passed(behavior.next_idle_update))
-        .for_each(|(ship_entity, mut queue, mut behavior, ship_sector)| {
-            let inventory = inventories.get(ship_entity).unwrap();
-            let plan =
-                TradePlan::search_for_trade_run(inventory.capacity, &buy_orders, &sell_orders);
+        .filter(|(_, _, behavior, ..)| now.has_passed(behavior.next_idle_update))
+        .collect();
+    // Earlier ships get to pick the best trades, so the order needs to be reproducible
+    idle_ships.sort_by_key(|(.., ship)| ship.id());
+
+    idle_ships.into_iter().for_each(
+        |(ship_entity, mut queue, mut behavior, ship_sector, engine, _)| {
+            let ship = TradingShip {
+                entity: ship_entity,
+                sector: ship_sector.get(),
+                storage_capacity: inventories.get(ship_entity).unwrap().capacity,
+                max_speed: engine.max_speed,
+                max_jump_range: behavior.max_jump_range,
+                credits: available_credits(ship_entity, &all_wallets, &all_owners, &session_data),
+            };
+            let ship_owner = all_owners.get(ship_entity).ok();
+            let is_willing_to_trade = |partner: Entity, intent: TradeIntent| {
+                session_data.is_willing_to_trade(all_owners.get(partner).ok(), ship_owner, intent)
+            };
+            let plan = TradePlan::search_for_trade_run(
+                &ship,
+                &market,
+                &is_willing_to_trade,
+                &all_sectors,
+                &all_transforms,
+            );
             let Some(plan) = plan else {
-                behavior.next_idle_update = now.add_seconds(2);
+                behavior.next_idle_update = now.add_milliseconds(rng.jitter(2000));
                 return;
             };
-            let [mut this_inventory, mut seller_inventory, mut buyer_inventory] = inventories
-                .get_many_mut([ship_entity, plan.seller.into(), plan.buyer.into()])
+            let [mut this_inventory, mut seller_inventory] = inventories
+                .get_many_mut([ship_entity, plan.seller.into()])
                 .unwrap();
 
             this_inventory.create_order(plan.item_id, TradeIntent::Buy, plan.amount);
             seller_inventory.create_order(plan.item_id, TradeIntent::Sell, plan.amount);
-
-            this_inventory.create_order(plan.item_id, TradeIntent::Sell, plan.amount);
-            buyer_inventory.create_order(plan.item_id, TradeIntent::Buy, plan.amount);
-
-            update_buy_and_sell_orders_for_entity(
-                TypedEntity::Ship(ship_entity.into()),
-                &this_inventory,
-                &mut buy_orders,
-                &mut sell_orders,
+            plan.reserve_credits(
+                ship_entity,
+                &mut all_wallets,
+                &all_owners,
+                &mut session_data,
             );
             update_buy_and_sell_orders_for_entity(
-                plan.buyer,
-                &buyer_inventory,
+                plan.seller,
+                &seller_inventory,
                 &mut buy_orders,
                 &mut sell_orders,
+                &all_wallets,
+                &mut market,
             );
+
+            plan.create_orders_for_sale(ship_entity, &mut inventories);
             update_buy_and_sell_orders_for_entity(
-                plan.seller,
-                &seller_inventory,
+                TypedEntity::Ship(ship_entity.into()),
+                inventories.get(ship_entity).unwrap(),
                 &mut buy_orders,
                 &mut sell_orders,
+                &all_wallets,
+                &mut market,
             );
+            for stop in &plan.stops {
+                update_buy_and_sell_orders_for_entity(
+                    stop.buyer,
+                    inventories.get(stop.buyer.into()).unwrap(),
+                    &mut buy_orders,
+                    &mut sell_orders,
+                    &all_wallets,
+                    &mut market,
+                );
+            }
 
+            let mut schedule =
+                DeliverySchedule::new(ship_entity, ship.max_speed, now, &mut delivery_deadlines);
             plan.create_tasks_for_purchase(
                 &all_sectors,
                 &all_transforms,
                 ship_entity,
                 ship_sector,
+                &mut schedule,
                 &mut queue,
             );
 
-            plan.create_tasks_for_sale(&all_sectors, &all_transforms, &mut queue);
+            plan.create_tasks_for_sale(&all_sectors, &all_transforms, &mut schedule, &mut queue);
             queue.apply(&mut commands, now, ship_entity);
-        });
+        },
+    );
 }
 
+/// Owned ships spend their faction's credits.
+fn available_credits(
+    ship: Entity,
+    all_wallets: &Query<&mut Wallet>,
+    all_owners: &Query<&Owner>,
+    session_data: &SessionData,
+) -> i64 {
+    if let Ok(owner) = all_owners.get(ship) {
+        session_data
+            .factions
+            .get(&owner.faction)
+            .map_or(0, |faction| faction.wallet.available_credits())
+    } else {
+        all_wallets.get(ship).map_or(0, Wallet::available_credits)
+    }
+}
+
+/// Also updates the [MarketIndex] right away, so ships handled later on within the same tick know about it.
 fn update_buy_and_sell_orders_for_entity(
     entity: TypedEntity,
     inventory: &Inventory,
-    buy_orders: &mut Query<(Entity, &mut BuyOrders, &InSector)>,
-    sell_orders: &mut Query<(Entity, &mut SellOrders, &InSector)>,
+    buy_orders: &mut Query<(Entity, &Station, &mut BuyOrders, &InSector)>,
+    sell_orders: &mut Query<(Entity, &Station, &mut SellOrders, &InSector)>,
+    all_wallets: &Query<&mut Wallet>,
+    market: &mut MarketIndex,
 ) {
-    if let Ok(mut buy_orders) = buy_orders.get_mut(entity.into()) {
-        buy_orders.1.update(inventory);
+    if let Ok((entity, station, mut orders, sector)) = buy_orders.get_mut(entity.into()) {
+        orders.update(inventory);
+        if let Ok(wallet) = all_wallets.get(entity) {
+            orders.limit_to_budget(wallet);
+        }
+        market.update_buy_orders(entity, station.id, sector.get(), &orders);
+    }
+    if let Ok((entity, station, mut orders, sector)) = sell_orders.get_mut(entity.into()) {
+        orders.update(inventory);
+        market.update_sell_orders(entity, station.id, sector.get(), &orders);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants;
+    use crate::game_data::DEBUG_ITEM_ID_A;
+    use crate::persistence::local_hex_position::LocalHexPosition;
+    use crate::persistence::{
+        FactionSaveData, PersistentShipId, PersistentStationId, ShipBehaviorSaveData,
+        UniverseSaveData,
+    };
+    use crate::session_data::FactionId;
+    use crate::simulation::prelude::SimulationTimestamp;
+    use crate::simulation::test_app::SimulationTestApp;
+    use bevy::prelude::Vec2;
+    use hexx::Hex;
+
+    const CENTER: Hex = Hex::new(0, 0);
+    const RIGHT: Hex = Hex::new(1, 0);
+
+    /// Generous, so changes to ship speeds or task durations don't break this immediately.
+    const MAX_TICKS_PER_STEP: u32 = 1200;
+    const MAX_TICKS_PER_TASK: u32 = 300;
+
+    struct TestUniverse {
+        data: UniverseSaveData,
+        ship: PersistentShipId,
+        seller: PersistentStationId,
+        buyer: PersistentStationId,
+    }
+
+    /// A seller and a trader in one sector, and a buyer in the neighboring one.
+    fn seller_and_buyer_one_gate_apart(max_jump_range: u8) -> TestUniverse {
+        let mut data = UniverseSaveData::default();
+        data.sectors.add(CENTER);
+        data.sectors.add(RIGHT);
+        data.gate_pairs.add(
+            LocalHexPosition::new(CENTER, Vec2::new(200.0, 0.0)),
+            LocalHexPosition::new(RIGHT, Vec2::new(-200.0, 0.0)),
+        );
+        let seller = data
+            .stations
+            .add(
+                LocalHexPosition::new(CENTER, Vec2::new(0.0, 100.0)),
+                String::from("Seller"),
+            )
+            .with_sells(vec![DEBUG_ITEM_ID_A])
+            .id;
+        let buyer = data
+            .stations
+            .add(
+                LocalHexPosition::new(RIGHT, Vec2::ZERO),
+                String::from("Buyer"),
+            )
+            .with_buys(vec![DEBUG_ITEM_ID_A])
+            .id;
+        let ship = data
+            .ships
+            .add(
+                LocalHexPosition::new(CENTER, Vec2::ZERO),
+                0.0,
+                String::from("Trader"),
+                ShipBehaviorSaveData::AutoTrade {
+                    next_idle_update: SimulationTimestamp::MIN,
+                    max_jump_range,
+                },
+            )
+            .id;
+
+        TestUniverse {
+            data,
+            ship,
+            seller,
+            buyer,
+        }
+    }
+
+    #[test]
+    fn ships_trade_with_stations_in_neighboring_sectors() {
+        let universe = seller_and_buyer_one_gate_apart(constants::DEFAULT_MAX_TRADE_JUMP_RANGE);
+
+        let mut app = SimulationTestApp::new(universe.data);
+        app.assert_ship_docks_at_station(universe.ship, universe.seller, MAX_TICKS_PER_STEP);
+        app.assert_ship_docks_at_station(universe.ship, universe.buyer, MAX_TICKS_PER_STEP);
+        app.assert_inventory_reaches(
+            universe.buyer,
+            DEBUG_ITEM_ID_A,
+            constants::SHIP_INVENTORY_SIZE,
+            MAX_TICKS_PER_STEP,
+        );
+        app.assert_no_ship_stuck_longer_than(MAX_TICKS_PER_TASK);
+    }
+
+    #[test]
+    fn traders_pay_sellers_and_get_paid_by_buyers() {
+        let universe = seller_and_buyer_one_gate_apart(constants::DEFAULT_MAX_TRADE_JUMP_RANGE);
+
+        let mut app = SimulationTestApp::new(universe.data);
+        let ship = app.ship(universe.ship);
+        let seller = app.station(universe.seller);
+        let buyer = app.station(universe.buyer);
+        let total_credits =
+            |app: &SimulationTestApp| app.credits(ship) + app.credits(seller) + app.credits(buyer);
+        let credits_before = total_credits(&app);
+
+        app.assert_inventory_reaches(
+            universe.buyer,
+            DEBUG_ITEM_ID_A,
+            constants::SHIP_INVENTORY_SIZE,
+            MAX_TICKS_PER_STEP,
+        );
+
+        assert!(app.credits(seller) > constants::STATION_STARTING_CREDITS);
+        assert!(app.credits(buyer) < constants::STATION_STARTING_CREDITS);
+        assert!(app.credits(ship) > constants::SHIP_STARTING_CREDITS);
+        assert_eq!(total_credits(&app), credits_before);
+    }
+
+    #[test]
+    fn credits_stay_reserved_until_the_wares_have_been_paid_for() {
+        let universe = seller_and_buyer_one_gate_apart(constants::DEFAULT_MAX_TRADE_JUMP_RANGE);
+
+        let mut app = SimulationTestApp::new(universe.data);
+        let ship = app.ship(universe.ship);
+        let buyer = app.station(universe.buyer);
+
+        app.assert_ship_docks_at_station(universe.ship, universe.seller, MAX_TICKS_PER_STEP);
+        assert!(app.available_credits(ship) < app.credits(ship));
+        assert!(app.available_credits(buyer) < app.credits(buyer));
+
+        app.assert_inventory_reaches(
+            universe.buyer,
+            DEBUG_ITEM_ID_A,
+            constants::SHIP_INVENTORY_SIZE,
+            MAX_TICKS_PER_STEP,
+        );
+        assert_eq!(app.available_credits(ship), app.credits(ship));
+        assert_eq!(app.available_credits(buyer), app.credits(buyer));
+    }
+
+    #[test]
+    fn owned_traders_trade_on_their_factions_account() {
+        const FACTION: FactionId = 1;
+        let mut universe = seller_and_buyer_one_gate_apart(constants::DEFAULT_MAX_TRADE_JUMP_RANGE);
+        universe.data.session.factions.push(FactionSaveData {
+            id: FACTION,
+            name: String::from("Traders"),
+            credits: constants::SHIP_STARTING_CREDITS,
+            required_favor: constants::DEFAULT_REQUIRED_FAVOR,
+        });
+        universe.data.ships.data[0].with_owner(FACTION);
+
+        let mut app = SimulationTestApp::new(universe.data);
+        app.assert_inventory_reaches(
+            universe.buyer,
+            DEBUG_ITEM_ID_A,
+            constants::SHIP_INVENTORY_SIZE,
+            MAX_TICKS_PER_STEP,
+        );
+
+        assert!(app.faction_credits(FACTION) > constants::SHIP_STARTING_CREDITS);
     }
-    if let Ok(mut sell_orders) = sell_orders.get_mut(entity.into()) {
-        sell_orders.1.update(inventory);
+
+    const TRADERS: FactionId = 1;
+    const SELLERS: FactionId = 2;
+    const BUYERS: FactionId = 3;
+
+    /// Puts the trader, the seller and the buyer into factions of their own.
+    fn owned_by_separate_factions(universe: &mut TestUniverse, required_favor: i64) {
+        for (id, name) in [
+            (TRADERS, "Traders"),
+            (SELLERS, "Sellers"),
+            (BUYERS, "Buyers"),
+        ] {
+            universe.data.session.factions.push(FactionSaveData {
+                id,
+                name: String::from(name),
+                credits: constants::SHIP_STARTING_CREDITS,
+                required_favor,
+            });
+        }
+        universe.data.ships.data[0].with_owner(TRADERS);
+        universe.data.stations.data[0].with_owner(SELLERS);
+        universe.data.stations.data[1].with_owner(BUYERS);
+    }
+
+    #[test]
+    fn buying_spends_favor_and_selling_earns_it() {
+        let mut universe = seller_and_buyer_one_gate_apart(constants::DEFAULT_MAX_TRADE_JUMP_RANGE);
+        owned_by_separate_factions(&mut universe, constants::DEFAULT_REQUIRED_FAVOR);
+
+        let mut app = SimulationTestApp::new(universe.data);
+        app.assert_inventory_reaches(
+            universe.buyer,
+            DEBUG_ITEM_ID_A,
+            constants::SHIP_INVENTORY_SIZE,
+            MAX_TICKS_PER_STEP,
+        );
+
+        assert!(app.favor(SELLERS, TRADERS) < 0);
+        assert!(app.favor(BUYERS, TRADERS) > 0);
+        assert_eq!(app.favor(TRADERS, SELLERS), 0);
+    }
+
+    #[test]
+    fn factions_refuse_to_trade_without_enough_favor() {
+        let mut universe = seller_and_buyer_one_gate_apart(constants::DEFAULT_MAX_TRADE_JUMP_RANGE);
+        // Nobody has any favor yet, so the traders can only sell to other factions, but not buy from them
+        owned_by_separate_factions(&mut universe, 1);
+        // Further away than the other seller, so it only gets picked if that one refuses to sell
+        universe
+            .data
+            .stations
+            .add(
+                LocalHexPosition::new(CENTER, Vec2::new(0.0, -150.0)),
+                String::from("Own seller"),
+            )
+            .with_sells(vec![DEBUG_ITEM_ID_A])
+            .with_owner(TRADERS);
+
+        let mut app = SimulationTestApp::new(universe.data);
+        app.assert_inventory_reaches(
+            universe.buyer,
+            DEBUG_ITEM_ID_A,
+            constants::SHIP_INVENTORY_SIZE,
+            MAX_TICKS_PER_STEP,
+        );
+
+        assert_eq!(app.favor(SELLERS, TRADERS), 0);
+        assert!(app.favor(BUYERS, TRADERS) > 0);
+    }
+
+    #[test]
+    fn ships_ignore_buyers_beyond_their_jump_range() {
+        let universe = seller_and_buyer_one_gate_apart(0);
+
+        let mut app = SimulationTestApp::new(universe.data);
+        app.run_ticks(MAX_TICKS_PER_STEP);
+
+        let buyer = app.station(universe.buyer);
+        assert_eq!(app.inventory_amount(buyer, DEBUG_ITEM_ID_A), 0);
     }
 }
@@ -10,6 +10,7 @@ pub mod auto_trade;
 pub enum BehaviorBuilder {
     AutoTrade {
         next_idle_update: SimulationTimestamp,
+        max_jump_range: u8,
     },
     AutoMine {
         next_idle_update: SimulationTimestamp,
@@ -27,11 +28,13 @@ impl BehaviorBuilder {
     // the end of https://github.com/bevyengine/bevy/discussions/11409
     pub fn build_and_add_default_component(&self, mut entity_commands: EntityCommands) {
         match self {
-            BehaviorBuilder::AutoTrade { next_idle_update } => {
-                entity_commands.insert(AutoTradeBehavior {
-                    next_idle_update: *next_idle_update,
-                })
-            }
+            BehaviorBuilder::AutoTrade {
+                next_idle_update,
+                max_jump_range,
+            } => entity_commands.insert(AutoTradeBehavior {
+                next_idle_update: *next_idle_update,
+                max_jump_range: *max_jump_range,
+            }),
             BehaviorBuilder::AutoMine {
                 next_idle_update,
                 state,
@@ -1,4 +1,4 @@
-mod behaviors;
+pub mod behaviors;
 mod plugin;
 mod ship_is_idle_filter;
 mod stop_idle_ships;
@@ -8,6 +8,7 @@ mod task_queue;
 mod task_result;
 mod tasks;
 
+pub use behaviors::auto_harvest::AutoHarvestBehavior;
 pub use behaviors::auto_mine::{AutoMineBehavior, AutoMineState};
 pub use behaviors::auto_trade::AutoTradeBehavior;
 pub use behaviors::BehaviorBuilder;
@@ -17,3 +18,6 @@ pub use task_inside_queue::TaskInsideQueue;
 pub use task_queue::TaskQueue;
 pub use tasks::AwaitingSignal;
 pub use tasks::MoveToEntity;
+pub use tasks::{
+    DeliveryDeadlines, ExchangeWares, GateTraversalState, HarvestGas, MineAsteroid, Undock, UseGate,
+};
@@ -2,8 +2,8 @@ use crate::simulation::asteroids;
 use crate::simulation::ship_ai::{behaviors, stop_idle_ships};
 use crate::simulation::ship_ai::task_finished_event::TaskFinishedEvent;
 use crate::simulation::ship_ai::tasks::{
-    AwaitingSignal, DockAtEntity, ExchangeWares, HarvestGas, MineAsteroid, MoveToEntity,
-    RequestAccess, Undock, UseGate,
+    AwaitingSignal, DeliveryDeadlines, DockAtEntity, ExchangeWares, HarvestGas, MineAsteroid,
+    MoveToEntity, RequestAccess, Undock, UseGate,
 };
 use crate::states::SimulationState;
 use bevy::app::App;
@@ -21,6 +21,7 @@ impl Plugin for ShipAiPlugin {
         app.add_event::<TaskFinishedEvent<MineAsteroid>>();
         app.add_event::<TaskFinishedEvent<HarvestGas>>();
         app.add_event::<TaskFinishedEvent<AwaitingSignal>>();
+        app.init_resource::<DeliveryDeadlines>();
         app.add_systems(
             FixedUpdate,
             (
@@ -50,6 +51,12 @@ impl Plugin for ShipAiPlugin {
             )
                 .run_if(in_state(SimulationState::Running)),
         );
+        app.add_systems(
+            FixedUpdate,
+            ExchangeWares::punish_expired_deliveries
+                .before(ExchangeWares::complete_tasks)
+                .run_if(in_state(SimulationState::Running)),
+        );
         app.add_systems(
             FixedPostUpdate,
             (ExchangeWares::on_task_creation, UseGate::on_task_creation, Undock::on_task_creation)
@@ -17,6 +17,8 @@ pub enum TaskInsideQueue {
     ExchangeWares {
         target: TypedEntity,
         data: ExchangeWareData,
+        /// Finishing later than this costs favor with the owner of `target`.
+        deadline: SimulationTimestamp,
     },
     MoveToEntity {
         target: TypedEntity,
@@ -43,11 +45,16 @@ impl TaskInsideQueue {
         now: CurrentSimulationTimestamp,
     ) {
         match self {
-            TaskInsideQueue::ExchangeWares { target, data } => {
+            TaskInsideQueue::ExchangeWares {
+                target,
+                data,
+                deadline,
+            } => {
                 entity_commands.insert(tasks::ExchangeWares {
                     finishes_at: SimulationTimestamp::MAX,
                     target: *target,
                     data: *data,
+                    deadline: *deadline,
                 });
             }
             TaskInsideQueue::MoveToEntity {
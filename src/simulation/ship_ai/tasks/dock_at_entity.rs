@@ -1,5 +1,6 @@
 use crate::components;
 use crate::components::Engine;
+use crate::persistence::ShipIdMap;
 use crate::simulation::physics::ShipVelocity;
 use crate::simulation::prelude::SimulationTime;
 use crate::simulation::ship_ai::task_finished_event::TaskFinishedEvent;
@@ -27,6 +28,7 @@ impl DockAtEntity {
 
     pub fn run_tasks(
         event_writer: EventWriter<TaskFinishedEvent<Self>>,
+        ship_id_map: Res<ShipIdMap>,
         time: Res<Time>,
         mut ships: Query<(Entity, &Self, &Engine, &mut ShipVelocity)>,
         all_transforms: Query<&SimulationTransform>,
@@ -54,21 +56,22 @@ impl DockAtEntity {
                 },
             );
 
-        send_completion_events(event_writer, task_completions);
+        send_completion_events(event_writer, task_completions, &ship_id_map);
     }
 
     pub fn complete_tasks(
         mut commands: Commands,
         mut event_reader: EventReader<TaskFinishedEvent<Self>>,
-        mut all_ships_with_task: Query<(&mut TaskQueue, &mut Visibility, &Self)>,
+        mut all_ships_with_task: Query<(&mut TaskQueue, Option<&mut Visibility>, &Self)>,
         simulation_time: Res<SimulationTime>,
     ) {
         let now = simulation_time.now();
 
         for event in event_reader.read() {
-            if let Ok((mut queue, mut visibility, task)) = all_ships_with_task.get_mut(event.entity)
-            {
-                *visibility = Visibility::Hidden;
+            if let Ok((mut queue, visibility, task)) = all_ships_with_task.get_mut(event.entity) {
+                if let Some(mut visibility) = visibility {
+                    *visibility = Visibility::Hidden;
+                }
 
                 let mut entity_commands = commands.entity(event.entity);
                 entity_commands.insert(components::IsDocked::new(task.target));
@@ -1,23 +1,61 @@
-use crate::components::{InteractionQueue, Inventory};
+use crate::components::{ship_wallet, InteractionQueue, Inventory, Owner, Wallet};
+use crate::persistence::ShipIdMap;
+use crate::session_data::{FactionId, SessionData};
 use crate::simulation::prelude::{
     AwaitingSignal, CurrentSimulationTimestamp, SimulationTime, SimulationTimestamp,
 };
 use crate::simulation::production::InventoryUpdateForProductionEvent;
 use crate::simulation::ship_ai::task_finished_event::TaskFinishedEvent;
+use crate::simulation::ship_ai::task_inside_queue::TaskInsideQueue;
 use crate::simulation::ship_ai::task_queue::TaskQueue;
 use crate::simulation::ship_ai::task_result::TaskResult;
 use crate::simulation::ship_ai::tasks;
 use crate::simulation::ship_ai::tasks::{finish_interaction, send_completion_events, DockAtEntity};
 use crate::utils::ExchangeWareData;
-use crate::utils::{TradeIntent, TypedEntity};
-use bevy::prelude::{error, Commands, Component, Entity, EventReader, EventWriter, Query, Res};
+use crate::utils::{ShipEntity, TradeIntent, TypedEntity};
+use bevy::prelude::{
+    error, Commands, Component, Entity, EventReader, EventWriter, Mut, Query, Res, ResMut, Resource,
+};
+use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
 
+/// Ships which might still have to exchange wares before a deadline, ordered by that deadline.
+/// Nothing gets removed when an exchange happens in time, the ship's [TaskQueue] is checked once its deadline passed.
+#[derive(Resource, Default)]
+pub struct DeliveryDeadlines {
+    ships: BTreeMap<SimulationTimestamp, Vec<ShipEntity>>,
+}
+
+impl DeliveryDeadlines {
+    pub fn add(&mut self, deadline: SimulationTimestamp, ship: ShipEntity) {
+        let ships = self.ships.entry(deadline).or_default();
+        if !ships.contains(&ship) {
+            ships.push(ship);
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct ExchangeWares {
     pub finishes_at: SimulationTimestamp,
     pub target: TypedEntity,
     pub data: ExchangeWareData,
+    /// Finishing later than this costs favor with the owner of `target`.
+    pub deadline: SimulationTimestamp,
+}
+
+/// The faction owning `target` and the one owning `ship`, unless they are the same or either one is independent.
+fn trade_partner_factions(
+    ship: Entity,
+    target: TypedEntity,
+    all_owners: &Query<&Owner>,
+) -> Option<(FactionId, FactionId)> {
+    let (Ok(ship_owner), Ok(target_owner)) = (all_owners.get(ship), all_owners.get(target.into()))
+    else {
+        return None;
+    };
+
+    (ship_owner != target_owner).then_some((target_owner.faction, ship_owner.faction))
 }
 
 impl ExchangeWares {
@@ -29,27 +67,168 @@ impl ExchangeWares {
         }
     }
 
+    /// Whether the ship is the one paying, and how many credits are paid.
+    fn payment(&self) -> (bool, i64) {
+        let ship_pays = matches!(self.data, ExchangeWareData::Buy(..));
+        (ship_pays, self.data.value())
+    }
+
+    /// The payer reserved its credits when the trade was planned, they are released whether the exchange happens or not.
+    fn release_reserved_credits(
+        &self,
+        this_entity: Entity,
+        all_wallets: &mut Query<&mut Wallet>,
+        all_owners: &Query<&Owner>,
+        session_data: &mut SessionData,
+    ) {
+        let (ship_pays, credits) = self.payment();
+        let payer = if ship_pays {
+            ship_wallet(this_entity, all_wallets, all_owners, session_data)
+        } else {
+            all_wallets
+                .get_mut(self.target.into())
+                .ok()
+                .map(Mut::into_inner)
+        };
+
+        if let Some(payer) = payer {
+            payer.release(credits);
+        }
+    }
+
+    /// Pays the price which was agreed upon when the trade was planned.
+    /// Owned ships pay and get paid through their faction's wallet.
+    fn pay(
+        &self,
+        this_entity: Entity,
+        all_wallets: &mut Query<&mut Wallet>,
+        all_owners: &Query<&Owner>,
+        session_data: &mut SessionData,
+    ) -> TaskResult {
+        let (ship_pays, credits) = self.payment();
+        let transfer = |ship_wallet: &mut Wallet, target_wallet: &mut Wallet| {
+            if ship_pays {
+                Wallet::transfer(ship_wallet, target_wallet, credits);
+            } else {
+                Wallet::transfer(target_wallet, ship_wallet, credits);
+            }
+        };
+
+        let target: Entity = self.target.into();
+        if let Ok(owner) = all_owners.get(this_entity) {
+            match (
+                session_data.factions.get_mut(&owner.faction),
+                all_wallets.get_mut(target),
+            ) {
+                (Some(faction), Ok(mut target_wallet)) => {
+                    transfer(&mut faction.wallet, &mut target_wallet);
+                    TaskResult::Finished
+                }
+                _ => {
+                    error!(
+                        "Failed to pay for ware exchange between faction {} and {target}!",
+                        owner.faction
+                    );
+                    TaskResult::Aborted
+                }
+            }
+        } else {
+            match all_wallets.get_many_mut([this_entity, target]) {
+                Ok([mut ship_wallet, mut target_wallet]) => {
+                    transfer(&mut ship_wallet, &mut target_wallet);
+                    TaskResult::Finished
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to pay for ware exchange between {this_entity} and {target}: {e:?}"
+                    );
+                    TaskResult::Aborted
+                }
+            }
+        }
+    }
+
+    /// Selling goods to another faction earns favor with it, buying goods from it spends favor.
+    /// Exchanges which don't happen at all cost as much favor as missing their deadline, unless that already passed,
+    /// in which case [Self::punish_expired_deliveries] took care of it.
+    fn update_favor(
+        &self,
+        this_entity: Entity,
+        all_owners: &Query<&Owner>,
+        session_data: &mut SessionData,
+        now: CurrentSimulationTimestamp,
+        exchanged: bool,
+    ) {
+        let Some((faction, towards)) = trade_partner_factions(this_entity, self.target, all_owners)
+        else {
+            return;
+        };
+
+        let value = self.data.value();
+        let relations = &mut session_data.relations;
+        if !exchanged {
+            if now.has_not_passed(self.deadline) {
+                relations.punish(faction, towards, value);
+            }
+            return;
+        }
+
+        match self.data {
+            ExchangeWareData::Buy(..) => relations.spend(faction, towards, value),
+            ExchangeWareData::Sell(..) => relations.earn(faction, towards, value),
+        }
+    }
+
+    /// Gives back the storage space and items which have been reserved for an exchange which won't happen.
+    fn cancel_orders(&self, this_entity: Entity, all_storages: &mut Query<&mut Inventory>) {
+        let (item_id, amount, this_intent, other_intent) = match self.data {
+            ExchangeWareData::Buy(item_id, amount, _) => {
+                (item_id, amount, TradeIntent::Buy, TradeIntent::Sell)
+            }
+            ExchangeWareData::Sell(item_id, amount, _) => {
+                (item_id, amount, TradeIntent::Sell, TradeIntent::Buy)
+            }
+        };
+
+        if let Ok(mut inventory) = all_storages.get_mut(this_entity) {
+            inventory.cancel_order(item_id, this_intent, amount);
+        }
+        if let Ok(mut inventory) = all_storages.get_mut(self.target.into()) {
+            inventory.cancel_order(item_id, other_intent, amount);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn complete(
         &self,
         this_entity: Entity,
         all_storages: &mut Query<&mut Inventory>,
+        all_wallets: &mut Query<&mut Wallet>,
+        all_owners: &Query<&Owner>,
+        session_data: &mut SessionData,
         event_writer: &mut EventWriter<InventoryUpdateForProductionEvent>,
+        now: CurrentSimulationTimestamp,
     ) -> TaskResult {
-        match all_storages.get_many_mut([this_entity, self.target.into()]) {
+        self.release_reserved_credits(this_entity, all_wallets, all_owners, session_data);
+
+        let result = match all_storages.get_many_mut([this_entity, self.target.into()]) {
             Ok([mut this_inv, mut other_inv]) => {
-                match self.data {
-                    ExchangeWareData::Buy(item_id, amount) => {
-                        this_inv.complete_order(item_id, TradeIntent::Buy, amount);
-                        other_inv.complete_order(item_id, TradeIntent::Sell, amount);
-                    }
-                    ExchangeWareData::Sell(item_id, amount) => {
-                        this_inv.complete_order(item_id, TradeIntent::Sell, amount);
-                        other_inv.complete_order(item_id, TradeIntent::Buy, amount);
+                match self.pay(this_entity, all_wallets, all_owners, session_data) {
+                    TaskResult::Aborted => TaskResult::Aborted,
+                    _ => {
+                        match self.data {
+                            ExchangeWareData::Buy(item_id, amount, _) => {
+                                this_inv.complete_order(item_id, TradeIntent::Buy, amount);
+                                other_inv.complete_order(item_id, TradeIntent::Sell, amount);
+                            }
+                            ExchangeWareData::Sell(item_id, amount, _) => {
+                                this_inv.complete_order(item_id, TradeIntent::Sell, amount);
+                                other_inv.complete_order(item_id, TradeIntent::Buy, amount);
+                            }
+                        }
+                        TaskResult::Finished
                     }
                 }
-                event_writer.send(InventoryUpdateForProductionEvent::new(this_entity));
-                event_writer.send(InventoryUpdateForProductionEvent::new(self.target.into()));
-                TaskResult::Finished
             }
             Err(e) => {
                 error!(
@@ -58,11 +237,70 @@ impl ExchangeWares {
                 );
                 TaskResult::Aborted
             }
+        };
+
+        let exchanged = matches!(result, TaskResult::Finished);
+        if !exchanged {
+            self.cancel_orders(this_entity, all_storages);
+        }
+        self.update_favor(this_entity, all_owners, session_data, now, exchanged);
+        event_writer.send(InventoryUpdateForProductionEvent::new(this_entity));
+        event_writer.send(InventoryUpdateForProductionEvent::new(self.target.into()));
+        result
+    }
+
+    /// Every delivery which is still waiting inside a ship's [TaskQueue] once its deadline has passed costs favor with
+    /// the owner of its trade partner, whether it happens later on or not.
+    pub fn punish_expired_deliveries(
+        simulation_time: Res<SimulationTime>,
+        mut delivery_deadlines: ResMut<DeliveryDeadlines>,
+        ship_id_map: Res<ShipIdMap>,
+        all_queues: Query<&TaskQueue>,
+        all_owners: Query<&Owner>,
+        mut session_data: ResMut<SessionData>,
+    ) {
+        let now = simulation_time.now();
+        while let Some(entry) = delivery_deadlines.ships.first_entry() {
+            if now.has_not_passed(*entry.key()) {
+                break;
+            }
+
+            let (deadline, mut ships) = entry.remove_entry();
+            // Punishments aren't commutative, so their order must not depend on entity allocation
+            ships.sort_by_key(|ship| ship_id_map.get_id(ship).copied());
+            for ship in ships {
+                let Ok(queue) = all_queues.get(ship.into()) else {
+                    continue;
+                };
+
+                for task in queue.iter() {
+                    let TaskInsideQueue::ExchangeWares {
+                        target,
+                        data,
+                        deadline: task_deadline,
+                    } = task
+                    else {
+                        continue;
+                    };
+                    if *task_deadline != deadline {
+                        continue;
+                    }
+
+                    if let Some((faction, towards)) =
+                        trade_partner_factions(ship.into(), *target, &all_owners)
+                    {
+                        session_data
+                            .relations
+                            .punish(faction, towards, data.value());
+                    }
+                }
+            }
         }
     }
 
     pub fn run_tasks(
         event_writer: EventWriter<TaskFinishedEvent<Self>>,
+        ship_id_map: Res<ShipIdMap>,
         simulation_time: Res<SimulationTime>,
         ships: Query<(Entity, &Self)>,
     ) {
@@ -79,14 +317,18 @@ impl ExchangeWares {
                     .push(TaskFinishedEvent::<Self>::new(entity)),
             });
 
-        send_completion_events(event_writer, task_completions);
+        send_completion_events(event_writer, task_completions, &ship_id_map);
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn complete_tasks(
         mut commands: Commands,
         mut event_reader: EventReader<TaskFinishedEvent<Self>>,
         mut all_ships_with_task: Query<(&mut TaskQueue, &Self)>,
         mut all_storages: Query<&mut Inventory>,
+        mut all_wallets: Query<&mut Wallet>,
+        all_owners: Query<&Owner>,
+        mut session_data: ResMut<SessionData>,
         mut event_writer: EventWriter<InventoryUpdateForProductionEvent>,
         simulation_time: Res<SimulationTime>,
     ) {
@@ -94,7 +336,15 @@ impl ExchangeWares {
 
         for event in event_reader.read() {
             if let Ok((mut queue, task)) = all_ships_with_task.get_mut(event.entity) {
-                task.complete(event.entity, &mut all_storages, &mut event_writer);
+                task.complete(
+                    event.entity,
+                    &mut all_storages,
+                    &mut all_wallets,
+                    &all_owners,
+                    &mut session_data,
+                    &mut event_writer,
+                    now,
+                );
 
                 tasks::remove_task_and_add_next_in_queue::<Self>(
                     &mut commands,
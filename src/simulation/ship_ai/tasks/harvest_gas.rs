@@ -1,5 +1,6 @@
 use crate::components::{InteractionQueue, Inventory};
 use crate::game_data::DEBUG_ITEM_ID_ORE;
+use crate::persistence::ShipIdMap;
 use crate::simulation::prelude::{
     AwaitingSignal, CurrentSimulationTimestamp, Milliseconds, SimulationTime, SimulationTimestamp,
 };
@@ -24,7 +25,7 @@ enum TaskResult {
 #[derive(Component)]
 pub struct HarvestGas {
     pub target: PlanetEntity,
-    next_update: SimulationTimestamp,
+    pub next_update: SimulationTimestamp,
 }
 
 impl HarvestGas {
@@ -57,6 +58,7 @@ impl HarvestGas {
 
     pub fn run_tasks(
         event_writer: EventWriter<TaskFinishedEvent<Self>>,
+        ship_id_map: Res<ShipIdMap>,
         simulation_time: Res<SimulationTime>,
         mut ships: Query<(Entity, &mut Self, &mut Inventory)>,
     ) {
@@ -76,7 +78,7 @@ impl HarvestGas {
                 },
             );
 
-        send_completion_events(event_writer, task_completions);
+        send_completion_events(event_writer, task_completions, &ship_id_map);
     }
 
     pub fn complete_tasks(
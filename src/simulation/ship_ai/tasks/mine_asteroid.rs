@@ -1,5 +1,6 @@
 use crate::components::{Asteroid, Inventory};
 use crate::game_data::DEBUG_ITEM_ID_ORE;
+use crate::persistence::ShipIdMap;
 use crate::simulation::asteroids::AsteroidWasFullyMinedEvent;
 use crate::simulation::prelude::{
     CurrentSimulationTimestamp, Milliseconds, SimulationTime, SimulationTimestamp,
@@ -26,8 +27,8 @@ enum TaskResult {
 #[derive(Component)]
 pub struct MineAsteroid {
     pub target: AsteroidEntity,
-    next_update: SimulationTimestamp,
-    reserved_ore_amount: u32,
+    pub next_update: SimulationTimestamp,
+    pub reserved_ore_amount: u32,
 }
 
 impl MineAsteroid {
@@ -78,6 +79,7 @@ impl MineAsteroid {
 
     pub fn run_tasks(
         event_writer: EventWriter<TaskFinishedEvent<Self>>,
+        ship_id_map: Res<ShipIdMap>,
         simulation_time: Res<SimulationTime>,
         mut ships: Query<(Entity, &mut Self, &mut Inventory)>,
         mut all_asteroids: Query<(&mut Asteroid, &mut SimulationTransform)>,
@@ -139,7 +141,7 @@ impl MineAsteroid {
             }
         }
 
-        send_completion_events(event_writer, task_completions);
+        send_completion_events(event_writer, task_completions, &ship_id_map);
     }
 
     pub fn complete_tasks(
@@ -12,23 +12,30 @@ mod request_access;
 mod undock;
 mod use_gate;
 
+use crate::persistence::ShipIdMap;
 use crate::simulation::prelude::{CurrentSimulationTimestamp, TaskFinishedEvent, TaskQueue};
 
 use crate::components::InteractionQueue;
 pub use {
-    awaiting_signal::AwaitingSignal, dock_at_entity::DockAtEntity, exchange_wares::ExchangeWares,
-    harvest_gas::HarvestGas, mine_asteroid::MineAsteroid, move_to_entity::MoveToEntity,
-    request_access::RequestAccess, undock::Undock, use_gate::UseGate,
+    awaiting_signal::AwaitingSignal, dock_at_entity::DockAtEntity,
+    exchange_wares::DeliveryDeadlines, exchange_wares::ExchangeWares, harvest_gas::HarvestGas,
+    mine_asteroid::MineAsteroid, move_to_entity::MoveToEntity, request_access::RequestAccess,
+    undock::Undock, use_gate::GateTraversalState, use_gate::UseGate,
 };
 
 pub fn send_completion_events<T: Component>(
     mut event_writer: EventWriter<TaskFinishedEvent<T>>,
     task_completions: Arc<Mutex<Vec<TaskFinishedEvent<T>>>>,
+    ship_id_map: &ShipIdMap,
 ) {
     match Arc::try_unwrap(task_completions) {
         Ok(task_completions) => {
-            let batch = task_completions.into_inner().unwrap();
+            let mut batch = task_completions.into_inner().unwrap();
             if !batch.is_empty() {
+                // Tasks are run in parallel, so completions arrive in whatever order the threads finished in.
+                // Their order decides things like who gets to interact with a station first,
+                // so it must not depend on how entities have been allocated within this session.
+                batch.sort_by_key(|event| ship_id_map.get_id(&event.entity.into()).copied());
                 event_writer.send_batch(batch);
             }
         }
@@ -1,4 +1,5 @@
 use crate::components::Engine;
+use crate::persistence::ShipIdMap;
 use crate::simulation::physics::ShipVelocity;
 use crate::simulation::prelude::SimulationTime;
 use crate::simulation::ship_ai::task_finished_event::TaskFinishedEvent;
@@ -96,6 +97,7 @@ pub fn move_to_entity(
 impl MoveToEntity {
     pub fn run_tasks(
         event_writer: EventWriter<TaskFinishedEvent<Self>>,
+        ship_id_map: Res<ShipIdMap>,
         time: Res<Time>,
         mut ships: Query<(Entity, &Self, &Engine, &mut ShipVelocity)>,
         all_transforms: Query<&SimulationTransform>,
@@ -124,7 +126,7 @@ impl MoveToEntity {
                 }
             });
 
-        send_completion_events(event_writer, task_completions);
+        send_completion_events(event_writer, task_completions, &ship_id_map);
     }
 
     pub fn complete_tasks(
@@ -1,5 +1,6 @@
 use crate::components::{Engine, InteractionQueue, IsDocked};
 use crate::constants;
+use crate::persistence::ShipIdMap;
 use crate::simulation::physics::ShipVelocity;
 use crate::simulation::prelude::SimulationTime;
 use crate::simulation::ship_ai::task_finished_event::TaskFinishedEvent;
@@ -16,7 +17,7 @@ use std::sync::{Arc, Mutex};
 
 #[derive(Component)]
 pub struct Undock {
-    start_position: Option<Vec2>,
+    pub start_position: Option<Vec2>,
 }
 
 impl Undock {
@@ -50,6 +51,7 @@ impl Undock {
 
     pub fn run_tasks(
         event_writer: EventWriter<TaskFinishedEvent<Self>>,
+        ship_id_map: Res<ShipIdMap>,
         time: Res<Time>,
         mut ships: Query<(
             Entity,
@@ -74,7 +76,7 @@ impl Undock {
                 }
             });
 
-        send_completion_events(event_writer, task_completions);
+        send_completion_events(event_writer, task_completions, &ship_id_map);
     }
 
     #[allow(clippy::type_complexity)]
@@ -84,23 +86,23 @@ impl Undock {
             Entity,
             &mut Self,
             &SimulationTransform,
-            &mut Visibility,
+            Option<&mut Visibility>,
             &IsDocked,
         )>,
         mut interaction_queues: Query<&mut InteractionQueue>,
         mut signal_writer: EventWriter<TaskFinishedEvent<AwaitingSignal>>,
     ) {
         // Compared to the other task_creation thingies we can cheat a little since we got IsDocked as a useful marker
-        for (entity, mut task, transform, mut visibility, is_docked) in
-            all_ships_with_task.iter_mut()
-        {
+        for (entity, mut task, transform, visibility, is_docked) in all_ships_with_task.iter_mut() {
             finish_interaction(
                 is_docked.at.into(),
                 &mut interaction_queues,
                 &mut signal_writer,
             );
 
-            *visibility = Visibility::Inherited;
+            if let Some(mut visibility) = visibility {
+                *visibility = Visibility::Inherited;
+            }
             task.start_position = Some(transform.translation);
             commands.entity(entity).remove::<IsDocked>();
         }
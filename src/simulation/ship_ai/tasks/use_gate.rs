@@ -1,3 +1,4 @@
+use crate::persistence::ShipIdMap;
 use bevy::prelude::{
     error, Commands, Component, CubicCurve, Entity, EventReader, EventWriter, Query, Res, Time,
     Vec2, With,
@@ -104,6 +105,7 @@ impl UseGate {
 
     pub fn run_tasks(
         event_writer: EventWriter<TaskFinishedEvent<Self>>,
+        ship_id_map: Res<ShipIdMap>,
         time: Res<Time>,
         mut ships: Query<(Entity, &mut Self, &mut SimulationTransform)>,
         transit_curve_query: Query<&Gate>,
@@ -123,7 +125,7 @@ impl UseGate {
                 }
             });
 
-        send_completion_events(event_writer, task_completions);
+        send_completion_events(event_writer, task_completions, &ship_id_map);
     }
 
     pub fn complete_tasks(
@@ -0,0 +1,275 @@
+//! Pausing, fast-forwarding and stepping through the simulation.
+//!
+//! Speeds are applied by scaling [Time<Virtual>], so more [FixedMain] iterations run per frame while the
+//! fixed timestep and thereby [crate::simulation::prelude::SimulationTime] advance exactly as they would at normal speed.
+
+use crate::states::{ApplicationState, SimulationState};
+use bevy::app::{App, FixedMain, Plugin, PreUpdate, Update};
+use bevy::input::ButtonInput;
+use bevy::prelude::{
+    in_state, Event, EventReader, EventWriter, IntoSystemConfigs, KeyCode, NextState, Res, ResMut,
+    Resource, State, Time, Virtual, World,
+};
+use bevy::time::Fixed;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The speeds which can be selected via hotkeys and the GUI.
+pub const SIMULATION_SPEEDS: [f32; 4] = [1.0, 2.0, 5.0, 10.0];
+const SPEED_HOTKEYS: [KeyCode; 4] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+];
+/// Bevy's default maximum delta of [Time<Virtual>], used at normal speed.
+/// Faster speeds need to scale this up, otherwise long frames would slow them down more than normal speed.
+const MAX_DELTA_AT_NORMAL_SPEED: Duration = Duration::from_millis(250);
+
+/// Send this event to change how fast the simulation is running.
+#[derive(Event, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum SimulationSpeedEvent {
+    TogglePause,
+    SetSpeed(f32),
+    /// Advances the simulation by exactly one tick. Ignored unless the simulation is paused.
+    AdvanceSingleTick,
+}
+
+pub struct SimulationSpeedPlugin;
+impl Plugin for SimulationSpeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingSingleTick>()
+            .add_event::<SimulationSpeedEvent>()
+            .add_systems(
+                PreUpdate,
+                advance_single_tick.run_if(in_state(SimulationState::Paused)),
+            )
+            .add_systems(
+                Update,
+                (send_speed_events_on_hotkey, handle_speed_events)
+                    .chain()
+                    .run_if(in_state(ApplicationState::InGame)),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+struct PendingSingleTick(bool);
+
+fn send_speed_events_on_hotkey(
+    input: Res<ButtonInput<KeyCode>>,
+    mut events: EventWriter<SimulationSpeedEvent>,
+) {
+    if input.just_pressed(KeyCode::Space) {
+        events.send(SimulationSpeedEvent::TogglePause);
+    }
+    if input.just_pressed(KeyCode::Period) {
+        events.send(SimulationSpeedEvent::AdvanceSingleTick);
+    }
+    for (key, speed) in SPEED_HOTKEYS.iter().zip(SIMULATION_SPEEDS) {
+        if input.just_pressed(*key) {
+            events.send(SimulationSpeedEvent::SetSpeed(speed));
+        }
+    }
+}
+
+fn handle_speed_events(
+    mut events: EventReader<SimulationSpeedEvent>,
+    current_state: Res<State<SimulationState>>,
+    mut next_state: ResMut<NextState<SimulationState>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut pending_single_tick: ResMut<PendingSingleTick>,
+) {
+    // Multiple toggles within the same frame need to know about each other
+    let mut state = *current_state.get();
+    for event in events.read() {
+        match event {
+            SimulationSpeedEvent::TogglePause => {
+                state = match state {
+                    SimulationState::Running => {
+                        time.pause();
+                        SimulationState::Paused
+                    }
+                    SimulationState::Paused => {
+                        time.unpause();
+                        SimulationState::Running
+                    }
+                };
+                next_state.set(state);
+            }
+            SimulationSpeedEvent::SetSpeed(speed) => {
+                time.set_relative_speed(*speed);
+                time.set_max_delta(MAX_DELTA_AT_NORMAL_SPEED.mul_f32(speed.max(1.0)));
+            }
+            SimulationSpeedEvent::AdvanceSingleTick => {
+                if state == SimulationState::Paused {
+                    pending_single_tick.0 = true;
+                }
+            }
+        }
+    }
+}
+
+/// Runs [FixedMain] once while the simulation is paused.
+fn advance_single_tick(world: &mut World) {
+    if !std::mem::take(&mut world.resource_mut::<PendingSingleTick>().0) {
+        return;
+    }
+
+    // All simulation systems only run while the simulation is running, so we pretend it does for this one tick.
+    // Nothing reacts to transitions between Running and Paused, which is why the State can be swapped directly.
+    world.insert_resource(State::new(SimulationState::Running));
+    // Time<Fixed> only advances while virtual time is accumulated, which doesn't happen while paused.
+    // Without this, its delta would be zero if nothing has been simulated since starting paused.
+    let mut fixed_time = world.resource_mut::<Time<Fixed>>();
+    let timestep = fixed_time.timestep();
+    fixed_time.advance_by(timestep);
+    *world.resource_mut::<Time>() = world.resource::<Time<Fixed>>().as_generic();
+    world.run_schedule(FixedMain);
+    *world.resource_mut::<Time>() = world.resource::<Time<Virtual>>().as_generic();
+    world.insert_resource(State::new(SimulationState::Paused));
+}
+
+/// Pauses the simulation right away rather than with the next state transition,
+/// so no further ticks are run within the current frame.
+pub fn pause_immediately(world: &mut World) {
+    world.resource_mut::<Time<Virtual>>().pause();
+    world.insert_resource(State::new(SimulationState::Paused));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::InSector;
+    use crate::simulation::physics::{PhysicsPlugin, ShipVelocity};
+    use crate::simulation::precomputed_orbit_directions::PrecomputedOrbitDirections;
+    use crate::simulation::prelude::{SimulationTime, SimulationTransform};
+    use crate::simulation::time::SimulationTimePlugin;
+    use crate::states::StatePlugin;
+    use crate::utils::SectorEntity;
+    use bevy::input::InputPlugin;
+    use bevy::prelude::{MinimalPlugins, Vec2};
+    use bevy::state::app::StatesPlugin;
+    use bevy::time::TimeUpdateStrategy;
+
+    const TIMESTEP: Duration = Duration::from_millis(100);
+
+    fn build_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            StatesPlugin,
+            InputPlugin,
+            StatePlugin,
+            SimulationTimePlugin,
+            SimulationSpeedPlugin,
+        ))
+        .insert_resource(Time::<Fixed>::from_duration(TIMESTEP))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(TIMESTEP));
+        app.world_mut()
+            .resource_mut::<NextState<ApplicationState>>()
+            .set(ApplicationState::InGame);
+        app.update();
+        app
+    }
+
+    fn send(app: &mut App, event: SimulationSpeedEvent) {
+        app.world_mut().send_event(event);
+        app.update();
+    }
+
+    fn current_tick(app: &App) -> u32 {
+        app.world().resource::<SimulationTime>().tick()
+    }
+
+    #[test]
+    fn single_ticks_advance_paused_simulation_by_exactly_one_timestep() {
+        let mut app = build_app();
+        send(&mut app, SimulationSpeedEvent::TogglePause);
+        let tick = current_tick(&app);
+        let total = app.world().resource::<SimulationTime>().total();
+
+        for _ in 0..5 {
+            app.update();
+        }
+        assert_eq!(current_tick(&app), tick);
+
+        send(&mut app, SimulationSpeedEvent::AdvanceSingleTick);
+        app.update();
+
+        assert_eq!(current_tick(&app), tick + 1);
+        assert_eq!(
+            app.world().resource::<SimulationTime>().total(),
+            total + TIMESTEP
+        );
+        assert_eq!(
+            app.world().resource::<State<SimulationState>>().get(),
+            &SimulationState::Paused
+        );
+    }
+
+    #[test]
+    fn faster_speeds_run_more_ticks_per_frame() {
+        let mut app = build_app();
+        send(&mut app, SimulationSpeedEvent::SetSpeed(5.0));
+        let tick = current_tick(&app);
+
+        app.update();
+
+        assert_eq!(current_tick(&app), tick + 5);
+    }
+
+    #[test]
+    fn faster_speeds_allow_longer_frames() {
+        let mut app = build_app();
+        let speed = SIMULATION_SPEEDS[SIMULATION_SPEEDS.len() - 1];
+        send(&mut app, SimulationSpeedEvent::SetSpeed(speed));
+        let frame = MAX_DELTA_AT_NORMAL_SPEED * 2;
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(frame));
+        app.update();
+        let tick = current_tick(&app);
+
+        app.update();
+
+        let expected_ticks = frame.mul_f32(speed).as_millis() / TIMESTEP.as_millis();
+        assert_eq!(current_tick(&app), tick + expected_ticks as u32);
+    }
+
+    #[test]
+    fn single_ticks_move_ships_while_paused_since_the_start() {
+        let mut app = build_app();
+        app.add_plugins(PhysicsPlugin)
+            .init_resource::<PrecomputedOrbitDirections>();
+        let ship = app
+            .world_mut()
+            .spawn((
+                SimulationTransform::from_translation(Vec2::ZERO),
+                ShipVelocity {
+                    forward: 10.0,
+                    angular: 0.0,
+                },
+                InSector {
+                    sector: SectorEntity::from(0),
+                },
+            ))
+            .id();
+        pause_immediately(app.world_mut());
+        assert_eq!(current_tick(&app), 0);
+
+        send(&mut app, SimulationSpeedEvent::AdvanceSingleTick);
+        app.update();
+
+        assert_eq!(current_tick(&app), 1);
+        let distance = app
+            .world()
+            .get::<SimulationTransform>(ship)
+            .unwrap()
+            .translation
+            .length();
+        assert!(
+            (distance - 10.0 * TIMESTEP.as_secs_f32()).abs() < 0.0001,
+            "Ship moved {distance} units!"
+        );
+    }
+}
@@ -0,0 +1,556 @@
+//! Hashes the relevant simulation state every couple of ticks, so two runs (or two peers) which are supposed to be
+//! identical can be compared and the exact point where they started to diverge can be found.
+//!
+//! Checksums are grouped per sector and contain a hash for every entity with a [PersistentEntityId].
+//! They are written to a log file with one RON encoded [StateChecksum] per line, which can then be compared
+//! with [compare_checksum_logs].
+
+use crate::components::{
+    Asteroid, Gate, InSector, Inventory, Owner, Planet, Sector, Ship, Station, Wallet,
+};
+use crate::persistence::{ComponentWithPersistentId, PersistentEntityId};
+use crate::session_data::{FactionId, SessionData};
+use crate::simulation::prelude::{GlobalProductionState, SimulationTime, TaskQueue};
+use crate::simulation::transform::simulation_transform::SimulationTransform;
+use crate::states::SimulationState;
+use bevy::app::{App, FixedLast, Plugin};
+use bevy::ecs::query::QueryData;
+use bevy::log::{debug, error};
+use bevy::prelude::{
+    in_state, Entity, In, IntoSystem, IntoSystemConfigs, Query, Res, ResMut, Resource,
+};
+use bevy::utils::HashMap;
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{LineWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// How many ticks are between two checksums unless a different interval was requested.
+pub const DEFAULT_CHECKSUM_INTERVAL: u32 = 100;
+
+/// The state of the whole simulation at a specific tick.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct StateChecksum {
+    pub tick: u32,
+    /// Ordered by sector coordinate, with entities in between sectors coming first.
+    pub sectors: Vec<SectorChecksum>,
+    /// Ordered by ID. Covers the faction's wallet and what it thinks about other factions.
+    #[serde(default)]
+    pub factions: Vec<(FactionId, u64)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct SectorChecksum {
+    /// None for entities which are currently in between sectors, e.g. while using a gate.
+    pub sector: Option<Hex>,
+    pub checksum: u64,
+    /// Ordered by ID.
+    pub entities: Vec<(PersistentEntityId, u64)>,
+}
+
+/// Records a [StateChecksum] every `interval` ticks and appends it to the file at `output`.
+pub struct StateChecksumPlugin {
+    pub interval: u32,
+    pub output: PathBuf,
+}
+
+impl Plugin for StateChecksumPlugin {
+    fn build(&self, app: &mut App) {
+        let file = File::create(&self.output).unwrap_or_else(|e| {
+            panic!(
+                "Unable to create checksum log {}: {e}",
+                self.output.display()
+            )
+        });
+
+        app.insert_resource(StateChecksumLog {
+            interval: self.interval.max(1),
+            writer: LineWriter::new(file),
+        })
+        .add_systems(
+            FixedLast,
+            capture_state_checksum
+                .pipe(write_state_checksum)
+                .run_if(in_state(SimulationState::Running))
+                .run_if(checksum_is_due),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct StateChecksumLog {
+    interval: u32,
+    writer: LineWriter<File>,
+}
+
+fn checksum_is_due(log: Res<StateChecksumLog>, simulation_time: Res<SimulationTime>) -> bool {
+    simulation_time.tick() % log.interval == 0
+}
+
+fn write_state_checksum(In(checksum): In<StateChecksum>, mut log: ResMut<StateChecksumLog>) {
+    for sector in &checksum.sectors {
+        match sector.sector {
+            Some(hex) => debug!(
+                "Tick {}: Sector [{},{}] has checksum {:016x}",
+                checksum.tick, hex.x, hex.y, sector.checksum
+            ),
+            None => debug!(
+                "Tick {}: Entities in between sectors have checksum {:016x}",
+                checksum.tick, sector.checksum
+            ),
+        }
+    }
+
+    let line = ron::to_string(&checksum).expect("Checksums should always be serializable!");
+    if let Err(e) = writeln!(log.writer, "{line}") {
+        error!("Unable to write checksum for tick {}: {e}", checksum.tick);
+    }
+}
+
+/// 64-bit FNV-1a. Unlike [std::hash::DefaultHasher], its algorithm is fixed, so checksums can be compared
+/// between different builds and platforms. Integers are always hashed as little-endian, and `usize` and `isize`
+/// (lengths and enum discriminants) are widened to 64 bits.
+///
+/// Integer slices are hashed as their raw bytes in native endianness by [Hash::hash_slice],
+/// so collections need to be hashed element by element with [StableHasher::write_collection].
+struct StableHasher(u64);
+
+impl StableHasher {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    fn write_collection<'a, T: Hash + 'a>(&mut self, items: impl ExactSizeIterator<Item = &'a T>) {
+        self.write_u64(items.len() as u64);
+        for item in items {
+            item.hash(self);
+        }
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_i64(i as i64);
+    }
+}
+
+#[derive(QueryData)]
+pub struct StateChecksumQuery {
+    in_sector: Option<&'static InSector>,
+    transform: Option<&'static SimulationTransform>,
+    inventory: Option<&'static Inventory>,
+    wallet: Option<&'static Wallet>,
+    owner: Option<&'static Owner>,
+    task_queue: Option<&'static TaskQueue>,
+    asteroid: Option<&'static Asteroid>,
+    gate: Option<&'static Gate>,
+    planet: Option<&'static Planet>,
+    ship: Option<&'static Ship>,
+    station: Option<&'static Station>,
+}
+
+impl StateChecksumQueryItem<'_> {
+    fn persistent_id(&self) -> Option<PersistentEntityId> {
+        if let Some(asteroid) = self.asteroid {
+            Some(asteroid.id().into())
+        } else if let Some(gate) = self.gate {
+            Some(gate.id().into())
+        } else if let Some(planet) = self.planet {
+            Some(planet.id.into())
+        } else if let Some(ship) = self.ship {
+            Some(ship.id().into())
+        } else {
+            self.station.map(|station| station.id().into())
+        }
+    }
+
+    fn checksum(&self, productions: Option<&Vec<u64>>) -> u64 {
+        let mut hasher = StableHasher::new();
+        if let Some(transform) = self.transform {
+            transform.translation.x.to_bits().hash(&mut hasher);
+            transform.translation.y.to_bits().hash(&mut hasher);
+            transform.rotation.as_radians().to_bits().hash(&mut hasher);
+        }
+        if let Some(inventory) = self.inventory {
+            let mut items: Vec<_> = inventory.inventory().iter().collect();
+            items.sort_by_key(|(item_id, _)| **item_id);
+            hasher.write_u64(items.len() as u64);
+            for (item_id, element) in items {
+                item_id.hash(&mut hasher);
+                element.currently_available.hash(&mut hasher);
+                element.planned_buying.hash(&mut hasher);
+                element.planned_selling.hash(&mut hasher);
+                element.planned_producing.hash(&mut hasher);
+            }
+        }
+        if let Some(wallet) = self.wallet {
+            wallet.credits().hash(&mut hasher);
+        }
+        if let Some(owner) = self.owner {
+            owner.faction.hash(&mut hasher);
+        }
+        if let Some(task_queue) = self.task_queue {
+            hasher.write_u64(task_queue.queue.len() as u64);
+            for task in &task_queue.queue {
+                std::mem::discriminant(task).hash(&mut hasher);
+            }
+        }
+        if let Some(asteroid) = self.asteroid {
+            asteroid.ore.hash(&mut hasher);
+            asteroid.remaining_after_reservations.hash(&mut hasher);
+        }
+        if let Some(productions) = productions {
+            hasher.write_collection(productions.iter());
+        }
+
+        hasher.finish()
+    }
+}
+
+/// Hashes the current simulation state. Can also be run on demand through [bevy::ecs::system::RunSystemOnce].
+pub fn capture_state_checksum(
+    simulation_time: Res<SimulationTime>,
+    global_production_state: Res<GlobalProductionState>,
+    session_data: Res<SessionData>,
+    entities: Query<(Entity, StateChecksumQuery)>,
+    sectors: Query<&Sector>,
+) -> StateChecksum {
+    // The heap is ordered by finish time only, so the hashes are sorted to become independent of insertion order.
+    let mut productions: HashMap<Entity, Vec<u64>> = HashMap::new();
+    for production in global_production_state.iter() {
+        let mut hasher = StableHasher::new();
+        production.kind.hash(&mut hasher);
+        production.finished_at.hash(&mut hasher);
+        productions
+            .entry(production.entity)
+            .or_default()
+            .push(hasher.finish());
+    }
+    for hashes in productions.values_mut() {
+        hashes.sort();
+    }
+
+    let mut entities_by_sector: HashMap<Option<Hex>, Vec<(PersistentEntityId, u64)>> =
+        HashMap::new();
+    for (entity, item) in entities.iter() {
+        let Some(id) = item.persistent_id() else {
+            continue;
+        };
+
+        let sector = item
+            .in_sector
+            .and_then(|x| sectors.get(x.get().into()).ok())
+            .map(|x| x.coordinate);
+        let checksum = item.checksum(productions.get(&entity));
+        entities_by_sector
+            .entry(sector)
+            .or_default()
+            .push((id, checksum));
+    }
+
+    let mut result: Vec<SectorChecksum> = entities_by_sector
+        .into_iter()
+        .map(|(sector, mut entities)| {
+            entities.sort();
+            let mut hasher = StableHasher::new();
+            hasher.write_collection(entities.iter());
+            SectorChecksum {
+                sector,
+                checksum: hasher.finish(),
+                entities,
+            }
+        })
+        .collect();
+    result.sort_by_key(|x| x.sector.map(|hex| (hex.x, hex.y)));
+
+    let relations = session_data.relations.sorted();
+    let mut factions: Vec<(FactionId, u64)> = session_data
+        .factions
+        .values()
+        .map(|faction| {
+            let mut hasher = StableHasher::new();
+            faction.wallet.credits().hash(&mut hasher);
+            for (_, towards, relation) in relations.iter().filter(|(x, ..)| *x == faction.id) {
+                towards.hash(&mut hasher);
+                relation.hash(&mut hasher);
+            }
+            (faction.id, hasher.finish())
+        })
+        .collect();
+    factions.sort();
+
+    StateChecksum {
+        tick: simulation_time.tick(),
+        sectors: result,
+        factions,
+    }
+}
+
+/// The first point at which two runs are no longer identical.
+#[derive(PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Divergence {
+    pub tick: u32,
+    /// None if the runs diverged for entities in between sectors, or the checksums were captured at different ticks.
+    pub sector: Option<Hex>,
+    /// None if the sector exists in only one of the runs, or the checksums were captured at different ticks.
+    pub entity: Option<PersistentEntityId>,
+    /// Only set if all sectors are identical, but the state of a faction isn't.
+    pub faction: Option<FactionId>,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Runs diverged at tick {}", self.tick)?;
+        match self.sector {
+            Some(hex) => write!(f, " in sector [{},{}]", hex.x, hex.y)?,
+            None if self.entity.is_some() => write!(f, " in between sectors")?,
+            None => {}
+        }
+        if let Some(entity) = &self.entity {
+            write!(f, ", starting with {entity}")?;
+        }
+        if let Some(faction) = self.faction {
+            write!(f, " for faction {faction}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the first tick, sector and entity or faction at which the two checksum histories differ.
+/// If one history is longer than the other, only the ticks recorded in both are compared.
+pub fn find_divergence(a: &[StateChecksum], b: &[StateChecksum]) -> Option<Divergence> {
+    let (a, b) = a.iter().zip(b).find(|(a, b)| a != b)?;
+    if a.tick != b.tick {
+        return Some(Divergence {
+            tick: a.tick.min(b.tick),
+            sector: None,
+            entity: None,
+            faction: None,
+        });
+    }
+    if a.sectors == b.sectors {
+        return Some(Divergence {
+            tick: a.tick,
+            sector: None,
+            entity: None,
+            faction: first_differing_id(&a.factions, &b.factions),
+        });
+    }
+
+    let sector_key = |x: &SectorChecksum| x.sector.map(|hex| (hex.x, hex.y));
+    let mut a_sectors = a.sectors.iter();
+    let mut b_sectors = b.sectors.iter();
+    let (sector, entity) = loop {
+        match (a_sectors.next(), b_sectors.next()) {
+            (Some(x), Some(y)) if x == y => continue,
+            (Some(x), Some(y)) if x.sector == y.sector => {
+                break (x.sector, first_differing_id(&x.entities, &y.entities));
+            }
+            (Some(x), Some(y)) if sector_key(x) < sector_key(y) => break (x.sector, None),
+            (Some(_), Some(y)) | (None, Some(y)) => break (y.sector, None),
+            (Some(x), None) => break (x.sector, None),
+            (None, None) => break (None, None),
+        }
+    };
+
+    Some(Divergence {
+        tick: a.tick,
+        sector,
+        entity,
+        faction: None,
+    })
+}
+
+fn first_differing_id<T: Ord + Clone>(a: &[(T, u64)], b: &[(T, u64)]) -> Option<T> {
+    let mut a = a.iter();
+    let mut b = b.iter();
+    loop {
+        match (a.next(), b.next()) {
+            (Some(x), Some(y)) if x == y => continue,
+            (Some((a_id, _)), Some((b_id, _))) => return Some(a_id.min(b_id).clone()),
+            (Some((id, _)), None) | (None, Some((id, _))) => return Some(id.clone()),
+            (None, None) => return None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ChecksumLogError {
+    Io(PathBuf, std::io::Error),
+    Parse {
+        path: PathBuf,
+        line: usize,
+        error: ron::error::SpannedError,
+    },
+}
+
+impl Display for ChecksumLogError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChecksumLogError::Io(path, e) => write!(f, "Unable to read {}: {e}", path.display()),
+            ChecksumLogError::Parse { path, line, error } => {
+                write!(f, "Line {line} of {} is malformed: {error}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ChecksumLogError {}
+
+pub fn read_checksum_log(path: &Path) -> Result<Vec<StateChecksum>, ChecksumLogError> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| ChecksumLogError::Io(path.to_path_buf(), e))?;
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            ron::from_str(line).map_err(|error| ChecksumLogError::Parse {
+                path: path.to_path_buf(),
+                line: index + 1,
+                error,
+            })
+        })
+        .collect()
+}
+
+/// Reads two checksum logs and returns the point at which they diverge, if they do.
+pub fn compare_checksum_logs(a: &Path, b: &Path) -> Result<Option<Divergence>, ChecksumLogError> {
+    Ok(find_divergence(
+        &read_checksum_log(a)?,
+        &read_checksum_log(b)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::PersistentShipId;
+
+    fn checksum(tick: u32, entities: Vec<(PersistentEntityId, u64)>) -> StateChecksum {
+        let mut hasher = StableHasher::new();
+        hasher.write_collection(entities.iter());
+        StateChecksum {
+            tick,
+            sectors: vec![SectorChecksum {
+                sector: Some(Hex::ZERO),
+                checksum: hasher.finish(),
+                entities,
+            }],
+            factions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn first_differing_entity_is_reported() {
+        let ship_a: PersistentEntityId = PersistentShipId::next().into();
+        let ship_b: PersistentEntityId = PersistentShipId::next().into();
+        let ship_c: PersistentEntityId = PersistentShipId::next().into();
+
+        let a = vec![
+            checksum(100, vec![(ship_a.clone(), 1), (ship_b.clone(), 2)]),
+            checksum(200, vec![(ship_a.clone(), 1), (ship_b.clone(), 3)]),
+        ];
+        let b = vec![
+            checksum(100, vec![(ship_a.clone(), 1), (ship_b.clone(), 2)]),
+            checksum(200, vec![(ship_a.clone(), 1), (ship_b.clone(), 4)]),
+        ];
+        let c = vec![
+            checksum(100, vec![(ship_a.clone(), 1), (ship_b.clone(), 2)]),
+            checksum(200, vec![(ship_a.clone(), 1), (ship_c, 3)]),
+        ];
+
+        assert_eq!(find_divergence(&a, &a), None);
+        assert_eq!(
+            find_divergence(&a, &b),
+            Some(Divergence {
+                tick: 200,
+                sector: Some(Hex::ZERO),
+                entity: Some(ship_b.clone()),
+                faction: None,
+            })
+        );
+        assert_eq!(
+            find_divergence(&a, &c),
+            Some(Divergence {
+                tick: 200,
+                sector: Some(Hex::ZERO),
+                entity: Some(ship_b),
+                faction: None,
+            })
+        );
+    }
+
+    #[test]
+    fn stable_hasher_does_not_depend_on_platform_or_release() {
+        // Reference values for FNV-1a, see http://www.isthe.com/chongo/src/fnv/test_fnv.c
+        let mut hasher = StableHasher::new();
+        assert_eq!(hasher.finish(), 0xcbf29ce484222325);
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63dc4c8601ec8c);
+
+        let mut a = StableHasher::new();
+        a.write_usize(1);
+        let mut b = StableHasher::new();
+        b.write_u64(1);
+        assert_eq!(a.finish(), b.finish());
+    }
+}
@@ -0,0 +1,274 @@
+//! Runs small universes for a couple of ticks, so behaviors can be tested from start to finish.
+//!
+//! Everything runs headless and deterministically, so a failing test fails the same way every time.
+
+use crate::components::{Inventory, IsDocked, Wallet};
+use crate::game_data::{GameData, ItemId};
+use crate::headless::HeadlessPlugin;
+use crate::persistence::{
+    PersistentShipId, PersistentStationId, ShipIdMap, StationIdMap, UniverseSaveData,
+    UniverseSaveDataLoadingPlugin,
+};
+use crate::session_data::{FactionId, SessionData};
+use crate::simulation::determinism::DeterministicSimulationPlugin;
+use crate::simulation::plugin::SimulationPlugin;
+use crate::simulation::prelude::{SimulationTime, TaskInsideQueue};
+use crate::simulation::ship_ai::TaskQueue;
+use crate::states::{ApplicationState, StatePlugin};
+use bevy::prelude::{App, Entity, State};
+use bevy::utils::HashMap;
+use std::mem::Discriminant;
+
+/// Loading is spread across multiple frames, this should be more than enough for any test.
+const MAX_LOADING_UPDATES: usize = 100;
+/// Updates without a single simulated tick before we assume the simulation won't ever continue.
+const MAX_UPDATES_WITHOUT_TICK: usize = 10;
+const SEED: u64 = 42;
+
+/// A headless app running the entire simulation for a given [UniverseSaveData].
+pub struct SimulationTestApp {
+    pub app: App,
+    current_tasks: HashMap<Entity, CurrentTask>,
+    /// The longest time every ship has spent on a single task so far.
+    longest_tasks: HashMap<Entity, LongestTask>,
+}
+
+/// The task a ship is currently working on, and the tick at which it started working on it.
+struct CurrentTask {
+    task: Discriminant<TaskInsideQueue>,
+    queue_length: usize,
+    since_tick: u32,
+}
+
+struct LongestTask {
+    task: Discriminant<TaskInsideQueue>,
+    queue_length: usize,
+    ticks: u32,
+}
+
+impl SimulationTestApp {
+    /// Builds the app and runs it until the universe has been loaded.
+    pub fn new(data: UniverseSaveData) -> Self {
+        let mut app = App::new();
+        app.add_plugins((
+            HeadlessPlugin,
+            StatePlugin,
+            UniverseSaveDataLoadingPlugin,
+            SimulationPlugin,
+            DeterministicSimulationPlugin { seed: SEED },
+        ))
+        .insert_resource(GameData::mock_data())
+        .insert_resource(SessionData::mock_data());
+        data.insert_as_resources(app.world_mut());
+        app.finish();
+        app.cleanup();
+
+        for _ in 0..MAX_LOADING_UPDATES {
+            app.update();
+            if app.world().resource::<State<ApplicationState>>().get() == &ApplicationState::InGame
+            {
+                return Self {
+                    app,
+                    current_tasks: HashMap::new(),
+                    longest_tasks: HashMap::new(),
+                };
+            }
+        }
+
+        panic!("Universe did not finish loading within {MAX_LOADING_UPDATES} updates!");
+    }
+
+    pub fn current_tick(&self) -> u32 {
+        self.app.world().resource::<SimulationTime>().tick()
+    }
+
+    /// Advances the simulation by exactly one tick.
+    pub fn run_tick(&mut self) {
+        let target = self.current_tick() + 1;
+        for _ in 0..MAX_UPDATES_WITHOUT_TICK {
+            self.app.update();
+            if self.current_tick() >= target {
+                self.track_current_tasks();
+                return;
+            }
+        }
+
+        panic!("Simulation did not advance within {MAX_UPDATES_WITHOUT_TICK} updates!");
+    }
+
+    pub fn run_ticks(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            self.run_tick();
+        }
+    }
+
+    /// Runs the simulation until `condition` is met, returning the amount of ticks this took.
+    /// Returns [None] if the condition still wasn't met after `max_ticks`.
+    pub fn run_until(
+        &mut self,
+        max_ticks: u32,
+        mut condition: impl FnMut(&Self) -> bool,
+    ) -> Option<u32> {
+        for tick in 0..=max_ticks {
+            if condition(self) {
+                return Some(tick);
+            }
+            if tick < max_ticks {
+                self.run_tick();
+            }
+        }
+
+        None
+    }
+
+    pub fn ship(&self, id: PersistentShipId) -> Entity {
+        self.app.world().resource::<ShipIdMap>().id_to_entity()[&id].into()
+    }
+
+    pub fn station(&self, id: PersistentStationId) -> Entity {
+        self.app.world().resource::<StationIdMap>().id_to_entity()[&id].into()
+    }
+
+    /// Returns the amount of `item_id` which is currently stored within `entity`s inventory.
+    pub fn inventory_amount(&self, entity: Entity, item_id: ItemId) -> u32 {
+        self.app
+            .world()
+            .get::<Inventory>(entity)
+            .expect("Entity should have an inventory!")
+            .get(&item_id)
+            .map_or(0, |element| element.currently_available)
+    }
+
+    pub fn credits(&self, entity: Entity) -> i64 {
+        self.app
+            .world()
+            .get::<Wallet>(entity)
+            .expect("Entity should have a wallet!")
+            .credits()
+    }
+
+    /// Credits which haven't been reserved for planned purchases.
+    pub fn available_credits(&self, entity: Entity) -> i64 {
+        self.app
+            .world()
+            .get::<Wallet>(entity)
+            .expect("Entity should have a wallet!")
+            .available_credits()
+    }
+
+    pub fn faction_credits(&self, faction: FactionId) -> i64 {
+        self.app.world().resource::<SessionData>().factions[&faction]
+            .wallet
+            .credits()
+    }
+
+    /// What `faction` thinks about `towards`.
+    pub fn favor(&self, faction: FactionId, towards: FactionId) -> i64 {
+        self.app
+            .world()
+            .resource::<SessionData>()
+            .relations
+            .get(faction, towards)
+            .favor
+    }
+
+    pub fn is_docked_at(&self, ship: Entity, station: Entity) -> bool {
+        self.app
+            .world()
+            .get::<IsDocked>(ship)
+            .is_some_and(|docked| Entity::from(docked.at) == station)
+    }
+
+    pub fn assert_ship_docks_at_station(
+        &mut self,
+        ship: PersistentShipId,
+        station: PersistentStationId,
+        max_ticks: u32,
+    ) {
+        let ship = self.ship(ship);
+        let station = self.station(station);
+        assert!(
+            self.run_until(max_ticks, |app| app.is_docked_at(ship, station))
+                .is_some(),
+            "Ship {ship:?} did not dock at station {station:?} within {max_ticks} ticks!"
+        );
+    }
+
+    pub fn assert_inventory_reaches(
+        &mut self,
+        station: PersistentStationId,
+        item_id: ItemId,
+        amount: u32,
+        max_ticks: u32,
+    ) {
+        let station = self.station(station);
+        let result = self.run_until(max_ticks, |app| {
+            app.inventory_amount(station, item_id) >= amount
+        });
+        assert!(
+            result.is_some(),
+            "Station {station:?} did not store {amount} of item {item_id} within {max_ticks} ticks, it only has {}!",
+            self.inventory_amount(station, item_id)
+        );
+    }
+
+    /// Fails if any ship has spent more than `max_ticks` on the same task at any point since the app was created,
+    /// even if it has recovered by now. Ships without any tasks are idle rather than stuck, and thus ignored.
+    pub fn assert_no_ship_stuck_longer_than(&self, max_ticks: u32) {
+        for (entity, longest) in &self.longest_tasks {
+            assert!(
+                longest.ticks <= max_ticks,
+                "Ship {entity:?} has been stuck on {:?} with {} queued tasks for {} ticks!",
+                longest.task,
+                longest.queue_length,
+                longest.ticks
+            );
+        }
+    }
+
+    fn track_current_tasks(&mut self) {
+        let now = self.current_tick();
+        let mut query = self.app.world_mut().query::<(Entity, &TaskQueue)>();
+        let mut still_busy = HashMap::new();
+        for (entity, queue) in query.iter(self.app.world()) {
+            let Some(task) = queue.front() else {
+                continue;
+            };
+
+            let task = std::mem::discriminant(task);
+            let since_tick = match self.current_tasks.get(&entity) {
+                Some(previous) if previous.task == task && previous.queue_length == queue.len() => {
+                    previous.since_tick
+                }
+                _ => now,
+            };
+
+            let ticks = now - since_tick;
+            let is_longest = match self.longest_tasks.get(&entity) {
+                Some(longest) => longest.ticks < ticks,
+                None => true,
+            };
+            if is_longest {
+                self.longest_tasks.insert(
+                    entity,
+                    LongestTask {
+                        task,
+                        queue_length: queue.len(),
+                        ticks,
+                    },
+                );
+            }
+
+            still_busy.insert(
+                entity,
+                CurrentTask {
+                    task,
+                    queue_length: queue.len(),
+                    since_tick,
+                },
+            );
+        }
+
+        self.current_tasks = still_busy;
+    }
+}
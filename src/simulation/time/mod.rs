@@ -2,7 +2,7 @@ mod plugin;
 mod simulation_time_resource;
 mod simulation_timestamp;
 
-pub use plugin::SimulationTimePlugin;
+pub use plugin::{advance_simulation_time, ExitAfterSimulatedTimePlugin, SimulationTimePlugin};
 pub use simulation_time_resource::SimulationTime;
 pub use simulation_timestamp::CurrentSimulationTimestamp;
 pub use simulation_timestamp::SimulationTimestamp;
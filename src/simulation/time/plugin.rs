@@ -1,7 +1,12 @@
 use crate::simulation::prelude::SimulationTime;
 use crate::states::SimulationState;
-use bevy::prelude::{in_state, App, FixedFirst, IntoSystemConfigs, Plugin, Res, ResMut, Time};
+use bevy::app::AppExit;
+use bevy::prelude::{
+    in_state, App, EventWriter, FixedFirst, FixedLast, IntoSystemConfigs, Plugin, Res, ResMut,
+    Resource, Time,
+};
 use bevy::time::Fixed;
+use std::time::Duration;
 
 pub struct SimulationTimePlugin;
 impl Plugin for SimulationTimePlugin {
@@ -9,7 +14,7 @@ impl Plugin for SimulationTimePlugin {
         app.insert_resource(SimulationTime::default());
         app.add_systems(
             FixedFirst,
-            update
+            advance_simulation_time
                 .after(bevy::time::TimeSystem)
                 .run_if(in_state(SimulationState::Running)),
         );
@@ -17,6 +22,39 @@ impl Plugin for SimulationTimePlugin {
 }
 
 /// Should always run **after** [bevy::time::TimeSystem]
-fn update(mut simulation_time: ResMut<SimulationTime>, bevy_time: Res<Time<Fixed>>) {
-    simulation_time.advance(bevy_time.delta());
+///
+/// Uses the timestep rather than [Time::delta], since single ticks can also be run manually while paused.
+pub fn advance_simulation_time(
+    mut simulation_time: ResMut<SimulationTime>,
+    bevy_time: Res<Time<Fixed>>,
+) {
+    simulation_time.advance(bevy_time.timestep());
+}
+
+/// Exits the app as soon as [SimulationTime] has reached `duration`.
+pub struct ExitAfterSimulatedTimePlugin {
+    pub duration: Duration,
+}
+
+impl Plugin for ExitAfterSimulatedTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ExitAfterSimulatedTime(self.duration));
+        app.add_systems(
+            FixedLast,
+            exit_once_simulated_time_has_passed.run_if(in_state(SimulationState::Running)),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct ExitAfterSimulatedTime(Duration);
+
+fn exit_once_simulated_time_has_passed(
+    simulation_time: Res<SimulationTime>,
+    exit_after: Res<ExitAfterSimulatedTime>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    if simulation_time.total() >= exit_after.0 {
+        exit_events.send(AppExit::Success);
+    }
 }
@@ -25,6 +25,11 @@ impl Default for SimulationTime {
 }
 
 impl SimulationTime {
+    /// Creates a new instance which continues from a previously persisted point in time.
+    pub(crate) fn new(total: Duration, tick: u32) -> Self {
+        Self { total, tick }
+    }
+
     #[inline]
     pub(crate) fn advance(&mut self, delta: Duration) {
         self.total += delta;
@@ -42,4 +47,10 @@ impl SimulationTime {
     pub fn tick(&self) -> u32 {
         self.tick
     }
+
+    /// Returns the total Duration since the simulation has started.
+    #[inline]
+    pub fn total(&self) -> Duration {
+        self.total
+    }
 }
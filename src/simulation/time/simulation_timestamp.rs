@@ -62,7 +62,7 @@ impl From<Milliseconds> for CurrentSimulationTimestamp {
 }
 
 /// Represents a specific Timestamp in Milliseconds since session start.
-#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[cfg_attr(test, derive(Debug))]
 pub struct SimulationTimestamp(Milliseconds);
 
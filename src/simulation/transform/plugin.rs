@@ -3,8 +3,8 @@ use crate::simulation::transform::simulation_transform::SimulationTransform;
 use crate::states::SimulationState;
 use bevy::app::{App, FixedPreUpdate, Plugin, Update};
 use bevy::prelude::{
-    in_state, DetectChanges, Fixed, IntoSystemConfigs, Local, Mut, Query, Res, Time, Transform,
-    ViewVisibility,
+    in_state, resource_changed, Condition, DetectChanges, Fixed, IntoSystemConfigs, Local, Mut,
+    Query, Res, Time, Transform, ViewVisibility,
 };
 
 /// Interpolates the transforms used for the visual representation to their respective simulation values.
@@ -17,7 +17,10 @@ impl Plugin for SimulationTransformPlugin {
         );
         app.add_systems(
             Update,
-            interpolate_transforms.run_if(in_state(SimulationState::Running)),
+            // Single ticks while paused should still be visible
+            interpolate_transforms.run_if(
+                in_state(SimulationState::Running).or_else(resource_changed::<SimulationTime>),
+            ),
         );
     }
 }
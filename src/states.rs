@@ -7,13 +7,16 @@ impl Plugin for StatePlugin {
         app.init_state::<ApplicationState>();
         app.add_sub_state::<SimulationState>();
         app.add_sub_state::<MenuState>();
+        app.add_sub_state::<LoadingState>();
     }
 }
 
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
 pub enum ApplicationState {
     Menu,
+    /// Spawns the universe from the SaveDataCollection resources, see [LoadingState].
     #[default]
+    Loading,
     InGame,
 }
 
@@ -31,3 +34,17 @@ pub enum MenuState {
     #[default]
     MainMenu,
 }
+
+/// The individual phases of spawning a universe, in order.
+/// Each phase may take multiple frames, so the app stays responsive.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, SubStates)]
+#[source(ApplicationState = ApplicationState::Loading)]
+pub enum LoadingState {
+    #[default]
+    Sectors,
+    Gates,
+    Stations,
+    Ships,
+    /// Restores ship tasks, docking and interaction queues, which may reference any other entity.
+    Tasks,
+}
@@ -1,89 +1,399 @@
-use bevy::prelude::{Entity, Query};
+use bevy::prelude::{Component, Entity, Query, Vec2};
+use bevy::utils::HashMap;
 
-use crate::components::{BuyOrders, InSector, Inventory, Sector, SellOrders, TradeOrder};
+use crate::components::{
+    affordable_amount, ship_wallet, BuyOrders, InSector, Inventory, Owner, Sector, Station,
+    TradeOrder, Wallet,
+};
 use crate::game_data::ItemId;
-use crate::simulation::ship_ai::{TaskInsideQueue, TaskQueue};
+use crate::pathfinding::surrounding_sector_search::surrounding_sector_search;
+use crate::session_data::SessionData;
+use crate::simulation::market_index::{MarketEntry, MarketIndex};
+use crate::simulation::prelude::{CurrentSimulationTimestamp, SimulationTimestamp};
+use crate::simulation::ship_ai::{DeliveryDeadlines, TaskInsideQueue, TaskQueue};
 use crate::simulation::transform::simulation_transform::SimulationTransform;
-use crate::utils::{ExchangeWareData, SectorEntity, TypedEntity};
+use crate::utils::{ExchangeWareData, SectorEntity, ShipEntity, TradeIntent, TypedEntity};
 use crate::{constants, pathfinding};
 
+/// A buyer along a [TradePlan], and how much of the cargo gets sold there.
+pub struct TradeStop {
+    pub buyer: TypedEntity,
+    pub buyer_sector: SectorEntity,
+    pub amount: u32,
+    /// The price per item which the buyer offered when the plan was made.
+    pub price: u32,
+}
+
+/// Buys one item from a single seller and sells it to one or more buyers.
+///
+/// Everything which gets reserved for a plan via [Inventory::create_order] should be exchanged before the deadlines
+/// of its [DeliverySchedule], otherwise the ship's owner loses favor with the owners of its trade partners.
 pub struct TradePlan {
     pub item_id: ItemId,
+    /// The amount bought from the seller, which equals the sum of all amounts sold at each [TradeStop].
     pub amount: u32,
+    /// The price per item which the seller asked for when the plan was made, 0 when selling the ship's own cargo.
+    pub price: u32,
     pub profit: u32,
     pub seller: TypedEntity,
     pub seller_sector: SectorEntity,
-    pub buyer: TypedEntity,
-    pub buyer_sector: SectorEntity,
+    /// Buyers in the order in which they are visited.
+    pub stops: Vec<TradeStop>,
+}
+
+/// The ship looking for a trade run, and everything about it which affects how long that run takes.
+pub struct TradingShip {
+    pub entity: Entity,
+    pub sector: SectorEntity,
+    pub storage_capacity: u32,
+    pub max_speed: f32,
+    /// How many gates the ship may jump through to reach a seller, and from there to its final buyer.
+    pub max_jump_range: u8,
+    /// Limits how much the ship can buy.
+    pub credits: i64,
+}
+
+impl TradingShip {
+    fn seconds_per_jump(&self) -> f32 {
+        seconds_per_jump(self.max_speed)
+    }
+
+    fn estimated_travel_seconds(&self, jumps: u8, from: Vec2, to: Vec2) -> f32 {
+        estimated_travel_seconds(self.max_speed, jumps, from, to)
+    }
+}
+
+fn seconds_per_jump(max_speed: f32) -> f32 {
+    constants::SECTOR_SIZE / max_speed + constants::SECONDS_TO_TRAVEL_THROUGH_GATE
+}
+
+/// A rough estimate, which assumes that every sector on the way is crossed entirely at full speed.
+fn estimated_travel_seconds(max_speed: f32, jumps: u8, from: Vec2, to: Vec2) -> f32 {
+    if jumps == 0 {
+        from.distance(to) / max_speed
+    } else {
+        jumps as f32 * seconds_per_jump(max_speed)
+    }
+}
+
+/// Estimates when each exchange of a [TradePlan] happens along the route which has been planned for it, and gives it
+/// a deadline accordingly. Deadlines are registered in [DeliveryDeadlines], so missing them costs favor.
+pub struct DeliverySchedule<'a> {
+    ship: ShipEntity,
+    max_speed: f32,
+    now: CurrentSimulationTimestamp,
+    /// Estimated time from `now` until the most recently scheduled exchange is done.
+    seconds: f32,
+    deadlines: &'a mut DeliveryDeadlines,
+}
+
+impl<'a> DeliverySchedule<'a> {
+    pub fn new(
+        ship: Entity,
+        max_speed: f32,
+        now: CurrentSimulationTimestamp,
+        deadlines: &'a mut DeliveryDeadlines,
+    ) -> Self {
+        Self {
+            ship: ship.into(),
+            max_speed,
+            now,
+            seconds: 0.0,
+            deadlines,
+        }
+    }
+
+    /// Schedules an exchange after travelling from `from` to `to` through `jumps` gates.
+    fn next_deadline(&mut self, jumps: usize, from: Vec2, to: Vec2) -> SimulationTimestamp {
+        let jumps = u8::try_from(jumps).unwrap_or(u8::MAX);
+        self.seconds += estimated_travel_seconds(self.max_speed, jumps, from, to)
+            + constants::ESTIMATED_SECONDS_PER_TRADE_STOP;
+        let allowed_seconds = self.seconds * constants::DELIVERY_DEADLINE_TRAVEL_TIME_FACTOR
+            + constants::DELIVERY_DEADLINE_GRACE_SECONDS;
+
+        let deadline = self.now.add_milliseconds((allowed_seconds * 1000.0) as u64);
+        self.deadlines.add(deadline, self.ship);
+        deadline
+    }
+}
+
+struct BuyOffer {
+    buyer: Entity,
+    buyer_sector: SectorEntity,
+    jumps_from_seller: u8,
+    margin: u32,
+    price: u32,
+    amount: u32,
+    seconds_from_seller: f32,
+}
+
+impl BuyOffer {
+    fn to_stop(&self, amount: u32) -> TradeStop {
+        TradeStop {
+            buyer: TypedEntity::AnyWithInventory(self.buyer),
+            buyer_sector: self.buyer_sector,
+            amount,
+            price: self.price,
+        }
+    }
+}
+
+/// All stops at which the cargo of a single purchase gets sold.
+struct Route {
+    profit: u32,
+    /// Estimated time from leaving the seller until the last stop has been completed.
+    seconds: f32,
+    stops: Vec<TradeStop>,
+}
+
+/// A seller within the ship's jump range, together with one of the items it sells.
+struct SellerCandidate {
+    item_id: ItemId,
+    entity: Entity,
+    sector: SectorEntity,
+    position: Vec2,
+    price: u32,
+    /// How much the ship can buy here at once.
+    capacity: u32,
+    /// Estimated time to get to the seller and buy something there.
+    seconds_to_seller: f32,
+}
+
+/// Keeps track of the best trade run for a [TradingShip] while sellers and their offers are fed into it.
+struct TradeRunSearch<'a> {
+    ship: &'a TradingShip,
+    /// Filters out stations whose owners don't want the ship's owner to buy or sell there.
+    is_willing_to_trade: &'a dyn Fn(Entity, TradeIntent) -> bool,
+    ship_position: Vec2,
+    reachable_from_ship: HashMap<SectorEntity, u8>,
+    /// Jump distances from the sector of every seller which has been looked at so far.
+    reachable_from_sellers: HashMap<SectorEntity, HashMap<SectorEntity, u8>>,
+    /// Jump distances from the sector of every final buyer which has been looked at so far,
+    /// reaching far enough to include every buyer which might be visited on the way to it.
+    reachable_from_final_buyers: HashMap<SectorEntity, HashMap<SectorEntity, u8>>,
+    best: Option<(f32, TradePlan)>,
+}
+
+impl<'a> TradeRunSearch<'a> {
+    fn new(
+        ship: &'a TradingShip,
+        is_willing_to_trade: &'a dyn Fn(Entity, TradeIntent) -> bool,
+        all_sectors: &Query<&Sector>,
+        all_transforms: &Query<&SimulationTransform>,
+    ) -> Self {
+        Self {
+            ship,
+            is_willing_to_trade,
+            ship_position: all_transforms.get(ship.entity).unwrap().translation,
+            reachable_from_ship: jump_distances(all_sectors, ship.sector, ship.max_jump_range),
+            reachable_from_sellers: HashMap::new(),
+            reachable_from_final_buyers: HashMap::new(),
+            best: None,
+        }
+    }
+
+    /// Returns [None] if the seller is out of range, unwilling to trade or there's nothing the ship could buy.
+    fn seller(
+        &mut self,
+        item_id: ItemId,
+        sell_order: &MarketEntry,
+        all_sectors: &Query<&Sector>,
+        all_transforms: &Query<&SimulationTransform>,
+    ) -> Option<SellerCandidate> {
+        if !(self.is_willing_to_trade)(sell_order.entity, TradeIntent::Buy) {
+            return None;
+        }
+        let jumps_to_seller = *self.reachable_from_ship.get(&sell_order.sector)?;
+        let capacity = self
+            .ship
+            .storage_capacity
+            .min(sell_order.amount)
+            .min(affordable_amount(self.ship.credits, sell_order.price));
+        if capacity == 0 {
+            // TODO: Add custom definable minimum amount
+            return None;
+        }
+
+        let ship = self.ship;
+        self.reachable_from_sellers
+            .entry(sell_order.sector)
+            .or_insert_with(|| jump_distances(all_sectors, sell_order.sector, ship.max_jump_range));
+
+        let position = all_transforms.get(sell_order.entity).unwrap().translation;
+        Some(SellerCandidate {
+            item_id,
+            entity: sell_order.entity,
+            sector: sell_order.sector,
+            position,
+            price: sell_order.price,
+            capacity,
+            seconds_to_seller: ship.estimated_travel_seconds(
+                jumps_to_seller,
+                self.ship_position,
+                position,
+            ) + constants::ESTIMATED_SECONDS_PER_TRADE_STOP,
+        })
+    }
+
+    fn sectors_reachable_from(
+        &self,
+        seller: &SellerCandidate,
+    ) -> impl Iterator<Item = &SectorEntity> {
+        self.reachable_from_sellers[&seller.sector].keys()
+    }
+
+    /// Returns [None] if selling to `buy_order` isn't profitable, it is out of range or unwilling to trade.
+    fn offer(
+        &self,
+        seller: &SellerCandidate,
+        buy_order: &MarketEntry,
+        all_transforms: &Query<&SimulationTransform>,
+    ) -> Option<BuyOffer> {
+        if buy_order.entity == seller.entity
+            || buy_order.price <= seller.price
+            || buy_order.amount == 0
+            || !(self.is_willing_to_trade)(buy_order.entity, TradeIntent::Sell)
+        {
+            return None;
+        }
+        let jumps = *self.reachable_from_sellers[&seller.sector].get(&buy_order.sector)?;
+
+        Some(BuyOffer {
+            buyer: buy_order.entity,
+            buyer_sector: buy_order.sector,
+            jumps_from_seller: jumps,
+            margin: buy_order.price - seller.price,
+            price: buy_order.price,
+            amount: buy_order.amount,
+            seconds_from_seller: self.ship.estimated_travel_seconds(
+                jumps,
+                seller.position,
+                all_transforms.get(buy_order.entity).unwrap().translation,
+            ),
+        })
+    }
+
+    /// Plans the best route to sell what can be bought from `seller`, and keeps it if it beats the best one so far.
+    fn consider(
+        &mut self,
+        seller: SellerCandidate,
+        offers: &[BuyOffer],
+        all_sectors: &Query<&Sector>,
+    ) {
+        let ship = self.ship;
+        let reachable_from_final_buyers = &mut self.reachable_from_final_buyers;
+        let mut jumps_between = |final_buyer: SectorEntity, other: SectorEntity| {
+            reachable_from_final_buyers
+                .entry(final_buyer)
+                .or_insert_with(|| {
+                    jump_distances(
+                        all_sectors,
+                        final_buyer,
+                        ship.max_jump_range + constants::MAX_TRADE_ROUTE_DETOUR_IN_JUMPS,
+                    )
+                })
+                .get(&other)
+                .copied()
+        };
+        let Some(route) = plan_route(
+            offers,
+            seller.capacity,
+            ship.seconds_per_jump(),
+            &mut jumps_between,
+        ) else {
+            return;
+        };
+
+        let score = route.profit as f32 / (seller.seconds_to_seller + route.seconds);
+        let is_this_a_better_offer = if let Some((best_score, _)) = &self.best {
+            score > *best_score
+        } else {
+            true
+        };
+
+        if is_this_a_better_offer {
+            self.best = Some((
+                score,
+                TradePlan {
+                    item_id: seller.item_id,
+                    amount: route.stops.iter().map(|stop| stop.amount).sum(),
+                    price: seller.price,
+                    profit: route.profit,
+                    seller: TypedEntity::AnyWithInventory(seller.entity),
+                    seller_sector: seller.sector,
+                    stops: route.stops,
+                },
+            ));
+        }
+    }
+
+    fn best_plan(self) -> Option<TradePlan> {
+        self.best.map(|(_, plan)| plan)
+    }
 }
 
 impl TradePlan {
+    /// Finds the trade run which yields the most profit per second for `ship`, including the time it takes to
+    /// get to the seller. If the final buyer can't take a full load, the remaining capacity is sold to other
+    /// buyers which are on the way to it.
+    ///
+    /// Stations for which `is_willing_to_trade` returns false when the ship intends to buy or sell there are ignored.
     pub fn search_for_trade_run(
-        storage_capacity: u32,
-        buy_orders: &Query<(Entity, &mut BuyOrders, &InSector)>,
-        sell_orders: &Query<(Entity, &mut SellOrders, &InSector)>,
+        ship: &TradingShip,
+        market: &MarketIndex,
+        is_willing_to_trade: &dyn Fn(Entity, TradeIntent) -> bool,
+        all_sectors: &Query<&Sector>,
+        all_transforms: &Query<&SimulationTransform>,
     ) -> Option<Self> {
-        let mut best_offer: Option<TradePlan> = None;
-
-        for (buyer, buy_orders, buyer_sector) in buy_orders.iter() {
-            for (seller, sell_orders, seller_sector) in sell_orders.iter() {
-                if buyer == seller {
-                    continue;
-                }
-
-                for (item_id, buy_order) in buy_orders.orders() {
-                    if let Some(sell_order) = sell_orders.orders().get(item_id) {
-                        if sell_order.price >= buy_order.price {
-                            continue;
-                        }
+        let mut search =
+            TradeRunSearch::new(ship, is_willing_to_trade, all_sectors, all_transforms);
 
-                        let amount = storage_capacity.min(buy_order.amount.min(sell_order.amount));
-                        if amount == 0 {
-                            // TODO: Add custom definable minimum amount
-                            continue;
-                        }
+        // Offers with equal scores are resolved by whichever comes first, so iteration order must be stable
+        let mut sell_orders: Vec<(ItemId, &MarketEntry)> = search
+            .reachable_from_ship
+            .keys()
+            .flat_map(|sector| market.sell_orders_in(*sector))
+            .collect();
+        sell_orders.sort_by_key(|(item_id, sell_order)| (sell_order.station, *item_id));
 
-                        let profit = (buy_order.price - sell_order.price) * amount;
+        for (item_id, sell_order) in sell_orders {
+            let Some(seller) = search.seller(item_id, sell_order, all_sectors, all_transforms)
+            else {
+                continue;
+            };
 
-                        let is_this_a_better_offer = if let Some(existing_offer) = &best_offer {
-                            profit > existing_offer.profit
-                        } else {
-                            true
-                        };
+            let mut buy_orders: Vec<&MarketEntry> = search
+                .sectors_reachable_from(&seller)
+                .flat_map(|sector| market.buy_orders_above(*sector, item_id, seller.price))
+                .collect();
+            buy_orders.sort_by_key(|buy_order| buy_order.station);
 
-                        if is_this_a_better_offer {
-                            best_offer = Some(TradePlan {
-                                item_id: *item_id,
-                                amount,
-                                profit,
-                                seller: TypedEntity::AnyWithInventory(seller),
-                                seller_sector: seller_sector.get(),
-                                buyer: TypedEntity::AnyWithInventory(buyer),
-                                buyer_sector: buyer_sector.get(),
-                            });
-                        }
-                    }
-                }
-            }
+            let offers: Vec<BuyOffer> = buy_orders
+                .into_iter()
+                .filter_map(|buy_order| search.offer(&seller, buy_order, all_transforms))
+                .collect();
+            search.consider(seller, &offers, all_sectors);
         }
 
-        best_offer
+        search.best_plan()
     }
 
+    /// Stations for which `is_willing_to_trade` returns false when `seller` intends to sell there are ignored.
     pub fn sell_anything_from_inventory(
         seller: Entity,
         seller_sector: &InSector,
         inventory: &Inventory,
-        buy_orders: &Query<(Entity, &mut BuyOrders, &InSector)>,
+        buy_orders: &Query<(Entity, &Station, &mut BuyOrders, &InSector)>,
+        is_willing_to_trade: &dyn Fn(Entity, TradeIntent) -> bool,
     ) -> Option<Self> {
         let mut best_offer: Option<TradePlan> = None;
 
-        for (buyer, buy_orders, buyer_sector) in buy_orders.iter() {
-            if seller == buyer {
+        for (buyer, _, buy_orders, buyer_sector) in sorted_by_station_id(buy_orders) {
+            if seller == buyer || !is_willing_to_trade(buyer, TradeIntent::Sell) {
                 continue;
             }
 
-            for (item_id, inventory_entry) in inventory.inventory() {
+            for (item_id, inventory_entry) in sorted_by_item_id(inventory.inventory()) {
                 if let Some(buy_order) = buy_orders.orders().get(item_id) {
                     let amount = inventory_entry.total.min(buy_order.amount);
                     if amount == 0 {
@@ -102,11 +412,16 @@ impl TradePlan {
                         best_offer = Some(TradePlan {
                             item_id: *item_id,
                             amount,
+                            price: 0,
                             profit,
                             seller: TypedEntity::AnyWithInventory(seller),
                             seller_sector: seller_sector.get(),
-                            buyer: TypedEntity::AnyWithInventory(buyer),
-                            buyer_sector: buyer_sector.get(),
+                            stops: vec![TradeStop {
+                                buyer: TypedEntity::AnyWithInventory(buyer),
+                                buyer_sector: buyer_sector.get(),
+                                amount,
+                                price: buy_order.price,
+                            }],
                         });
                     }
                 }
@@ -122,20 +437,24 @@ impl TradePlan {
         all_transforms: &Query<&SimulationTransform>,
         ship_entity: Entity,
         ship_sector: &InSector,
+        schedule: &mut DeliverySchedule,
         queue: &mut TaskQueue,
     ) {
+        let ship_pos = all_transforms.get(ship_entity).unwrap().translation;
+        let seller_pos = all_transforms.get(self.seller.into()).unwrap().translation;
+        let mut jumps = 0;
         if ship_sector != self.seller_sector {
-            let ship_pos = all_transforms.get(ship_entity).unwrap().translation;
             let path = pathfinding::find_path(
                 all_sectors,
                 all_transforms,
                 ship_sector.get(),
                 ship_pos,
                 self.seller_sector,
-                Some(all_transforms.get(self.seller.into()).unwrap().translation),
+                Some(seller_pos),
             )
             .unwrap();
 
+            jumps = path.len();
             pathfinding::create_tasks_to_follow_path(queue, path);
         }
 
@@ -152,43 +471,529 @@ impl TradePlan {
         });
         queue.push_back(TaskInsideQueue::ExchangeWares {
             target: self.seller,
-            data: ExchangeWareData::Buy(self.item_id, self.amount),
+            data: ExchangeWareData::Buy(self.item_id, self.amount, self.price),
+            deadline: schedule.next_deadline(jumps, ship_pos, seller_pos),
         });
         queue.push_back(TaskInsideQueue::Undock) // TODO: Ideally that should be added dynamically at the start of MoveToEntity if we are docked
     }
 
+    /// Reserves the goods which will be sold at each [TradeStop], both inside `ship`s inventory and the buyer's.
+    pub fn create_orders_for_sale(&self, ship: Entity, inventories: &mut Query<&mut Inventory>) {
+        for stop in &self.stops {
+            let [mut ship_inventory, mut buyer_inventory] =
+                inventories.get_many_mut([ship, stop.buyer.into()]).unwrap();
+
+            ship_inventory.create_order(self.item_id, TradeIntent::Sell, stop.amount);
+            buyer_inventory.create_order(self.item_id, TradeIntent::Buy, stop.amount);
+        }
+    }
+
+    /// Reserves the agreed price of every exchange in the payer's [Wallet]. The ship pays for the purchase through its
+    /// [crate::components::ShipAccount] and every buyer pays for what is sold to it.
+    pub fn reserve_credits(
+        &self,
+        ship: Entity,
+        all_wallets: &mut Query<&mut Wallet>,
+        all_owners: &Query<&Owner>,
+        session_data: &mut SessionData,
+    ) {
+        if self.price > 0 {
+            if let Some(wallet) = ship_wallet(ship, all_wallets, all_owners, session_data) {
+                wallet.reserve(self.price as i64 * self.amount as i64);
+            }
+        }
+
+        for stop in &self.stops {
+            if let Ok(mut wallet) = all_wallets.get_mut(stop.buyer.into()) {
+                wallet.reserve(stop.price as i64 * stop.amount as i64);
+            }
+        }
+    }
+
+    /// Creates the tasks to deliver the cargo to all [TradeStop]s, starting at the seller.
     pub fn create_tasks_for_sale(
         &self,
         all_sectors: &Query<&Sector>,
         all_transforms: &Query<&SimulationTransform>,
+        schedule: &mut DeliverySchedule,
         queue: &mut TaskQueue,
     ) {
-        if self.seller_sector != self.buyer_sector {
-            let seller_pos = all_transforms.get(self.seller.into()).unwrap().translation;
-            let path = pathfinding::find_path(
-                all_sectors,
-                all_transforms,
-                self.seller_sector,
-                seller_pos,
-                self.buyer_sector,
-                Some(all_transforms.get(self.buyer.into()).unwrap().translation),
+        let mut previous = self.seller;
+        let mut previous_sector = self.seller_sector;
+        for stop in &self.stops {
+            let previous_pos = all_transforms.get(previous.into()).unwrap().translation;
+            let buyer_pos = all_transforms.get(stop.buyer.into()).unwrap().translation;
+            let mut jumps = 0;
+            if previous_sector != stop.buyer_sector {
+                let path = pathfinding::find_path(
+                    all_sectors,
+                    all_transforms,
+                    previous_sector,
+                    previous_pos,
+                    stop.buyer_sector,
+                    Some(buyer_pos),
+                )
+                .unwrap();
+
+                jumps = path.len();
+                pathfinding::create_tasks_to_follow_path(queue, path);
+            }
+
+            queue.push_back(TaskInsideQueue::MoveToEntity {
+                target: stop.buyer,
+                stop_at_target: true,
+                distance_to_target: constants::DOCKING_DISTANCE_TO_STATION,
+            });
+            queue.push_back(TaskInsideQueue::RequestAccess { target: stop.buyer });
+            queue.push_back(TaskInsideQueue::DockAtEntity { target: stop.buyer });
+            queue.push_back(TaskInsideQueue::ExchangeWares {
+                target: stop.buyer,
+                data: ExchangeWareData::Sell(self.item_id, stop.amount, stop.price),
+                deadline: schedule.next_deadline(jumps, previous_pos, buyer_pos),
+            });
+            queue.push_back(TaskInsideQueue::Undock); // TODO: Ideally that should be added dynamically at the start of MoveToEntity if we are docked
+
+            previous = stop.buyer;
+            previous_sector = stop.buyer_sector;
+        }
+    }
+}
+
+/// Picks the offer which yields the most profit per second on its own. In case it can't take all of `capacity`,
+/// the remainder is split across the offers with the best margins which barely require any detour to get there.
+///
+/// Detours are measured in gate jumps, `jumps_between` returns how many it takes to get from the final buyer's sector
+/// to another one, or [None] if that one is out of range. Gates work in both directions, so that's also the way back.
+///
+/// Stops are ordered by their jump distance to the seller.
+fn plan_route(
+    offers: &[BuyOffer],
+    capacity: u32,
+    seconds_per_jump: f32,
+    jumps_between: &mut dyn FnMut(SectorEntity, SectorEntity) -> Option<u8>,
+) -> Option<Route> {
+    let seconds_until_done =
+        |offer: &BuyOffer| offer.seconds_from_seller + constants::ESTIMATED_SECONDS_PER_TRADE_STOP;
+
+    // max_by returns the last maximum, but earlier offers should win ties
+    let final_offer = offers.iter().rev().max_by(|a, b| {
+        let profit_per_second = |offer: &BuyOffer| {
+            (offer.margin * offer.amount.min(capacity)) as f32 / seconds_until_done(offer)
+        };
+        profit_per_second(*a).total_cmp(&profit_per_second(*b))
+    })?;
+
+    let mut remaining = capacity - final_offer.amount.min(capacity);
+    let mut profit = final_offer.margin * (capacity - remaining);
+    let mut seconds = seconds_until_done(final_offer);
+    let mut additional_stops = Vec::new();
+    if remaining > 0 {
+        let mut candidates: Vec<(&BuyOffer, u8)> = offers
+            .iter()
+            .filter(|offer| offer.buyer != final_offer.buyer)
+            .filter_map(|offer| {
+                let jumps_to_final_buyer =
+                    jumps_between(final_offer.buyer_sector, offer.buyer_sector)?;
+                let detour = (offer.jumps_from_seller + jumps_to_final_buyer)
+                    .saturating_sub(final_offer.jumps_from_seller);
+                (detour <= constants::MAX_TRADE_ROUTE_DETOUR_IN_JUMPS).then_some((offer, detour))
+            })
+            .collect();
+        // Stable, so offers with equal margins remain sorted by station ID
+        candidates.sort_by_key(|(offer, _)| std::cmp::Reverse(offer.margin));
+
+        for (offer, detour) in candidates
+            .into_iter()
+            .take(constants::MAX_TRADE_ROUTE_STOPS - 1)
+        {
+            let amount = remaining.min(offer.amount);
+            profit += offer.margin * amount;
+            seconds +=
+                detour as f32 * seconds_per_jump + constants::ESTIMATED_SECONDS_PER_TRADE_STOP;
+            remaining -= amount;
+            additional_stops.push((offer, amount));
+            if remaining == 0 {
+                break;
+            }
+        }
+
+        additional_stops.sort_by_key(|(offer, _)| offer.jumps_from_seller);
+    }
+
+    let stops = additional_stops
+        .into_iter()
+        .map(|(offer, amount)| offer.to_stop(amount))
+        .chain(std::iter::once(
+            final_offer.to_stop(capacity.min(final_offer.amount)),
+        ))
+        .collect();
+
+    Some(Route {
+        profit,
+        seconds,
+        stops,
+    })
+}
+
+/// Returns how many gate jumps it takes to get from `from` to every sector within `max_range`.
+fn jump_distances(
+    all_sectors: &Query<&Sector>,
+    from: SectorEntity,
+    max_range: u8,
+) -> HashMap<SectorEntity, u8> {
+    let mut result = HashMap::new();
+    for sector in surrounding_sector_search(all_sectors, from, 0, max_range, all_sectors, |_| true)
+    {
+        // Results are ordered by distance, so the first entry for every sector is the shortest one
+        result.entry(sector.sector).or_insert(sector.distance);
+    }
+
+    result
+}
+
+fn sorted_by_station_id<'a, T>(
+    query: &'a Query<(Entity, &Station, &mut T, &InSector)>,
+) -> Vec<(Entity, &'a Station, &'a T, &'a InSector)>
+where
+    T: Component,
+{
+    let mut result: Vec<_> = query.iter().collect();
+    result.sort_by_key(|(_, station, ..)| station.id);
+    result
+}
+
+fn sorted_by_item_id<T>(map: &HashMap<ItemId, T>) -> Vec<(&ItemId, &T)> {
+    let mut result: Vec<_> = map.iter().collect();
+    result.sort_by_key(|(item_id, _)| **item_id);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Engine, SellOrders, Ship};
+    use crate::persistence::universe_generator::UniverseGenerator;
+    use crate::simulation::market_index::update_market_index;
+    use crate::simulation::test_app::SimulationTestApp;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::{Res, With};
+    use hexx::Hex;
+    use std::collections::VecDeque;
+
+    const SECONDS_PER_JUMP: f32 = 10.0;
+
+    /// The item, seller, buyer and profit of a trade run with a single stop.
+    type RunSummary = (ItemId, Entity, Entity, u32);
+
+    fn summarize(plan: Option<TradePlan>) -> Option<RunSummary> {
+        plan.map(|plan| {
+            assert_eq!(plan.stops.len(), 1);
+            (
+                plan.item_id,
+                plan.seller.into(),
+                plan.stops[0].buyer.into(),
+                plan.profit,
             )
-            .unwrap();
+        })
+    }
 
-            pathfinding::create_tasks_to_follow_path(queue, path);
+    /// Breadth-first search through the gates of every sector, without any range limit.
+    fn jumps_between(
+        all_sectors: &Query<&Sector>,
+        from: SectorEntity,
+        to: SectorEntity,
+    ) -> Option<u8> {
+        let mut distances = HashMap::new();
+        distances.insert(from, 0);
+        let mut queue = VecDeque::from([from]);
+        while let Some(sector) = queue.pop_front() {
+            let distance = distances[&sector];
+            if sector == to {
+                return Some(distance);
+            }
+
+            for next in all_sectors.get(sector.into()).unwrap().gates.keys() {
+                if !distances.contains_key(next) {
+                    distances.insert(*next, distance + 1);
+                    queue.push_back(*next);
+                }
+            }
         }
 
-        queue.push_back(TaskInsideQueue::MoveToEntity {
-            target: self.buyer,
-            stop_at_target: true,
-            distance_to_target: constants::DOCKING_DISTANCE_TO_STATION,
-        });
-        queue.push_back(TaskInsideQueue::RequestAccess { target: self.buyer });
-        queue.push_back(TaskInsideQueue::DockAtEntity { target: self.buyer });
-        queue.push_back(TaskInsideQueue::ExchangeWares {
-            target: self.buyer,
-            data: ExchangeWareData::Sell(self.item_id, self.amount),
-        });
-        queue.push_back(TaskInsideQueue::Undock) // TODO: Ideally that should be added dynamically at the start of MoveToEntity if we are docked
+        None
+    }
+
+    fn travel_seconds(ship: &TradingShip, jumps: u8, from: Vec2, to: Vec2) -> f32 {
+        if jumps == 0 {
+            from.distance(to) / ship.max_speed
+        } else {
+            jumps as f32
+                * (constants::SECTOR_SIZE / ship.max_speed
+                    + constants::SECONDS_TO_TRAVEL_THROUGH_GATE)
+        }
+    }
+
+    /// A plain loop over every pair of stations, which neither uses the [MarketIndex] nor [TradeRunSearch].
+    ///
+    /// Only works for ships which carry a single item, so every run ends at its first buyer.
+    /// Just like the actual search, the buyer is picked per seller before the time to get to the seller is added.
+    fn best_single_item_run(
+        ship: &TradingShip,
+        stations: &[(
+            Entity,
+            &Station,
+            Option<&BuyOrders>,
+            Option<&SellOrders>,
+            &InSector,
+        )],
+        all_sectors: &Query<&Sector>,
+        all_transforms: &Query<&SimulationTransform>,
+    ) -> Option<RunSummary> {
+        assert_eq!(ship.storage_capacity, 1);
+        let stop = constants::ESTIMATED_SECONDS_PER_TRADE_STOP;
+        let ship_position = all_transforms.get(ship.entity).unwrap().translation;
+        let mut best: Option<(f32, RunSummary)> = None;
+
+        for (seller, _, _, sell_orders, seller_sector) in stations {
+            let Some(sell_orders) = sell_orders else {
+                continue;
+            };
+            let Some(jumps_to_seller) =
+                jumps_between(all_sectors, ship.sector, seller_sector.get())
+                    .filter(|jumps| *jumps <= ship.max_jump_range)
+            else {
+                continue;
+            };
+            let seller_position = all_transforms.get(*seller).unwrap().translation;
+            let seconds_to_seller =
+                travel_seconds(ship, jumps_to_seller, ship_position, seller_position) + stop;
+
+            for (item_id, sell_order) in sorted_by_item_id(sell_orders.orders()) {
+                if sell_order.amount == 0 || affordable_amount(ship.credits, sell_order.price) == 0
+                {
+                    continue;
+                }
+
+                let mut best_buyer: Option<(f32, Entity, u32)> = None;
+                for (buyer, _, buy_orders, _, buyer_sector) in stations {
+                    let Some(buy_order) =
+                        buy_orders.and_then(|orders| orders.orders().get(item_id))
+                    else {
+                        continue;
+                    };
+                    if buyer == seller
+                        || buy_order.price <= sell_order.price
+                        || buy_order.amount == 0
+                    {
+                        continue;
+                    }
+                    let Some(jumps) =
+                        jumps_between(all_sectors, seller_sector.get(), buyer_sector.get())
+                            .filter(|jumps| *jumps <= ship.max_jump_range)
+                    else {
+                        continue;
+                    };
+
+                    let margin = buy_order.price - sell_order.price;
+                    let buyer_position = all_transforms.get(*buyer).unwrap().translation;
+                    let seconds =
+                        travel_seconds(ship, jumps, seller_position, buyer_position) + stop;
+                    if best_buyer.map_or(true, |(best_seconds, _, best_margin)| {
+                        margin as f32 / seconds > best_margin as f32 / best_seconds
+                    }) {
+                        best_buyer = Some((seconds, *buyer, margin));
+                    }
+                }
+
+                let Some((seconds, buyer, margin)) = best_buyer else {
+                    continue;
+                };
+                let score = margin as f32 / (seconds_to_seller + seconds);
+                if best
+                    .as_ref()
+                    .map_or(true, |(best_score, _)| score > *best_score)
+                {
+                    best = Some((score, (*item_id, *seller, buyer, margin)));
+                }
+            }
+        }
+
+        best.map(|(_, run)| run)
+    }
+
+    #[test]
+    fn market_index_finds_the_same_trade_runs_as_looking_at_every_station() {
+        let data = UniverseGenerator::new(7, 2).with_ships(20).generate();
+        let mut app = SimulationTestApp::new(data);
+        // Let production and trading move inventories and prices around a bit
+        app.run_ticks(100);
+
+        let world = app.app.world_mut();
+        // A freshly created system considers every order as changed, so this syncs the entire index
+        world.run_system_once(update_market_index);
+        let found_plans = world.run_system_once(
+            |ships: Query<(Entity, &InSector, &Engine, &Wallet), With<Ship>>,
+             market: Res<MarketIndex>,
+             stations: Query<(
+                Entity,
+                &Station,
+                Option<&BuyOrders>,
+                Option<&SellOrders>,
+                &InSector,
+            )>,
+             all_sectors: Query<&Sector>,
+             all_transforms: Query<&SimulationTransform>| {
+                let mut stations: Vec<_> = stations.iter().collect();
+                stations.sort_by_key(|(_, station, ..)| station.id);
+
+                let mut found_plans = 0;
+                for (entity, sector, engine, wallet) in &ships {
+                    for max_jump_range in 0..=constants::DEFAULT_MAX_TRADE_JUMP_RANGE {
+                        let ship = TradingShip {
+                            entity,
+                            sector: sector.get(),
+                            storage_capacity: 1,
+                            max_speed: engine.max_speed,
+                            max_jump_range,
+                            credits: wallet.credits(),
+                        };
+
+                        let indexed = summarize(TradePlan::search_for_trade_run(
+                            &ship,
+                            &market,
+                            &|_, _| true,
+                            &all_sectors,
+                            &all_transforms,
+                        ));
+                        let expected =
+                            best_single_item_run(&ship, &stations, &all_sectors, &all_transforms);
+
+                        assert_eq!(
+                            indexed, expected,
+                            "Ship {entity:?} found a different plan with a jump range of {max_jump_range}!"
+                        );
+                        if indexed.is_some() {
+                            found_plans += 1;
+                        }
+                    }
+                }
+
+                found_plans
+            },
+        );
+
+        assert!(found_plans > 0, "Nothing has been compared!");
+    }
+
+    fn offer(buyer: u32, jumps_from_seller: u8, margin: u32, amount: u32) -> BuyOffer {
+        BuyOffer {
+            buyer: Entity::from_raw(buyer),
+            buyer_sector: SectorEntity::from(buyer),
+            jumps_from_seller,
+            margin,
+            price: margin,
+            amount,
+            seconds_from_seller: 10.0,
+        }
+    }
+
+    /// Every buyer gets its own sector at the given coordinate, with gates connecting all neighboring sectors.
+    /// The seller is located at [Hex::ZERO].
+    #[derive(Default)]
+    struct HexGrid(HashMap<SectorEntity, Hex>);
+
+    impl HexGrid {
+        fn offer(&mut self, buyer: u32, coordinate: Hex, margin: u32, amount: u32) -> BuyOffer {
+            self.0.insert(SectorEntity::from(buyer), coordinate);
+            offer(
+                buyer,
+                Hex::ZERO.unsigned_distance_to(coordinate) as u8,
+                margin,
+                amount,
+            )
+        }
+
+        fn plan_route(&self, offers: &[BuyOffer], capacity: u32) -> Option<Route> {
+            plan_route(offers, capacity, SECONDS_PER_JUMP, &mut |from, to| {
+                Some(self.0[&from].unsigned_distance_to(self.0[&to]) as u8)
+            })
+        }
+    }
+
+    #[test]
+    fn remaining_capacity_is_sold_to_buyers_on_the_way() {
+        let mut grid = HexGrid::default();
+        let offers = [
+            grid.offer(1, Hex::new(3, 0), 10, 60),
+            grid.offer(2, Hex::new(1, 0), 5, 100),
+            // Too far off the route towards buyer 1
+            grid.offer(3, Hex::new(0, 3), 5, 30),
+            grid.offer(4, Hex::new(2, 0), 7, 10),
+        ];
+
+        let route = grid.plan_route(&offers, 100).unwrap();
+
+        assert_eq!(route.profit, 60 * 10 + 10 * 7 + 30 * 5);
+        // Both additional stops are directly on the way, so only their stop durations are added
+        assert_eq!(
+            route.seconds,
+            10.0 + 3.0 * constants::ESTIMATED_SECONDS_PER_TRADE_STOP
+        );
+        let stops: Vec<_> = route
+            .stops
+            .iter()
+            .map(|stop| (Entity::from(stop.buyer), stop.amount))
+            .collect();
+        assert_eq!(
+            stops,
+            vec![
+                (Entity::from_raw(2), 30),
+                (Entity::from_raw(4), 10),
+                (Entity::from_raw(1), 60),
+            ]
+        );
+    }
+
+    #[test]
+    fn buyers_without_gates_towards_the_final_buyer_are_not_on_the_way() {
+        let offers = [offer(1, 2, 10, 60), offer(2, 1, 5, 100)];
+
+        // Buyer 2 is right next to the seller, but only connected to the final buyer through the seller's sector
+        let route = plan_route(&offers, 100, SECONDS_PER_JUMP, &mut |_, _| Some(3)).unwrap();
+
+        assert_eq!(route.profit, 60 * 10);
+        assert_eq!(route.stops.len(), 1);
+    }
+
+    #[test]
+    fn single_buyer_taking_everything_needs_no_further_stops() {
+        let mut grid = HexGrid::default();
+        let offers = [
+            grid.offer(1, Hex::new(1, 0), 5, 500),
+            grid.offer(2, Hex::new(1, 0), 4, 500),
+        ];
+
+        let route = grid.plan_route(&offers, 100).unwrap();
+
+        assert_eq!(route.profit, 500);
+        assert_eq!(route.stops.len(), 1);
+        assert_eq!(Entity::from(route.stops[0].buyer), Entity::from_raw(1));
+    }
+
+    #[test]
+    fn nearby_buyers_win_over_slightly_more_profitable_distant_ones() {
+        let mut grid = HexGrid::default();
+        let offers = [
+            BuyOffer {
+                seconds_from_seller: 100.0,
+                ..grid.offer(1, Hex::new(10, 0), 10, 100)
+            },
+            grid.offer(2, Hex::new(1, 0), 8, 100),
+        ];
+
+        let route = grid.plan_route(&offers, 100).unwrap();
+
+        assert_eq!(route.profit, 800);
+        assert_eq!(Entity::from(route.stops[0].buyer), Entity::from_raw(2));
     }
 }
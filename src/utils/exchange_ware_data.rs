@@ -1,7 +1,20 @@
 use crate::game_data::ItemId;
 
+/// The item and amount which are exchanged, as well as the price per item which was agreed upon while planning
+/// the trade. That price is paid once the exchange is completed, no matter how prices have changed since.
 #[derive(Copy, Clone)]
 pub enum ExchangeWareData {
-    Buy(ItemId, u32),
-    Sell(ItemId, u32),
+    Buy(ItemId, u32, u32),
+    Sell(ItemId, u32, u32),
+}
+
+impl ExchangeWareData {
+    /// How many credits are paid for the exchange.
+    pub fn value(&self) -> i64 {
+        match *self {
+            ExchangeWareData::Buy(_, amount, price) | ExchangeWareData::Sell(_, amount, price) => {
+                price as i64 * amount as i64
+            }
+        }
+    }
 }
@@ -14,7 +14,8 @@ use bevy::prelude::{Commands, Rot2, Sprite, SpriteBundle};
 pub fn spawn_asteroid(
     commands: &mut Commands,
     asteroid_id_map: &mut AsteroidIdMap,
-    sprites: &SpriteHandles,
+    sprites: Option<&SpriteHandles>,
+    asteroid_id: PersistentAsteroidId,
     name: String,
     global_pos: Vec2,
     asteroid_feature: &mut SectorAsteroidComponent,
@@ -27,7 +28,6 @@ pub fn spawn_asteroid(
     despawn_at: SimulationTimestamp,
     fading_in: bool,
 ) -> AsteroidEntity {
-    let asteroid_id = PersistentAsteroidId::next();
     let asteroid = Asteroid::new(asteroid_id, ore_current, ore_max, despawn_at);
 
     let simulation_transform = SimulationTransform::new(
@@ -36,24 +36,28 @@ pub fn spawn_asteroid(
         asteroid.scale_depending_on_current_ore_volume(),
     );
 
-    let entity = commands
-        .spawn((
-            Name::new(name),
-            SelectableEntity::Asteroid,
-            ConstantVelocity::new(velocity, angular_velocity),
-            SpriteBundle {
-                texture: sprites.asteroid.clone(),
-                transform: simulation_transform.as_transform(constants::z_layers::ASTEROID),
-                sprite: Sprite {
-                    color: Color::linear_rgba(1.0, 1.0, 1.0, if fading_in { 0.0 } else { 1.0 }),
-                    ..Default::default()
-                },
-                ..Default::default()
-            },
-            simulation_transform,
-            asteroid,
-        ))
-        .id();
+    let sprite = sprites.map(|sprites| SpriteBundle {
+        texture: sprites.asteroid.clone(),
+        transform: simulation_transform.as_transform(constants::z_layers::ASTEROID),
+        sprite: Sprite {
+            color: Color::linear_rgba(1.0, 1.0, 1.0, if fading_in { 0.0 } else { 1.0 }),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+
+    let mut entity_commands = commands.spawn((
+        Name::new(name),
+        SelectableEntity::Asteroid,
+        ConstantVelocity::new(velocity, angular_velocity),
+        simulation_transform,
+        asteroid,
+    ));
+    if let Some(sprite) = sprite {
+        entity_commands.insert(sprite);
+    }
+
+    let entity = entity_commands.id();
 
     asteroid_id_map.insert(asteroid_id, AsteroidEntity::from(entity));
     asteroid_feature.add_asteroid(
@@ -18,7 +18,7 @@ pub fn spawn_gate_pair(
     gate_id_map: &mut GateIdMap,
     sectors: &mut Query<(&mut Sector, Option<&SectorStarComponent>)>,
     stars: &Query<&Star>,
-    sprites: &SpriteHandles,
+    sprites: Option<&SpriteHandles>,
     from_id: PersistentGateId,
     from_pos: SectorPosition,
     to_id: PersistentGateId,
@@ -89,7 +89,7 @@ fn spawn_gate(
     commands: &mut Commands,
     id: PersistentGateId,
     gate_id_map: &mut GateIdMap,
-    sprites: &SpriteHandles,
+    sprites: Option<&SpriteHandles>,
     pos: &SectorPosition,
     from: &mut Sector,
     to: &Sector,
@@ -98,6 +98,12 @@ fn spawn_gate(
 ) -> GateEntity {
     let simulation_transform =
         SimulationTransform::from_translation(from.world_pos + pos.local_position);
+    let sprite = sprites.map(|sprites| SpriteBundle {
+        transform: simulation_transform.as_transform(constants::z_layers::GATE),
+        texture: sprites.gate.clone(),
+        ..Default::default()
+    });
+
     let mut entity_commands = commands.spawn((
         Name::new(format!(
             "Gate [{},{}] -> [{},{}]",
@@ -105,13 +111,11 @@ fn spawn_gate(
         )),
         Gate::new(id, ship_curve),
         SelectableEntity::Gate,
-        SpriteBundle {
-            transform: simulation_transform.as_transform(constants::z_layers::GATE),
-            texture: sprites.gate.clone(),
-            ..Default::default()
-        },
         simulation_transform,
     ));
+    if let Some(sprite) = sprite {
+        entity_commands.insert(sprite);
+    }
 
     if let Some(star) = star {
         let radius = pos.local_position.length();
@@ -15,7 +15,7 @@ pub fn spawn_planet(
     commands: &mut Commands,
     sector_planet_component: &mut components::SectorPlanets,
     planet_id_map: &mut PlanetIdMap,
-    sprites: &SpriteHandles,
+    sprites: Option<&SpriteHandles>,
     planet_data: &SectorPlanetSaveData,
     sector_pos: Vec2,
     sector_entity: SectorEntity,
@@ -38,6 +38,12 @@ pub fn spawn_planet(
     let simulation_transform =
         SimulationTransform::new(sector_pos + local_position, Rot2::IDENTITY, 1.0);
 
+    let sprite = sprites.map(|sprites| SpriteBundle {
+        texture: sprites.planet.clone(),
+        transform: simulation_transform.as_transform(constants::z_layers::PLANET_AND_STARS),
+        ..default()
+    });
+
     let entity = commands
         .spawn((
             Name::new(planet_data.name.clone()),
@@ -48,15 +54,13 @@ pub fn spawn_planet(
                 planet_data.orbit.radius,
                 velocity,
             ),
-            SpriteBundle {
-                texture: sprites.planet.clone(),
-                transform: simulation_transform.as_transform(constants::z_layers::PLANET_AND_STARS),
-                ..default()
-            },
             simulation_transform,
             planet,
         ))
         .id();
+    if let Some(sprite) = sprite {
+        commands.entity(entity).insert(sprite);
+    }
 
     match planet_data.kind {
         PlanetKindSaveData::Terrestrial => {}
@@ -17,7 +17,7 @@ pub fn spawn_sector(
     layout: &HexLayout,
     coordinate: Hex,
     features: &SectorFeatureSaveData, // Create a feature list if we ever want to spawn sectors from something else than save data, but for now that's enough
-    sprites: &SpriteHandles,
+    sprites: Option<&SpriteHandles>,
     asteroid_id_map: &mut AsteroidIdMap,
     planet_id_map: &mut PlanetIdMap,
     orbit_directions: &PrecomputedOrbitDirections,
@@ -49,6 +49,7 @@ pub fn spawn_sector(
                 commands,
                 asteroid_id_map,
                 sprites,
+                x.id,
                 "Asteroid".to_string(),
                 x.position,
                 &mut component,
@@ -71,21 +72,24 @@ pub fn spawn_sector(
         gravitation_well_mass = Some(star.mass);
         let simulation_transform = SimulationTransform::from_translation(position);
 
+        let sprite = sprites.map(|sprites| SpriteBundle {
+            transform: simulation_transform.as_transform(constants::z_layers::PLANET_AND_STARS),
+            texture: sprites.star.clone(),
+            ..Default::default()
+        });
+
         let star_entity = commands
             .spawn((
                 Name::new(format!("[{},{}] Star", coordinate.x, coordinate.y)),
                 components::Star::new(coordinate, star.mass),
                 InSector { sector },
                 SelectableEntity::Star,
-                SpriteBundle {
-                    transform: simulation_transform
-                        .as_transform(constants::z_layers::PLANET_AND_STARS),
-                    texture: sprites.star.clone(),
-                    ..Default::default()
-                },
                 simulation_transform,
             ))
             .id();
+        if let Some(sprite) = sprite {
+            commands.entity(star_entity).insert(sprite);
+        }
 
         commands.entity(sector_entity).insert(SectorStarComponent {
             entity: StarEntity::from(star_entity),
@@ -1,4 +1,4 @@
-use crate::components::{Engine, Inventory, Sector, SelectableEntity, Ship};
+use crate::components::{Engine, Inventory, Sector, SelectableEntity, Ship, ShipAccount};
 use crate::persistence::{PersistentShipId, ShipIdMap};
 use crate::simulation::physics::ShipVelocity;
 use crate::simulation::ship_ai::{BehaviorBuilder, TaskQueue};
@@ -11,7 +11,7 @@ use bevy::prelude::{default, Commands, Query, Rot2, SpriteBundle};
 
 pub fn spawn_ship(
     commands: &mut Commands,
-    sprites: &SpriteHandles,
+    sprites: Option<&SpriteHandles>,
     id: PersistentShipId,
     name: String,
     sector_query: &mut Query<&mut Sector>,
@@ -19,8 +19,9 @@ pub fn spawn_ship(
     position: Vec2,
     rotation: f32,
     behavior: &BehaviorBuilder,
+    account: ShipAccount,
     ship_id_map: &mut ShipIdMap,
-) {
+) -> ShipEntity {
     let mut sector_data = sector_query.get_mut(sector.into()).unwrap();
 
     let simulation_transform = SimulationTransform::new(
@@ -29,6 +30,12 @@ pub fn spawn_ship(
         1.0,
     );
 
+    let sprite = sprites.map(|sprites| SpriteBundle {
+        texture: sprites.ship.clone(),
+        transform: simulation_transform.as_transform(constants::z_layers::SHIP),
+        ..default()
+    });
+
     let entity = commands
         .spawn((
             Name::new(name),
@@ -36,19 +43,23 @@ pub fn spawn_ship(
             SelectableEntity::Ship,
             Engine::default(),
             ShipVelocity::default(),
-            Inventory::new(100),
+            Inventory::new(constants::SHIP_INVENTORY_SIZE),
             TaskQueue::new(),
-            SpriteBundle {
-                texture: sprites.ship.clone(),
-                transform: simulation_transform.as_transform(constants::z_layers::SHIP),
-                ..default()
-            },
             simulation_transform,
         ))
         .id();
+    if let Some(sprite) = sprite {
+        commands.entity(entity).insert(sprite);
+    }
+    match account {
+        ShipAccount::Wallet(wallet) => commands.entity(entity).insert(wallet),
+        ShipAccount::Owner(owner) => commands.entity(entity).insert(owner),
+    };
 
-    ship_id_map.insert(id, ShipEntity::from(entity));
+    let ship_entity = ShipEntity::from(entity);
+    ship_id_map.insert(id, ship_entity);
     behavior.build_and_add_default_component(commands.entity(entity));
 
-    sector_data.add_ship(commands, sector, ShipEntity::from(entity));
+    sector_data.add_ship(commands, sector, ship_entity);
+    ship_entity
 }
@@ -1,5 +1,6 @@
 use crate::components::{
-    BuyOrders, InteractionQueue, Inventory, Sector, SelectableEntity, SellOrders, Station,
+    BuyOrders, InteractionQueue, Inventory, Owner, Sector, SelectableEntity, SellOrders, Station,
+    StationIcon, Wallet,
 };
 use crate::game_data::ItemDefinition;
 use crate::persistence::{PersistentStationId, StationIdMap};
@@ -17,7 +18,7 @@ pub fn spawn_station(
     commands: &mut Commands,
     sector_query: &mut Query<&mut Sector>,
     station_id_map: &mut StationIdMap,
-    sprites: &SpriteHandles,
+    sprites: Option<&SpriteHandles>,
     id: PersistentStationId,
     name: &str,
     local_pos: Vec2,
@@ -26,31 +27,34 @@ pub fn spawn_station(
     sells: Vec<&ItemDefinition>,
     production: Option<ProductionComponent>,
     shipyard: Option<ShipyardComponent>,
+    owner: Option<Owner>,
+    wallet: Wallet,
 ) {
     let mut sector = sector_query.get_mut(sector_entity.into()).unwrap();
 
-    let icon_sprite = match sells.first() {
-        None => {
-            if shipyard.is_some() {
-                sprites.icon_ship.clone()
-            } else {
-                sprites.icon_unknown.clone()
-            }
-        }
-        Some(item) => match item.id {
-            // somehow matching with aliased-constants doesn't work?
-            1 => sprites.icon_item_a.clone(),
-            2 => sprites.icon_item_b.clone(),
-            3 => sprites.icon_item_c.clone(),
-            _ => sprites.icon_unknown.clone(),
-        },
-    };
-
     let simulation_transform = SimulationTransform::from_translation(local_pos + sector.world_pos);
 
-    let _icon_entity = commands
-        .spawn((
+    if let Some(sprites) = sprites {
+        let icon_sprite = match sells.first() {
+            None => {
+                if shipyard.is_some() {
+                    sprites.icon_ship.clone()
+                } else {
+                    sprites.icon_unknown.clone()
+                }
+            }
+            Some(item) => match item.id {
+                // somehow matching with aliased-constants doesn't work?
+                1 => sprites.icon_item_a.clone(),
+                2 => sprites.icon_item_b.clone(),
+                3 => sprites.icon_item_c.clone(),
+                _ => sprites.icon_unknown.clone(),
+            },
+        };
+
+        commands.spawn((
             Name::new(format!("{name} (Icon)")),
+            StationIcon,
             SpriteBundle {
                 texture: icon_sprite,
                 transform: simulation_transform.as_transform(constants::z_layers::STATION_ICON),
@@ -60,33 +64,47 @@ pub fn spawn_station(
                 },
                 ..default()
             },
-        ))
-        .id();
+        ));
+    }
+
+    let inventory = Inventory::new_with_content(
+        constants::MOCK_STATION_INVENTORY_SIZE,
+        sells
+            .iter()
+            .map(|x| (x.id, constants::MOCK_STATION_INVENTORY_SIZE))
+            .collect(),
+    );
+    let buy_orders = if buys.is_empty() {
+        None
+    } else {
+        let mut buy_orders = BuyOrders::mock(buys);
+        buy_orders.limit_to_budget(&wallet);
+        Some(buy_orders)
+    };
+
+    let sprite = sprites.map(|sprites| SpriteBundle {
+        texture: sprites.station.clone(),
+        transform: simulation_transform.as_transform(constants::z_layers::STATION),
+        ..default()
+    });
 
     let entity = commands
         .spawn((
             Name::new(name.to_string()),
             SelectableEntity::Station,
             Station::new(id),
-            SpriteBundle {
-                texture: sprites.station.clone(),
-                transform: simulation_transform.as_transform(constants::z_layers::STATION),
-                ..default()
-            },
-            Inventory::new_with_content(
-                constants::MOCK_STATION_INVENTORY_SIZE,
-                sells
-                    .iter()
-                    .map(|x| (x.id, constants::MOCK_STATION_INVENTORY_SIZE))
-                    .collect(),
-            ),
+            inventory,
             InteractionQueue::new(constants::SIMULTANEOUS_STATION_INTERACTIONS),
+            wallet,
             simulation_transform,
         ))
         .id();
 
-    if !buys.is_empty() {
-        commands.entity(entity).insert(BuyOrders::mock(buys));
+    if let Some(sprite) = sprite {
+        commands.entity(entity).insert(sprite);
+    }
+    if let Some(buy_orders) = buy_orders {
+        commands.entity(entity).insert(buy_orders);
     }
     if !sells.is_empty() {
         commands.entity(entity).insert(SellOrders::mock(sells));
@@ -100,6 +118,10 @@ pub fn spawn_station(
         commands.entity(entity).insert(shipyard);
     }
 
+    if let Some(owner) = owner {
+        commands.entity(entity).insert(owner);
+    }
+
     station_id_map.insert(id, StationEntity::from(entity));
     sector.add_station(commands, sector_entity, StationEntity::from(entity));
 }
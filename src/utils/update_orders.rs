@@ -1,15 +1,20 @@
 use crate::components::Inventory;
 use crate::components::SellOrders;
-use crate::components::{BuyOrders, TradeOrder};
+use crate::components::{BuyOrders, TradeOrder, Wallet};
 use bevy::prelude::Mut;
 
+/// Buy orders are limited to what `wallet` can afford, if there is one.
 pub fn update_orders(
     inventory: &Inventory,
+    wallet: Option<&Wallet>,
     buy_orders: Option<Mut<BuyOrders>>,
     sell_orders: Option<Mut<SellOrders>>,
 ) {
     if let Some(mut buy_orders) = buy_orders {
         buy_orders.update(inventory);
+        if let Some(wallet) = wallet {
+            buy_orders.limit_to_budget(wallet);
+        }
     }
     if let Some(mut sell_orders) = sell_orders {
         sell_orders.update(inventory);